@@ -0,0 +1,231 @@
+use anyhow::{anyhow, Result};
+use rocksdb::DB;
+use serde_json::json;
+
+/// BIC coin amounts are fixed-point integers with 9 decimals, whatever the token
+const TOKEN_DECIMALS: u32 = 9;
+
+/// 48-byte public key of a Base58 account
+pub fn parse_public_key(account: &str) -> Result<Vec<u8>> {
+    let public_key = bs58::decode(account)
+        .into_vec()
+        .map_err(|e| anyhow!("Invalid Base58 account {}: {}", account, e))?;
+    if public_key.len() != 48 {
+        return Err(anyhow!("Account {} decodes to {} bytes, expected a 48-byte public key", account, public_key.len()));
+    }
+    Ok(public_key)
+}
+
+/// Integer stored as a decimal string (how the node writes balances and nonces) or as an ETF integer
+pub fn parse_integer(value: &[u8]) -> Option<i128> {
+    if let Some(int) = std::str::from_utf8(value).ok().and_then(|text| text.parse().ok()) {
+        return Some(int);
+    }
+    match crate::parse_etf_to_json(value).ok()? {
+        serde_json::Value::Number(number) => number.as_i64().map(i128::from),
+        serde_json::Value::String(text) => text.parse().ok(),
+        _ => None,
+    }
+}
+
+/// `1234500000000` -> `1234.5`
+fn format_amount(amount: i128) -> String {
+    let scale = 10i128.pow(TOKEN_DECIMALS);
+    let sign = if amount < 0 { "-" } else { "" };
+    let (whole, fraction) = (amount.unsigned_abs() / scale as u128, amount.unsigned_abs() % scale as u128);
+    if fraction == 0 {
+        return format!("{}{}", sign, whole);
+    }
+    let fraction = format!("{:0width$}", fraction, width = TOKEN_DECIMALS as usize);
+    format!("{}{}.{}", sign, whole, fraction.trim_end_matches('0'))
+}
+
+fn value_json(value: &[u8]) -> serde_json::Value {
+    crate::parse_etf_to_json(value).unwrap_or_else(|_| json!(hex::encode(value)))
+}
+
+/// Newest `limit` entries of a nonce index for `public_key`, plus the total count
+fn nonce_index(db: &DB, cf_name: &str, public_key: &[u8], limit: usize) -> Result<serde_json::Value> {
+    let (count, entries) = crate::tx::nonce_index_entries(db, cf_name, public_key, limit)?;
+    let entries: Vec<_> = entries
+        .iter()
+        .map(|(nonce, tx_hash)| json!({ "nonce": nonce, "tx_hash": hex::encode(tx_hash) }))
+        .collect();
+    Ok(json!({ "count": count, "entries": entries }))
+}
+
+/// Everything the database holds about one account: its balances, nonce and contract accounts
+/// (read under their key prefixes), its tx nonce index entries and, with `all_keys`, every other
+/// contractstate key containing its public key (a full scan)
+pub fn show_account(db: &DB, account: &str, tx_limit: usize, all_keys: bool, json_output: bool) -> Result<()> {
+    let report = account_report(db, account, tx_limit, all_keys)?;
+    if json_output {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    let rows = |field: &str| report[field].as_array().cloned().unwrap_or_default();
+    let balances = rows("balances");
+    println!("👤 Account {}", account);
+    println!("{}", "=".repeat(80));
+    println!("{:<34} {}", "Public key (hex):", report["public_key_hex"].as_str().unwrap_or_default());
+    println!("{:<34} {}", "Nonce:", report["nonce"].as_str().unwrap_or("(none)"));
+
+    println!("\n💰 Balances ({})", balances.len());
+    for balance in &balances {
+        println!(
+            "   {:<12} {:>30}   raw {}",
+            balance["symbol"].as_str().unwrap_or_default(),
+            balance["amount"].as_str().unwrap_or("(not an integer)"),
+            balance["raw"].as_str().unwrap_or_default()
+        );
+    }
+    let mut sections = vec![("📜 Contract accounts", rows("contract_accounts"))];
+    if all_keys {
+        sections.push(("🔑 Other keys", rows("other_keys")));
+    }
+    for (title, rows) in sections {
+        println!("\n{} ({})", title, rows.len());
+        for row in rows {
+            println!("   {}", row["key"].as_str().unwrap_or_default());
+            println!("      {}", row["value"]);
+        }
+    }
+    for cf_name in ["tx_account_nonce", "tx_receiver_nonce"] {
+        let index = &report[cf_name];
+        let entries = index["entries"].as_array().map(Vec::as_slice).unwrap_or_default();
+        println!("\n🧾 {} ({} entries{})", cf_name, index["count"], if entries.len() < index["count"].as_u64().unwrap_or(0) as usize {
+            format!(", newest {} shown", entries.len())
+        } else {
+            String::new()
+        });
+        for entry in entries {
+            println!("   {:>20}   {}", entry["nonce"], entry["tx_hash"].as_str().unwrap_or_default());
+        }
+    }
+    println!("{}", "=".repeat(80));
+    Ok(())
+}
+
+/// The report `show_account` prints; `other_keys` is null unless `all_keys` scanned for them
+fn account_report(db: &DB, account: &str, tx_limit: usize, all_keys: bool) -> Result<serde_json::Value> {
+    let public_key = parse_public_key(account)?;
+    let contractstate_cf = db
+        .cf_handle("contractstate")
+        .ok_or_else(|| anyhow!("contractstate CF not found"))?;
+
+    let balance_prefix = [b"bic:coin:balance:".as_slice(), &public_key, b":"].concat();
+    let nonce_key = [b"bic:base:nonce:".as_slice(), &public_key].concat();
+    let contract_account_prefix = [b"bic:contract:account:".as_slice(), &public_key].concat();
+
+    let mut account_keys = Vec::new();
+    if all_keys {
+        for item in db.iterator_cf(&contractstate_cf, rocksdb::IteratorMode::Start) {
+            let (key, value) = item?;
+            if key.windows(public_key.len()).any(|window| window == public_key.as_slice()) {
+                account_keys.push((key.to_vec(), value.to_vec()));
+            }
+        }
+    } else {
+        for prefix in [&balance_prefix, &contract_account_prefix] {
+            let iter = db.iterator_cf(&contractstate_cf, rocksdb::IteratorMode::From(prefix, rocksdb::Direction::Forward));
+            for item in iter {
+                let (key, value) = item?;
+                if !key.starts_with(prefix) {
+                    break;
+                }
+                account_keys.push((key.to_vec(), value.to_vec()));
+            }
+        }
+        if let Some(value) = db.get_cf(&contractstate_cf, &nonce_key)? {
+            account_keys.push((nonce_key.clone(), value));
+        }
+    }
+
+    let mut balances = Vec::new();
+    let mut nonce = None;
+    let mut contract_accounts = Vec::new();
+    let mut other_keys = Vec::new();
+    for (key, value) in account_keys {
+        let decoded_key = crate::decode_contractstate_key(&key);
+        if let Some(symbol) = key.strip_prefix(balance_prefix.as_slice()) {
+            let symbol = String::from_utf8_lossy(symbol).to_string();
+            let amount = parse_integer(&value);
+            balances.push(json!({
+                "symbol": symbol,
+                "amount": amount.map(format_amount),
+                "raw": amount.map(|amount| amount.to_string()).unwrap_or_else(|| hex::encode(&value)),
+                "key": decoded_key,
+            }));
+        } else if key == nonce_key {
+            nonce = parse_integer(&value);
+        } else if key.starts_with(b"bic:contract:account:") {
+            contract_accounts.push(json!({ "key": decoded_key, "value": value_json(&value) }));
+        } else {
+            other_keys.push(json!({ "key": decoded_key, "value": value_json(&value) }));
+        }
+    }
+
+    Ok(json!({
+        "account": account,
+        "public_key_hex": hex::encode(&public_key),
+        "balances": balances,
+        "nonce": nonce.map(|nonce| nonce.to_string()),
+        "contract_accounts": contract_accounts,
+        "other_keys": if all_keys { json!(other_keys) } else { serde_json::Value::Null },
+        "tx_account_nonce": nonce_index(db, "tx_account_nonce", &public_key, tx_limit)?,
+        "tx_receiver_nonce": nonce_index(db, "tx_receiver_nonce", &public_key, tx_limit)?,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_amount_formatting_and_integers() {
+        assert_eq!(format_amount(5_000_000_000), "5");
+        assert_eq!(format_amount(1_234_500_000_000), "1234.5");
+        assert_eq!(format_amount(1), "0.000000001");
+        assert_eq!(format_amount(-2_500_000_000), "-2.5");
+        assert_eq!(parse_integer(b"280"), Some(280));
+        assert_eq!(parse_integer(&[131, 97, 7]), Some(7));
+        assert_eq!(parse_integer(b"abc"), None);
+        assert!(parse_public_key("1111").is_err());
+    }
+
+    #[test]
+    fn test_account_report_reads_prefixes() {
+        let path = std::env::temp_dir().join(format!("fabric_doctor_account_{}", std::process::id()));
+        let path = path.to_string_lossy().to_string();
+        let _ = std::fs::remove_dir_all(&path);
+        crate::create_target_database(&path).unwrap();
+        let db = crate::open_target_database_readwrite(&path).unwrap();
+        let contractstate = db.cf_handle("contractstate").unwrap();
+
+        let public_key = [9u8; 48];
+        let account = bs58::encode(public_key).into_string();
+        let key = |parts: &[&[u8]]| parts.concat();
+        db.put_cf(&contractstate, key(&[b"bic:coin:balance:", &public_key, b":AMA"]), b"2500000000").unwrap();
+        db.put_cf(&contractstate, key(&[b"bic:coin:balance:", &[8u8; 48], b":AMA"]), b"1").unwrap();
+        db.put_cf(&contractstate, key(&[b"bic:base:nonce:", &public_key]), b"7").unwrap();
+        db.put_cf(&contractstate, key(&[b"bic:contract:account:", &public_key, b":bytecode"]), b"code").unwrap();
+        db.put_cf(&contractstate, key(&[b"bic:epoch:pop:", &public_key]), b"pop").unwrap();
+
+        let report = account_report(&db, &account, 10, false).unwrap();
+        assert_eq!(report["balances"].as_array().unwrap().len(), 1);
+        assert_eq!(report["balances"][0]["amount"], "2.5");
+        assert_eq!(report["nonce"], "7");
+        assert_eq!(report["contract_accounts"].as_array().unwrap().len(), 1);
+        assert!(report["other_keys"].is_null());
+
+        let scanned = account_report(&db, &account, 10, true).unwrap();
+        assert_eq!(scanned["balances"], report["balances"]);
+        assert_eq!(scanned["nonce"], report["nonce"]);
+        assert_eq!(scanned["contract_accounts"], report["contract_accounts"]);
+        assert_eq!(scanned["other_keys"].as_array().unwrap().len(), 1);
+
+        drop(db);
+        let _ = std::fs::remove_dir_all(&path);
+    }
+}
@@ -0,0 +1,298 @@
+//! Key layouts of the `attestation` column family.
+//!
+//! Hashes and signers are stored as raw bytes, so the ':' separators cannot be used to split
+//! keys; every layout is decoded by its fixed field widths instead:
+//! - `attestation:{height:012}:<signer 48>:<mutations_hash 32>`
+//! - `attestation:{height:012}:<entry_hash 32>:<signer 48>:<mutations_hash 32>`
+//! - `my_attestation:<entry_hash 32>`
+//! - `consensus:<entry_hash 32>:<mutations_hash 32>`
+//!
+//! Only the `consensus:<entry_hash>:` prefix comes from the original migration code. The
+//! attestation layouts are the height + signer + mutations hash key described for the node, with
+//! or without the entry hash, and `my_attestation:` stands in for the legacy
+//! `my_attestation_for_entry|entryhash` column family. Commands that copy, move or delete attestation
+//! records call `check_layouts` first and stop with an error when the database doesn't use them.
+
+use anyhow::{anyhow, Result};
+use rocksdb::DB;
+
+/// Legacy column family holding the node's own attestation per entry, keyed by entry hash
+pub const LEGACY_MY_ATTESTATION_CF: &str = "my_attestation_for_entry|entryhash";
+pub const ATTESTATION_PREFIX: &[u8] = b"attestation:";
+pub const MY_ATTESTATION_PREFIX: &[u8] = b"my_attestation:";
+pub const CONSENSUS_PREFIX: &[u8] = b"consensus:";
+
+const HASH_LEN: usize = 32;
+const SIGNER_LEN: usize = 48;
+const HEIGHT_LEN: usize = 12;
+/// Keys read from the start of the column family and from each known prefix by `check_layouts`
+const LAYOUT_SAMPLE: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttestationKey {
+    Attestation {
+        height: u64,
+        /// `None` for keys without the entry hash, which are matched by height only
+        entry_hash: Option<Vec<u8>>,
+        signer: Vec<u8>,
+        mutations_hash: Vec<u8>,
+    },
+    MyAttestation {
+        entry_hash: Vec<u8>,
+    },
+    Consensus {
+        entry_hash: Vec<u8>,
+        mutations_hash: Vec<u8>,
+    },
+}
+
+impl AttestationKey {
+    pub fn entry_hash(&self) -> Option<&[u8]> {
+        match self {
+            AttestationKey::Attestation { entry_hash, .. } => entry_hash.as_deref(),
+            AttestationKey::MyAttestation { entry_hash } | AttestationKey::Consensus { entry_hash, .. } => Some(entry_hash),
+        }
+    }
+
+    /// Same key pointing at another entry hash; keys without one are returned unchanged
+    pub fn with_entry_hash(&self, new_entry_hash: &[u8]) -> AttestationKey {
+        let mut key = self.clone();
+        match &mut key {
+            AttestationKey::Attestation { entry_hash, .. } => {
+                if let Some(entry_hash) = entry_hash {
+                    *entry_hash = new_entry_hash.to_vec();
+                }
+            }
+            AttestationKey::MyAttestation { entry_hash }
+            | AttestationKey::Consensus { entry_hash, .. } => *entry_hash = new_entry_hash.to_vec(),
+        }
+        key
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            AttestationKey::Attestation { height, entry_hash, signer, mutations_hash } => {
+                let mut key = attestation_height_prefix(*height);
+                if let Some(entry_hash) = entry_hash {
+                    key.extend_from_slice(entry_hash);
+                    key.push(b':');
+                }
+                key.extend_from_slice(signer);
+                key.push(b':');
+                key.extend_from_slice(mutations_hash);
+                key
+            }
+            AttestationKey::MyAttestation { entry_hash } => my_attestation_key(entry_hash),
+            AttestationKey::Consensus { entry_hash, mutations_hash } => {
+                let mut key = CONSENSUS_PREFIX.to_vec();
+                key.extend_from_slice(entry_hash);
+                key.push(b':');
+                key.extend_from_slice(mutations_hash);
+                key
+            }
+        }
+    }
+}
+
+/// Reads `len` bytes at `pos`, optionally followed by a ':' separator
+fn take<'a>(key: &'a [u8], pos: &mut usize, len: usize, separator: bool) -> Option<&'a [u8]> {
+    let end = *pos + len;
+    if key.len() < end {
+        return None;
+    }
+    let field = &key[*pos..end];
+    *pos = end;
+    if separator {
+        if key.get(*pos) != Some(&b':') {
+            return None;
+        }
+        *pos += 1;
+    }
+    Some(field)
+}
+
+pub fn decode_attestation_key(key: &[u8]) -> Option<AttestationKey> {
+    if let Some(rest) = key.strip_prefix(ATTESTATION_PREFIX) {
+        let mut pos = 0;
+        let height_bytes = take(rest, &mut pos, HEIGHT_LEN, true)?;
+        if !height_bytes.iter().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let height = std::str::from_utf8(height_bytes).ok()?.parse().ok()?;
+        // The two layouts differ in length, so the remaining length tells whether the entry hash is there
+        let entry_hash = if rest.len() - pos == SIGNER_LEN + 1 + HASH_LEN {
+            None
+        } else {
+            Some(take(rest, &mut pos, HASH_LEN, true)?.to_vec())
+        };
+        let signer = take(rest, &mut pos, SIGNER_LEN, true)?.to_vec();
+        let mutations_hash = take(rest, &mut pos, HASH_LEN, false)?.to_vec();
+        if pos != rest.len() {
+            return None;
+        }
+        return Some(AttestationKey::Attestation { height, entry_hash, signer, mutations_hash });
+    }
+
+    if let Some(rest) = key.strip_prefix(MY_ATTESTATION_PREFIX) {
+        if rest.len() != HASH_LEN {
+            return None;
+        }
+        return Some(AttestationKey::MyAttestation { entry_hash: rest.to_vec() });
+    }
+
+    if let Some(rest) = key.strip_prefix(CONSENSUS_PREFIX) {
+        let mut pos = 0;
+        let entry_hash = take(rest, &mut pos, HASH_LEN, true)?.to_vec();
+        let mutations_hash = take(rest, &mut pos, HASH_LEN, false)?.to_vec();
+        if pos != rest.len() {
+            return None;
+        }
+        return Some(AttestationKey::Consensus { entry_hash, mutations_hash });
+    }
+
+    None
+}
+
+/// Sample the attestation CF and make sure its keys fit the layouts above before a command copies,
+/// moves or deletes records by them. Fails when the column family holds keys but none of the
+/// sampled ones fits, or when a key under one of the known prefixes doesn't fit its layout.
+pub fn check_layouts(db: &DB, attestation_cf: &impl rocksdb::AsColumnFamilyRef) -> Result<()> {
+    let mut matched = 0;
+    let mut unknown: Vec<Vec<u8>> = Vec::new();
+    for prefix in [&b""[..], ATTESTATION_PREFIX, CONSENSUS_PREFIX, MY_ATTESTATION_PREFIX] {
+        let iter = db.iterator_cf(attestation_cf, rocksdb::IteratorMode::From(prefix, rocksdb::Direction::Forward));
+        for item in iter.take(LAYOUT_SAMPLE) {
+            let (key, _) = item?;
+            if !key.starts_with(prefix) {
+                break;
+            }
+            if decode_attestation_key(&key).is_some() {
+                matched += 1;
+            } else if let Some(known) = [ATTESTATION_PREFIX, CONSENSUS_PREFIX, MY_ATTESTATION_PREFIX].iter().find(|known| key.starts_with(known)) {
+                return Err(anyhow!(
+                    "attestation key {} starts with {} but doesn't fit its layout; refusing to touch attestation records",
+                    hex::encode(&key),
+                    String::from_utf8_lossy(known)
+                ));
+            } else {
+                unknown.push(key.to_vec());
+            }
+        }
+    }
+    if matched == 0 && !unknown.is_empty() {
+        return Err(anyhow!(
+            "None of the {} sampled attestation keys fits a known layout (first: {}); refusing to touch attestation records",
+            unknown.len(),
+            hex::encode(&unknown[0])
+        ));
+    }
+    if !unknown.is_empty() {
+        println!("⚠️  {} sampled attestation keys have an unknown prefix and are left alone", unknown.len());
+    }
+    Ok(())
+}
+
+/// Prefix of all attestations signed for entries at `height`
+pub fn attestation_height_prefix(height: u64) -> Vec<u8> {
+    let mut prefix = ATTESTATION_PREFIX.to_vec();
+    prefix.extend_from_slice(format!("{:012}:", height).as_bytes());
+    prefix
+}
+
+pub fn my_attestation_key(entry_hash: &[u8]) -> Vec<u8> {
+    let mut key = MY_ATTESTATION_PREFIX.to_vec();
+    key.extend_from_slice(entry_hash);
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_attestation_key_with_separator_bytes_in_hash() {
+        let entry_hash = [b':'; 32];
+        let signer = [7u8; 48];
+        let mutations_hash = [9u8; 32];
+
+        let mut key = attestation_height_prefix(42);
+        key.extend_from_slice(&entry_hash);
+        key.push(b':');
+        key.extend_from_slice(&signer);
+        key.push(b':');
+        key.extend_from_slice(&mutations_hash);
+
+        assert_eq!(
+            decode_attestation_key(&key),
+            Some(AttestationKey::Attestation {
+                height: 42,
+                entry_hash: Some(entry_hash.to_vec()),
+                signer: signer.to_vec(),
+                mutations_hash: mutations_hash.to_vec(),
+            })
+        );
+
+        assert_eq!(decode_attestation_key(&key).unwrap().encode(), key);
+
+        key.push(0);
+        assert_eq!(decode_attestation_key(&key), None);
+    }
+
+    #[test]
+    fn test_decode_my_attestation_and_consensus_keys() {
+        let entry_hash = [1u8; 32];
+        assert_eq!(
+            decode_attestation_key(&my_attestation_key(&entry_hash)),
+            Some(AttestationKey::MyAttestation { entry_hash: entry_hash.to_vec() })
+        );
+
+        let mut consensus = CONSENSUS_PREFIX.to_vec();
+        consensus.extend_from_slice(&entry_hash);
+        consensus.push(b':');
+        consensus.extend_from_slice(&[2u8; 32]);
+        let decoded = decode_attestation_key(&consensus).unwrap();
+        assert_eq!(decoded.entry_hash(), Some(&entry_hash[..]));
+    }
+
+    #[test]
+    fn test_check_layouts() {
+        let path = std::env::temp_dir().join(format!("fabric_doctor_attestation_layouts_{}", std::process::id()));
+        let path = path.to_string_lossy().to_string();
+        let _ = std::fs::remove_dir_all(&path);
+        crate::create_target_database(&path).unwrap();
+        let db = crate::open_target_database_readwrite(&path).unwrap();
+        let attestation_cf = db.cf_handle("attestation").unwrap();
+
+        // An empty column family and keys of an unknown layout alone
+        assert!(check_layouts(&db, &attestation_cf).is_ok());
+        db.put_cf(&attestation_cf, b"att|42|something", b"").unwrap();
+        assert!(check_layouts(&db, &attestation_cf).is_err());
+
+        // Known keys next to unknown ones are fine, a known prefix with another layout is not
+        db.put_cf(&attestation_cf, my_attestation_key(&[1u8; 32]), b"").unwrap();
+        assert!(check_layouts(&db, &attestation_cf).is_ok());
+        let mut short = CONSENSUS_PREFIX.to_vec();
+        short.extend_from_slice(&[2u8; 16]);
+        db.put_cf(&attestation_cf, &short, b"").unwrap();
+        assert!(check_layouts(&db, &attestation_cf).is_err());
+
+        drop(db);
+        let _ = std::fs::remove_dir_all(&path);
+    }
+
+    #[test]
+    fn test_decode_attestation_key_without_entry_hash() {
+        let mut key = attestation_height_prefix(7);
+        key.extend_from_slice(&[3u8; 48]);
+        key.push(b':');
+        key.extend_from_slice(&[4u8; 32]);
+
+        let decoded = decode_attestation_key(&key).unwrap();
+        assert_eq!(
+            decoded,
+            AttestationKey::Attestation { height: 7, entry_hash: None, signer: vec![3u8; 48], mutations_hash: vec![4u8; 32] }
+        );
+        assert_eq!(decoded.encode(), key);
+        assert_eq!(decoded.with_entry_hash(&[5u8; 32]), decoded);
+    }
+}
@@ -0,0 +1,239 @@
+use anyhow::{anyhow, Result};
+use rocksdb::{IngestExternalFileOptions, Options, SstFileWriter, DB};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::digest::DigestBuilder;
+
+/// SST files are rolled over after this many bytes of keys and values
+const MAX_SST_BYTES: u64 = 256 * 1024 * 1024;
+
+/// kvs written through WriteBatch into a scratch database to measure the rate SST ingestion is
+/// compared against
+const BASELINE_SAMPLE_KVS: u64 = 100_000;
+
+/// kvs and bytes copied and how long it took
+pub struct Throughput {
+    pub count: u64,
+    pub bytes: u64,
+    pub elapsed: Duration,
+}
+
+impl Throughput {
+    fn seconds(&self) -> f64 {
+        self.elapsed.as_secs_f64().max(0.001)
+    }
+
+    fn kvs_per_second(&self) -> f64 {
+        self.count as f64 / self.seconds()
+    }
+}
+
+/// Copy a whole column family by writing its (already sorted) keys into external SST files
+/// and ingesting them, bypassing the memtable, WAL and the compactions that follow.
+/// Returns the throughput of the copy and, with `benchmark`, of a WriteBatch baseline measured
+/// on a sample of the CF.
+pub fn copy_column_family_sst(
+    source_db: &DB,
+    source_cf: &impl rocksdb::AsColumnFamilyRef,
+    target_db: &DB,
+    target_cf: &impl rocksdb::AsColumnFamilyRef,
+    cf_name: &str,
+    source_digest: &mut DigestBuilder,
+    benchmark: bool,
+) -> Result<(Throughput, Option<Throughput>)> {
+    let work_dir = work_dir(target_db.path(), cf_name);
+    std::fs::create_dir_all(work_dir.parent().unwrap_or(Path::new(".")))?;
+    // Never reuse (and so never delete) a directory this run didn't create
+    std::fs::create_dir(&work_dir)
+        .map_err(|e| anyhow!("Failed to create bulk load work directory {}: {}", work_dir.display(), e))?;
+
+    let result = (|| {
+        let baseline = if benchmark {
+            Some(measure_write_batch_baseline(source_db, source_cf, &work_dir.join("write_batch_baseline"))?)
+        } else {
+            None
+        };
+        let started = Instant::now();
+        let (count, bytes) = write_and_ingest(source_db, source_cf, target_db, target_cf, source_digest, &work_dir)?;
+        Ok((Throughput { count, bytes, elapsed: started.elapsed() }, baseline))
+    })();
+    let _ = std::fs::remove_dir_all(&work_dir);
+    result
+}
+
+/// Scratch directory next to the target database (same filesystem, so ingestion can move the
+/// SST files), never inside it
+fn work_dir(target_path: &Path, cf_name: &str) -> PathBuf {
+    let cf_name = cf_name.replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+    match target_path.file_name() {
+        Some(target_name) => target_path.with_file_name(format!(
+            "{}.bulk_load_{}_{}",
+            target_name.to_string_lossy(),
+            cf_name,
+            std::process::id()
+        )),
+        None => std::env::temp_dir().join(format!("fabric_doctor_bulk_load_{}_{}", cf_name, std::process::id())),
+    }
+}
+
+/// Time copying the first BASELINE_SAMPLE_KVS kvs through WriteBatch, 1000 at a time as
+/// migrate_column_family_full does, into a scratch database at `path`
+fn measure_write_batch_baseline(source_db: &DB, source_cf: &impl rocksdb::AsColumnFamilyRef, path: &Path) -> Result<Throughput> {
+    let scratch_db = DB::open_default(path)?;
+    let started = Instant::now();
+    let mut count = 0u64;
+    let mut bytes = 0u64;
+    let mut write_batch = rocksdb::WriteBatch::default();
+    for item in source_db.iterator_cf(source_cf, rocksdb::IteratorMode::Start).take(BASELINE_SAMPLE_KVS as usize) {
+        let (key, value) = item?;
+        write_batch.put(&key, &value);
+        count += 1;
+        bytes += (key.len() + value.len()) as u64;
+        if write_batch.len() >= 1000 {
+            scratch_db.write(std::mem::take(&mut write_batch))?;
+        }
+    }
+    if !write_batch.is_empty() {
+        scratch_db.write(write_batch)?;
+    }
+    Ok(Throughput { count, bytes, elapsed: started.elapsed() })
+}
+
+fn write_and_ingest(
+    source_db: &DB,
+    source_cf: &impl rocksdb::AsColumnFamilyRef,
+    target_db: &DB,
+    target_cf: &impl rocksdb::AsColumnFamilyRef,
+    source_digest: &mut DigestBuilder,
+    work_dir: &Path,
+) -> Result<(u64, u64)> {
+    let opts = Options::default();
+    let mut iter = source_db.iterator_cf(source_cf, rocksdb::IteratorMode::Start);
+    let mut files: Vec<PathBuf> = Vec::new();
+    let mut count = 0u64;
+    let mut bytes = 0u64;
+
+    loop {
+        let path = work_dir.join(format!("{:06}.sst", files.len()));
+        let mut writer = SstFileWriter::create(&opts);
+        writer
+            .open(&path)
+            .map_err(|e| anyhow!("Failed to create SST file {}: {}", path.display(), e))?;
+
+        let mut file_kvs = 0u64;
+        let mut file_bytes = 0u64;
+        let mut exhausted = true;
+        for item in iter.by_ref() {
+            let (key, value) = item?;
+            source_digest.update(&key, &value);
+            writer.put(&key, &value)?;
+            file_kvs += 1;
+            file_bytes += (key.len() + value.len()) as u64;
+            if file_bytes >= MAX_SST_BYTES {
+                exhausted = false;
+                break;
+            }
+        }
+
+        // An SST file cannot be finished without entries
+        if file_kvs == 0 {
+            break;
+        }
+        writer.finish()?;
+        count += file_kvs;
+        bytes += file_bytes;
+        println!("📦 Wrote SST file {} ({} kvs, {} so far)", path.display(), file_kvs, count);
+        files.push(path);
+
+        if exhausted {
+            break;
+        }
+    }
+
+    if !files.is_empty() {
+        println!("📥 Ingesting {} SST files...", files.len());
+        let mut ingest_opts = IngestExternalFileOptions::default();
+        ingest_opts.set_move_files(true);
+        target_db.ingest_external_file_cf_opts(target_cf, &ingest_opts, files)?;
+    }
+    Ok((count, bytes))
+}
+
+/// One line with kvs/s and MB/s, printed for both load modes so runs can be compared
+pub fn print_throughput(cf_name: &str, mode: &str, throughput: &Throughput) {
+    let megabytes = throughput.bytes as f64 / (1024.0 * 1024.0);
+    println!(
+        "⏱️  {} copied via {}: {} kvs, {:.1} MB in {:.2}s ({:.0} kvs/s, {:.1} MB/s)",
+        cf_name,
+        mode,
+        throughput.count,
+        megabytes,
+        throughput.seconds(),
+        throughput.kvs_per_second(),
+        megabytes / throughput.seconds()
+    );
+}
+
+/// SST ingestion rate relative to the WriteBatch baseline
+pub fn print_speedup(cf_name: &str, sst: &Throughput, baseline: &Throughput) {
+    if sst.count == 0 || baseline.count == 0 {
+        return;
+    }
+    println!(
+        "⏱️  {} WriteBatch baseline ({} kvs sampled): {:.0} kvs/s, SST ingestion is {:.2}x that",
+        cf_name,
+        baseline.count,
+        baseline.kvs_per_second(),
+        sst.kvs_per_second() / baseline.kvs_per_second()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_column_family_sst_round_trip() {
+        let dir = std::env::temp_dir().join(format!("fabric_doctor_bulk_load_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let source_path = dir.join("source").to_string_lossy().to_string();
+        let target_path = dir.join("target").to_string_lossy().to_string();
+        crate::create_target_database(&source_path).unwrap();
+        crate::create_target_database(&target_path).unwrap();
+
+        let source_db = crate::open_target_database_readwrite(&source_path).unwrap();
+        let target_db = crate::open_target_database_readwrite(&target_path).unwrap();
+        let source_cf = source_db.cf_handle("contractstate").unwrap();
+        let target_cf = target_db.cf_handle("contractstate").unwrap();
+        let kvs: Vec<(Vec<u8>, Vec<u8>)> = (0u32..2500)
+            .map(|i| (format!("bic:coin:balance:{:08}", i).into_bytes(), i.to_be_bytes().to_vec()))
+            .collect();
+        for (key, value) in &kvs {
+            source_db.put_cf(&source_cf, key, value).unwrap();
+        }
+
+        let mut source_digest = DigestBuilder::new("contractstate", crate::digest::DEFAULT_RANGE_SIZE);
+        let (sst, baseline) =
+            copy_column_family_sst(&source_db, &source_cf, &target_db, &target_cf, "contractstate", &mut source_digest, true)
+                .unwrap();
+        assert_eq!(sst.count, kvs.len() as u64);
+        assert_eq!(baseline.unwrap().count, kvs.len() as u64);
+
+        let copied: Vec<(Vec<u8>, Vec<u8>)> = target_db
+            .iterator_cf(&target_cf, rocksdb::IteratorMode::Start)
+            .map(|item| item.map(|(key, value)| (key.to_vec(), value.to_vec())).unwrap())
+            .collect();
+        assert_eq!(copied, kvs);
+        let target_digest =
+            crate::digest::digest_column_family(&target_db, &target_cf, "contractstate", crate::digest::DEFAULT_RANGE_SIZE).unwrap();
+        assert_eq!(source_digest.finish().hex(), target_digest.hex());
+        // The work directory sat next to the target and is gone; the target holds only RocksDB files
+        assert!(!work_dir(target_db.path(), "contractstate").exists());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+        assert!(std::fs::read_dir(target_db.path()).unwrap().all(|entry| entry.unwrap().file_type().unwrap().is_file()));
+
+        drop((source_db, target_db));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
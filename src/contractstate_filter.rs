@@ -0,0 +1,241 @@
+use anyhow::{anyhow, Result};
+use rocksdb::DB;
+use std::collections::BTreeMap;
+
+/// Selects which contractstate keys a filtered `migrate weak` copies.
+///
+/// Patterns are matched against the decoded key as printed by `decode_contractstate_key`
+/// (e.g. `bic:coin:balance:<Base58 pk>:AMA`). A pattern without `*` is a prefix, `*` matches
+/// any run of characters.
+#[derive(Debug, Default)]
+pub struct ContractstateFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+    /// Base58 form as given, raw 48-byte public key
+    accounts: Vec<(String, Vec<u8>)>,
+    /// Keep about one key in N, chosen by key hash so reruns select the same keys
+    sample_one_in: Option<u64>,
+}
+
+impl ContractstateFilter {
+    pub fn new(include: Vec<String>, exclude: Vec<String>, accounts: Vec<String>, sample_one_in: Option<u64>) -> Result<Self> {
+        let accounts = accounts
+            .into_iter()
+            .map(|account| {
+                let public_key = crate::account::parse_public_key(&account)?;
+                Ok((account, public_key))
+            })
+            .collect::<Result<_>>()?;
+        if sample_one_in == Some(0) {
+            return Err(anyhow!("--sample must be at least 1"));
+        }
+        Ok(ContractstateFilter { include, exclude, accounts, sample_one_in })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty() && self.accounts.is_empty() && self.sample_one_in.is_none()
+    }
+
+    fn selects(&self, key: &[u8], decoded: &str) -> bool {
+        let included = (self.include.is_empty() && self.accounts.is_empty())
+            || self.include.iter().any(|pattern| pattern_matches(pattern, decoded))
+            || self
+                .accounts
+                .iter()
+                .any(|(_, public_key)| key.windows(public_key.len()).any(|window| window == public_key.as_slice()));
+        included && !self.exclude.iter().any(|pattern| pattern_matches(pattern, decoded))
+    }
+
+    fn sampled(&self, key: &[u8]) -> bool {
+        match self.sample_one_in {
+            Some(n) => {
+                let hash = blake3::hash(key);
+                u64::from_le_bytes(hash.as_bytes()[..8].try_into().unwrap()) % n == 0
+            }
+            None => true,
+        }
+    }
+
+    /// Filter settings as recorded in the migration manifest
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "include": self.include,
+            "exclude": self.exclude,
+            "accounts": self.accounts.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            "sample_one_in": self.sample_one_in,
+        })
+    }
+
+    pub fn print(&self) {
+        println!("🔎 Contractstate filter:");
+        if !self.include.is_empty() {
+            println!("   - Include: {}", self.include.join(", "));
+        }
+        if !self.accounts.is_empty() {
+            let names: Vec<&str> = self.accounts.iter().map(|(name, _)| name.as_str()).collect();
+            println!("   - Accounts: {}", names.join(", "));
+        }
+        if !self.exclude.is_empty() {
+            println!("   - Exclude: {}", self.exclude.join(", "));
+        }
+        if let Some(n) = self.sample_one_in {
+            println!("   - Sample: 1 in {}", n);
+        }
+    }
+}
+
+/// Prefix match, or glob match when the pattern contains `*`
+fn pattern_matches(pattern: &str, decoded: &str) -> bool {
+    if !pattern.contains('*') {
+        return decoded.starts_with(pattern);
+    }
+
+    let pattern = pattern.as_bytes();
+    let text = decoded.as_bytes();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == b'*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&b| b == b'*')
+}
+
+/// Group used in the report: the first two segments of the decoded key (`bic:coin:`)
+fn key_group(decoded: &str) -> String {
+    let mut end = 0;
+    for (segments, (i, _)) in decoded.match_indices(':').enumerate() {
+        end = i + 1;
+        if segments == 1 {
+            break;
+        }
+    }
+    if end == 0 {
+        decoded.to_string()
+    } else {
+        decoded[..end].to_string()
+    }
+}
+
+/// Copy the contractstate keys selected by `filter` from source to target
+pub fn migrate_contractstate_filtered(source_db: &DB, target_db: &DB, filter: &ContractstateFilter) -> Result<()> {
+    println!("🔄 Migrating contractstate (filtered)...");
+    filter.print();
+
+    let source_cf = source_db
+        .cf_handle("contractstate")
+        .ok_or_else(|| anyhow!("contractstate CF not found in source"))?;
+    let target_cf = target_db
+        .cf_handle("contractstate")
+        .ok_or_else(|| anyhow!("contractstate CF not found in target"))?;
+
+    let mut scanned = 0u64;
+    let mut copied = 0u64;
+    let mut not_selected = 0u64;
+    let mut not_sampled = 0u64;
+    let mut copied_by_group: BTreeMap<String, u64> = BTreeMap::new();
+    let mut write_batch = rocksdb::WriteBatch::default();
+
+    for item in source_db.iterator_cf(&source_cf, rocksdb::IteratorMode::Start) {
+        let (key, value) = item?;
+        scanned += 1;
+
+        let decoded = crate::decode_contractstate_key(&key);
+        if !filter.selects(&key, &decoded) {
+            not_selected += 1;
+            continue;
+        }
+        if !filter.sampled(&key) {
+            not_sampled += 1;
+            continue;
+        }
+
+        write_batch.put_cf(&target_cf, &key, &value);
+        copied += 1;
+        *copied_by_group.entry(key_group(&decoded)).or_insert(0) += 1;
+
+        if write_batch.len() >= 1000 {
+            target_db.write(std::mem::take(&mut write_batch))?;
+            println!("📦 Migrated {} kvs so far...", copied);
+        }
+    }
+
+    if !write_batch.is_empty() {
+        target_db.write(write_batch)?;
+    }
+
+    // Every copied key must now be readable from the target
+    let mut missing = 0u64;
+    for item in source_db.iterator_cf(&source_cf, rocksdb::IteratorMode::Start) {
+        let (key, value) = item?;
+        let decoded = crate::decode_contractstate_key(&key);
+        if filter.selects(&key, &decoded) && filter.sampled(&key) && target_db.get_cf(&target_cf, &key)?.as_deref() != Some(value.as_ref()) {
+            missing += 1;
+        }
+    }
+    if missing > 0 {
+        return Err(anyhow!("{} selected contractstate keys are missing or differ in the target", missing));
+    }
+
+    println!("✅ Filtered contractstate migration complete:");
+    println!("   - Keys scanned: {}", scanned);
+    println!("   - Keys copied: {}", copied);
+    println!("   - Not matching filter: {}", not_selected);
+    if filter.sample_one_in.is_some() {
+        println!("   - Left out by sampling: {}", not_sampled);
+    }
+    for (group, count) in &copied_by_group {
+        println!("   - {}: {}", group, count);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_matches() {
+        assert!(pattern_matches("bic:coin:", "bic:coin:balance:abc:AMA"));
+        assert!(!pattern_matches("bic:epoch:", "bic:coin:balance:abc:AMA"));
+        assert!(pattern_matches("bic:coin:balance:*:AMA", "bic:coin:balance:abc:AMA"));
+        assert!(!pattern_matches("bic:coin:balance:*:USDC", "bic:coin:balance:abc:AMA"));
+        assert!(pattern_matches("*segment_vr_hash", "bic:epoch:segment_vr_hash"));
+        assert_eq!(key_group("bic:coin:balance:abc:AMA"), "bic:coin:");
+        assert_eq!(key_group("plainkey"), "plainkey");
+    }
+
+    #[test]
+    fn test_filter_selection() {
+        let public_key = vec![7u8; 48];
+        let account = bs58::encode(&public_key).into_string();
+        let filter = ContractstateFilter::new(
+            vec!["bic:epoch:".to_string()],
+            vec!["bic:epoch:trainers:*".to_string()],
+            vec![account],
+            None,
+        )
+        .unwrap();
+
+        let mut balance_key = b"bic:coin:balance:".to_vec();
+        balance_key.extend_from_slice(&public_key);
+        balance_key.extend_from_slice(b":AMA");
+        assert!(filter.selects(&balance_key, &crate::decode_contractstate_key(&balance_key)));
+        assert!(filter.selects(b"bic:epoch:segment_vr_hash", "bic:epoch:segment_vr_hash"));
+        assert!(!filter.selects(b"bic:epoch:trainers:000000000001", "bic:epoch:trainers:000000000001"));
+        assert!(!filter.selects(b"bic:coin:total", "bic:coin:total"));
+
+        assert!(ContractstateFilter::new(vec![], vec![], vec!["notbase58!".to_string()], None).is_err());
+    }
+}
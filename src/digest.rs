@@ -0,0 +1,514 @@
+use anyhow::{anyhow, Result};
+use rocksdb::DB;
+
+/// Number of keys covered by each range digest, used to localize mismatches
+pub const DEFAULT_RANGE_SIZE: u64 = 100_000;
+
+/// Digest of a contiguous run of keys inside a column family
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeDigest {
+    /// First key of the range in the source; ranges digested against a source keep its boundaries
+    pub start_key: Vec<u8>,
+    pub end_key: Vec<u8>,
+    pub key_count: u64,
+    pub digest: [u8; 32],
+}
+
+/// Digest of a whole column family plus its per-range digests
+#[derive(Debug, Clone)]
+pub struct CfDigest {
+    pub cf_name: String,
+    pub key_count: u64,
+    pub byte_count: u64,
+    pub digest: [u8; 32],
+    pub ranges: Vec<RangeDigest>,
+}
+
+impl CfDigest {
+    pub fn hex(&self) -> String {
+        hex::encode(self.digest)
+    }
+
+    /// Keys at which the ranges start, to cut another column family's digest at the same places
+    pub fn boundaries(&self) -> Vec<Vec<u8>> {
+        self.ranges.iter().map(|range| range.start_key.clone()).collect()
+    }
+
+    /// Index of the first range whose digest differs from `other`, if any
+    pub fn first_mismatching_range(&self, other: &CfDigest) -> Option<usize> {
+        let shared = self.ranges.len().min(other.ranges.len());
+        for i in 0..shared {
+            if self.ranges[i] != other.ranges[i] {
+                return Some(i);
+            }
+        }
+        if self.ranges.len() != other.ranges.len() {
+            return Some(shared);
+        }
+        None
+    }
+}
+
+/// Streaming blake3 digest over ordered (key, value) pairs.
+/// Each pair is length-prefixed so that key/value boundaries cannot be shifted.
+/// Ranges are cut every `range_size` keys, or at fixed `boundaries` so that a target is digested
+/// over the same key ranges as its source and one extra or missing key only affects its own range.
+pub struct DigestBuilder {
+    cf_name: String,
+    range_size: u64,
+    boundaries: Option<Vec<Vec<u8>>>,
+    next_boundary: usize,
+    total: blake3::Hasher,
+    range: blake3::Hasher,
+    range_start: Option<Vec<u8>>,
+    range_end: Vec<u8>,
+    range_count: u64,
+    key_count: u64,
+    byte_count: u64,
+    ranges: Vec<RangeDigest>,
+}
+
+impl DigestBuilder {
+    pub fn new(cf_name: &str, range_size: u64) -> Self {
+        DigestBuilder {
+            cf_name: cf_name.to_string(),
+            range_size: range_size.max(1),
+            boundaries: None,
+            next_boundary: 0,
+            total: blake3::Hasher::new(),
+            range: blake3::Hasher::new(),
+            range_start: None,
+            range_end: Vec::new(),
+            range_count: 0,
+            key_count: 0,
+            byte_count: 0,
+            ranges: Vec::new(),
+        }
+    }
+
+    /// Cut ranges at `boundaries` (sorted start keys, usually `CfDigest::boundaries` of the source)
+    /// instead of by key count. Keys below the first boundary fall into the first range and every
+    /// boundary gets a range, empty or not, so range indexes line up with the source's.
+    pub fn with_boundaries(cf_name: &str, boundaries: Vec<Vec<u8>>) -> Self {
+        DigestBuilder { boundaries: Some(boundaries), ..DigestBuilder::new(cf_name, u64::MAX) }
+    }
+
+    pub fn update(&mut self, key: &[u8], value: &[u8]) {
+        if let Some(boundaries) = self.boundaries.take() {
+            // Open every range up to the key's one; the ones it skips stay empty
+            let index = boundaries.partition_point(|boundary| boundary.as_slice() <= key).max(1) - 1;
+            while self.next_boundary <= index {
+                self.close_range();
+                self.range_start = Some(boundaries.get(self.next_boundary).cloned().unwrap_or_default());
+                self.next_boundary += 1;
+            }
+            self.boundaries = Some(boundaries);
+        }
+
+        for hasher in [&mut self.total, &mut self.range] {
+            hasher.update(&(key.len() as u64).to_le_bytes());
+            hasher.update(key);
+            hasher.update(&(value.len() as u64).to_le_bytes());
+            hasher.update(value);
+        }
+
+        if self.range_start.is_none() {
+            self.range_start = Some(key.to_vec());
+        }
+        self.range_end = key.to_vec();
+        self.range_count += 1;
+        self.key_count += 1;
+        self.byte_count += (key.len() + value.len()) as u64;
+
+        if self.boundaries.is_none() && self.range_count >= self.range_size {
+            self.close_range();
+        }
+    }
+
+    fn close_range(&mut self) {
+        if let Some(start_key) = self.range_start.take() {
+            let hasher = std::mem::replace(&mut self.range, blake3::Hasher::new());
+            self.ranges.push(RangeDigest {
+                start_key,
+                end_key: std::mem::take(&mut self.range_end),
+                key_count: self.range_count,
+                digest: *hasher.finalize().as_bytes(),
+            });
+            self.range_count = 0;
+        }
+    }
+
+    pub fn finish(mut self) -> CfDigest {
+        self.close_range();
+        // Boundaries no key reached are empty ranges
+        let unreached = self.boundaries.as_ref().and_then(|boundaries| boundaries.get(self.next_boundary..));
+        for start_key in unreached.into_iter().flatten() {
+            self.ranges.push(RangeDigest {
+                start_key: start_key.clone(),
+                end_key: Vec::new(),
+                key_count: 0,
+                digest: *blake3::Hasher::new().finalize().as_bytes(),
+            });
+        }
+        CfDigest {
+            cf_name: self.cf_name,
+            key_count: self.key_count,
+            byte_count: self.byte_count,
+            digest: *self.total.finalize().as_bytes(),
+            ranges: self.ranges,
+        }
+    }
+}
+
+/// Compute the digest of a column family with a single ordered scan
+pub fn digest_column_family(
+    db: &DB,
+    cf: &impl rocksdb::AsColumnFamilyRef,
+    cf_name: &str,
+    range_size: u64,
+) -> Result<CfDigest> {
+    let mut builder = DigestBuilder::new(cf_name, range_size);
+    let iter = db.iterator_cf(cf, rocksdb::IteratorMode::Start);
+    for item in iter {
+        let (key, value) = item?;
+        builder.update(&key, &value);
+    }
+    Ok(builder.finish())
+}
+
+/// Compute the digest of a column family over the same key ranges as `source`, so a mismatch is
+/// localized to the ranges that actually differ
+pub fn digest_column_family_like(db: &DB, cf: &impl rocksdb::AsColumnFamilyRef, source: &CfDigest) -> Result<CfDigest> {
+    let mut builder = DigestBuilder::with_boundaries(&source.cf_name, source.boundaries());
+    for item in db.iterator_cf(cf, rocksdb::IteratorMode::Start) {
+        let (key, value) = item?;
+        builder.update(&key, &value);
+    }
+    Ok(builder.finish())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyDifference {
+    ValueMismatch(Vec<u8>),
+    MissingInTarget(Vec<u8>),
+    ExtraInTarget(Vec<u8>),
+}
+
+impl KeyDifference {
+    pub fn key(&self) -> &[u8] {
+        match self {
+            KeyDifference::ValueMismatch(key)
+            | KeyDifference::MissingInTarget(key)
+            | KeyDifference::ExtraInTarget(key) => key,
+        }
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match self {
+            KeyDifference::ValueMismatch(_) => "value differs",
+            KeyDifference::MissingInTarget(_) => "missing in target",
+            KeyDifference::ExtraInTarget(_) => "only in target",
+        }
+    }
+}
+
+/// Summary of the differences found from the first mismatching range onwards
+#[derive(Debug, Default)]
+pub struct DiffSummary {
+    pub first: Option<KeyDifference>,
+    pub value_mismatches: u64,
+    pub missing_in_target: u64,
+    pub extra_in_target: u64,
+}
+
+/// Walk both column families in key order starting at the first mismatching range
+/// and classify every differing key. `max_keys` bounds how far the walk continues
+/// once the first difference has been found.
+pub fn diff_column_families(
+    source_db: &DB,
+    source_cf: &impl rocksdb::AsColumnFamilyRef,
+    source_digest: &CfDigest,
+    target_db: &DB,
+    target_cf: &impl rocksdb::AsColumnFamilyRef,
+    target_digest: &CfDigest,
+    max_keys: Option<u64>,
+) -> Result<DiffSummary> {
+    let mut summary = DiffSummary::default();
+
+    let range_index = match source_digest.first_mismatching_range(target_digest) {
+        Some(index) => index,
+        None => return Ok(summary),
+    };
+
+    // Both sides agree on everything before this key; the first range also holds any target keys
+    // below the source's first key
+    let start_key = match (range_index, source_digest.ranges.get(range_index), target_digest.ranges.get(range_index)) {
+        (0, _, _) => Vec::new(),
+        (_, Some(s), Some(t)) => s.start_key.clone().min(t.start_key.clone()),
+        (_, Some(s), None) => s.start_key.clone(),
+        (_, None, Some(t)) => t.start_key.clone(),
+        (_, None, None) => return Err(anyhow!("digest ranges disagree but no range found at index {}", range_index)),
+    };
+
+    let mode = rocksdb::IteratorMode::From(&start_key, rocksdb::Direction::Forward);
+    let mut source_iter = source_db.iterator_cf(source_cf, mode).peekable();
+    let mut target_iter = target_db.iterator_cf(target_cf, mode).peekable();
+    let mut walked = 0u64;
+
+    loop {
+        if let (Some(limit), Some(_)) = (max_keys, &summary.first) {
+            if walked >= limit {
+                break;
+            }
+        }
+
+        let difference = match (source_iter.peek(), target_iter.peek()) {
+            (None, None) => break,
+            (Some(Err(_)), _) => return Err(anyhow!("{}", source_iter.next().unwrap().unwrap_err())),
+            (_, Some(Err(_))) => return Err(anyhow!("{}", target_iter.next().unwrap().unwrap_err())),
+            (Some(Ok((source_key, _))), None) => {
+                let key = source_key.to_vec();
+                source_iter.next();
+                Some(KeyDifference::MissingInTarget(key))
+            }
+            (None, Some(Ok((target_key, _)))) => {
+                let key = target_key.to_vec();
+                target_iter.next();
+                Some(KeyDifference::ExtraInTarget(key))
+            }
+            (Some(Ok((source_key, source_value))), Some(Ok((target_key, target_value)))) => {
+                match source_key.cmp(target_key) {
+                    std::cmp::Ordering::Less => {
+                        let key = source_key.to_vec();
+                        source_iter.next();
+                        Some(KeyDifference::MissingInTarget(key))
+                    }
+                    std::cmp::Ordering::Greater => {
+                        let key = target_key.to_vec();
+                        target_iter.next();
+                        Some(KeyDifference::ExtraInTarget(key))
+                    }
+                    std::cmp::Ordering::Equal => {
+                        let difference = if source_value != target_value {
+                            Some(KeyDifference::ValueMismatch(source_key.to_vec()))
+                        } else {
+                            None
+                        };
+                        source_iter.next();
+                        target_iter.next();
+                        difference
+                    }
+                }
+            }
+        };
+
+        if summary.first.is_some() {
+            walked += 1;
+        }
+
+        if let Some(difference) = difference {
+            match &difference {
+                KeyDifference::ValueMismatch(_) => summary.value_mismatches += 1,
+                KeyDifference::MissingInTarget(_) => summary.missing_in_target += 1,
+                KeyDifference::ExtraInTarget(_) => summary.extra_in_target += 1,
+            }
+            if summary.first.is_none() {
+                summary.first = Some(difference);
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Compare every column family present in both databases and report the first differing key.
+/// Fails when a column family that should match differs: every shared one, or, when the target
+/// carries a migration manifest, the ones its command copied in full. `expect_differ` names
+/// column families that may differ anyway.
+pub fn verify_databases(source_db_path: &str, target_db_path: &str, expect_differ: &[String]) -> Result<()> {
+    println!("🔍 Verifying {} against {} by content digest", target_db_path, source_db_path);
+
+    let source_db = crate::open_source_database_readonly(source_db_path)?;
+    let target_db = crate::open_source_database_readonly(target_db_path)?;
+
+    let copied_in_full = match crate::manifest::read_manifest(&target_db)? {
+        Some(manifest) => {
+            let copied_in_full = manifest.fully_copied_cfs();
+            match &copied_in_full {
+                Some(cfs) => println!("📜 Target produced by `{}`, expecting only {:?} to match", manifest.command, cfs),
+                None => println!("📜 Target produced by `{}`, expecting every shared column family to match", manifest.command),
+            }
+            copied_in_full
+        }
+        None => None,
+    };
+    let expected_to_match = |cf_name: &String| {
+        !expect_differ.contains(cf_name) && copied_in_full.as_ref().is_none_or(|cfs| cfs.contains(cf_name))
+    };
+
+    let mut cf_names = DB::list_cf(&rocksdb::Options::default(), source_db_path)?;
+    cf_names.sort();
+
+    let mut unexpected = Vec::new();
+    let mut expected = 0;
+
+    for cf_name in &cf_names {
+        let (Some(source_cf), Some(target_cf)) = (source_db.cf_handle(cf_name), target_db.cf_handle(cf_name)) else {
+            println!("⏭️  {}: not present in target, skipped", cf_name);
+            continue;
+        };
+
+        let source_digest = digest_column_family(&source_db, &source_cf, cf_name, DEFAULT_RANGE_SIZE)?;
+        let target_digest = digest_column_family_like(&target_db, &target_cf, &source_digest)?;
+
+        if source_digest.digest == target_digest.digest {
+            println!(
+                "✅ {}: {} kvs, {} bytes, digest {}",
+                source_digest.cf_name, source_digest.key_count, source_digest.byte_count, source_digest.hex()
+            );
+            continue;
+        }
+
+        if cf_name == "sysconf" && sysconf_matches_apart_from_written_keys(&source_db, &target_db)? {
+            println!("✅ {}: identical apart from the heights and manifest written by migrations", cf_name);
+            continue;
+        }
+
+        let summary = diff_column_families(
+            &source_db, &source_cf, &source_digest,
+            &target_db, &target_cf, &target_digest,
+            Some(DEFAULT_RANGE_SIZE),
+        )?;
+        if expected_to_match(cf_name) {
+            unexpected.push(cf_name.clone());
+            println!("❌ {}: digests differ", cf_name);
+        } else {
+            expected += 1;
+            println!("ℹ️  {}: digests differ (not copied in full, expected)", cf_name);
+        }
+        println!("   - Source: {} kvs, digest {}", source_digest.key_count, source_digest.hex());
+        println!("   - Target: {} kvs, digest {}", target_digest.key_count, target_digest.hex());
+        print_diff_summary(&summary);
+    }
+
+    if !unexpected.is_empty() {
+        return Err(anyhow!("{} column families differ from the source: {}", unexpected.len(), unexpected.join(", ")));
+    }
+    if expected > 0 {
+        println!("🎉 Every column family expected to match is identical ({} others differ as expected)", expected);
+    } else {
+        println!("🎉 All shared column families are identical");
+    }
+
+    Ok(())
+}
+
+/// Whether the two sysconf column families hold the same keys and values, ignoring the heights
+/// and the manifest that migrations write into the target
+fn sysconf_matches_apart_from_written_keys(source_db: &DB, target_db: &DB) -> Result<bool> {
+    let written = [crate::sysconf::MIGRATION_MANIFEST, crate::sysconf::TEMPORAL_HEIGHT, crate::sysconf::ROOTED_HEIGHT];
+    let read = |db: &DB| -> Result<std::collections::BTreeMap<Vec<u8>, Vec<u8>>> {
+        let cf = db.cf_handle("sysconf").ok_or_else(|| anyhow!("sysconf CF not found"))?;
+        let mut records = std::collections::BTreeMap::new();
+        for item in db.iterator_cf(&cf, rocksdb::IteratorMode::Start) {
+            let (key, value) = item?;
+            if !written.iter().any(|name| name.as_bytes() == key.as_ref()) {
+                records.insert(key.to_vec(), value.to_vec());
+            }
+        }
+        Ok(records)
+    };
+    Ok(read(source_db)? == read(target_db)?)
+}
+
+pub fn print_diff_summary(summary: &DiffSummary) {
+    if let Some(first) = &summary.first {
+        println!(
+            "   - First differing key ({}): {} [{}]",
+            first.describe(),
+            crate::decode_contractstate_key(first.key()),
+            hex::encode(first.key())
+        );
+    }
+    println!(
+        "   - From there: {} changed, {} missing in target, {} only in target",
+        summary.value_mismatches, summary.missing_in_target, summary.extra_in_target
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest_of(pairs: &[(&[u8], &[u8])], range_size: u64) -> CfDigest {
+        let mut builder = DigestBuilder::new("test", range_size);
+        for (key, value) in pairs {
+            builder.update(key, value);
+        }
+        builder.finish()
+    }
+
+    #[test]
+    fn test_digest_detects_shifted_boundaries() {
+        let a = digest_of(&[(b"ab", b"c")], 10);
+        let b = digest_of(&[(b"a", b"bc")], 10);
+        assert_ne!(a.digest, b.digest);
+    }
+
+    #[test]
+    fn test_ranges_split_by_key_count() {
+        let d = digest_of(&[(b"a", b"1"), (b"b", b"2"), (b"c", b"3")], 2);
+        assert_eq!(d.key_count, 3);
+        assert_eq!(d.ranges.len(), 2);
+        assert_eq!(d.ranges[0].start_key, b"a");
+        assert_eq!(d.ranges[0].end_key, b"b");
+        assert_eq!(d.ranges[1].start_key, b"c");
+        assert_eq!(d.ranges[1].key_count, 1);
+    }
+
+    #[test]
+    fn test_first_mismatching_range() {
+        let a = digest_of(&[(b"a", b"1"), (b"b", b"2"), (b"c", b"3"), (b"d", b"4")], 2);
+        let b = digest_of(&[(b"a", b"1"), (b"b", b"2"), (b"c", b"X"), (b"d", b"4")], 2);
+        assert_eq!(a.first_mismatching_range(&a), None);
+        assert_eq!(a.first_mismatching_range(&b), Some(1));
+
+        let shorter = digest_of(&[(b"a", b"1"), (b"b", b"2")], 2);
+        assert_eq!(a.first_mismatching_range(&shorter), Some(1));
+    }
+
+    fn digest_like(pairs: &[(&[u8], &[u8])], source: &CfDigest) -> CfDigest {
+        let mut builder = DigestBuilder::with_boundaries("test", source.boundaries());
+        for (key, value) in pairs {
+            builder.update(key, value);
+        }
+        builder.finish()
+    }
+
+    #[test]
+    fn test_ranges_at_source_boundaries_localize_extra_and_missing_keys() {
+        let pairs: Vec<(&[u8], &[u8])> = vec![(b"a", b"1"), (b"b", b"2"), (b"c", b"3"), (b"d", b"4"), (b"e", b"5"), (b"f", b"6")];
+        let source = digest_of(&pairs, 2);
+        assert_eq!(source.boundaries(), vec![b"a".to_vec(), b"c".to_vec(), b"e".to_vec()]);
+        assert_eq!(digest_like(&pairs, &source).ranges, source.ranges);
+
+        // An extra key early on only changes its own range, later ones still match
+        let mut extra = pairs.clone();
+        extra.insert(1, (b"aa", b"x"));
+        let target = digest_like(&extra, &source);
+        assert_eq!(target.ranges.len(), 3);
+        assert_ne!(target.ranges[0], source.ranges[0]);
+        assert_eq!(target.ranges[1..], source.ranges[1..]);
+
+        // So does a missing key, and a whole missing range leaves an empty one in its place
+        let target = digest_like(&[(b"a", b"1"), (b"b", b"2"), (b"e", b"5"), (b"f", b"6")], &source);
+        assert_eq!(source.first_mismatching_range(&target), Some(1));
+        assert_eq!(target.ranges[1].key_count, 0);
+        assert_eq!(target.ranges[2], source.ranges[2]);
+
+        // Keys below the source's first key land in the first range
+        let target = digest_like(&[(b"0", b"x"), (b"a", b"1"), (b"b", b"2"), (b"c", b"3"), (b"d", b"4"), (b"e", b"5"), (b"f", b"6")], &source);
+        assert_eq!(source.first_mismatching_range(&target), Some(0));
+        assert_eq!(target.ranges[1..], source.ranges[1..]);
+    }
+}
@@ -0,0 +1,255 @@
+use anyhow::{anyhow, Result};
+
+pub const BY_HEIGHT_PREFIX: &[u8] = b"by_height:";
+pub const BY_SLOT_PREFIX: &[u8] = b"by_slot:";
+
+/// `by_height:{012}:<binary hash>` as written by the node
+pub fn height_index_key(height: u64, entry_hash: &[u8]) -> Vec<u8> {
+    index_key(BY_HEIGHT_PREFIX, height, entry_hash)
+}
+
+/// `by_slot:{012}:<binary hash>` as written by the node
+pub fn slot_index_key(slot: u64, entry_hash: &[u8]) -> Vec<u8> {
+    index_key(BY_SLOT_PREFIX, slot, entry_hash)
+}
+
+fn index_key(prefix: &[u8], number: u64, entry_hash: &[u8]) -> Vec<u8> {
+    let mut key = prefix.to_vec();
+    key.extend_from_slice(format!("{:012}:", number).as_bytes());
+    key.extend_from_slice(entry_hash);
+    key
+}
+
+/// Decode an index key into (prefix, number, hash bytes). The hash part is returned as stored,
+/// so callers can tell binary hashes (32 bytes) from hex-encoded ones (64 ASCII bytes).
+pub fn decode_index_key(key: &[u8]) -> Option<(&'static [u8], u64, &[u8])> {
+    let prefix = if key.starts_with(BY_HEIGHT_PREFIX) {
+        BY_HEIGHT_PREFIX
+    } else if key.starts_with(BY_SLOT_PREFIX) {
+        BY_SLOT_PREFIX
+    } else {
+        return None;
+    };
+
+    let rest = &key[prefix.len()..];
+    if rest.len() < 13 || rest[12] != b':' || !rest[..12].iter().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let number = std::str::from_utf8(&rest[..12]).ok()?.parse().ok()?;
+    Some((prefix, number, &rest[13..]))
+}
+
+/// Human-readable form of an index key, with the hash part shown as hex
+pub fn describe_index_key(key: &[u8]) -> String {
+    match decode_index_key(key) {
+        Some((prefix, number, hash)) => match std::str::from_utf8(hash) {
+            Ok(text) if hash.len() == 64 => format!("{}{:012}:{}", String::from_utf8_lossy(prefix), number, text),
+            _ => format!("{}{:012}:{}", String::from_utf8_lossy(prefix), number, hex::encode(hash)),
+        },
+        None => format!("hex:{}", hex::encode(key)),
+    }
+}
+
+#[derive(Debug, Default)]
+struct ReindexStats {
+    entries_scanned: u64,
+    undecodable_entries: u64,
+    hash_mismatches: u64,
+    height_keys_written: u64,
+    slot_keys_written: u64,
+    index_keys_checked: u64,
+    malformed_keys: u64,
+    hex_encoded_keys: u64,
+    missing_entry_keys: u64,
+    wrong_number_keys: u64,
+    value_mismatch_keys: u64,
+}
+
+/// Rebuild `by_height` (and `by_slot`, when the database uses it) from the entry column family
+/// and remove index keys that point to missing entries or use the wrong format.
+pub fn reindex_entries(db_path: &str, dry_run: bool) -> Result<()> {
+    println!("🔄 Rebuilding entry_meta indexes from entry CF{}", if dry_run { " (dry run)" } else { "" });
+
+    let db = if dry_run {
+        crate::open_source_database_readonly(db_path)?
+    } else {
+        crate::open_target_database_readwrite(db_path)?
+    };
+    let entry_cf = db
+        .cf_handle("entry")
+        .ok_or_else(|| anyhow!("entry CF not found"))?;
+    let entry_meta_cf = db
+        .cf_handle("entry_meta")
+        .ok_or_else(|| anyhow!("entry_meta CF not found"))?;
+
+    let with_slot_index = db
+        .iterator_cf(&entry_meta_cf, rocksdb::IteratorMode::From(BY_SLOT_PREFIX, rocksdb::Direction::Forward))
+        .next()
+        .transpose()?
+        .is_some_and(|(key, _)| key.starts_with(BY_SLOT_PREFIX));
+    println!("ℹ️  Slot index {}", if with_slot_index { "present, will be rebuilt" } else { "not used by this database" });
+
+    let mut stats = ReindexStats::default();
+    let mut write_batch = rocksdb::WriteBatch::default();
+    let batch_size = 1000;
+    let mut pending = 0;
+
+    // Pass 1: every entry gets its index keys
+    println!("📦 Pass 1: scanning entries...");
+    let iter = db.iterator_cf(&entry_cf, rocksdb::IteratorMode::Start);
+    for item in iter {
+        let (entry_hash, entry_data) = item?;
+        stats.entries_scanned += 1;
+
+        let (height, slot, computed_hash) = match crate::parse_entry_metadata(&entry_data) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                stats.undecodable_entries += 1;
+                println!("⚠️  Could not decode entry {}: {}", hex::encode(&entry_hash), e);
+                continue;
+            }
+        };
+
+        if computed_hash != entry_hash.as_ref() {
+            stats.hash_mismatches += 1;
+            println!(
+                "⚠️  Entry stored under {} hashes to {} (indexed under stored key)",
+                hex::encode(&entry_hash),
+                hex::encode(&computed_hash)
+            );
+        }
+
+        let mut keys = vec![(height_index_key(height, &entry_hash), true)];
+        if with_slot_index {
+            keys.push((slot_index_key(slot, &entry_hash), false));
+        }
+
+        for (key, is_height) in keys {
+            if db.get_cf(&entry_meta_cf, &key)?.as_deref() == Some(entry_hash.as_ref()) {
+                continue;
+            }
+            if is_height {
+                stats.height_keys_written += 1;
+            } else {
+                stats.slot_keys_written += 1;
+            }
+            if !dry_run {
+                write_batch.put_cf(&entry_meta_cf, &key, &entry_hash);
+                pending += 1;
+            }
+        }
+
+        if pending >= batch_size {
+            db.write(std::mem::take(&mut write_batch))?;
+            pending = 0;
+        }
+
+        if stats.entries_scanned % 100_000 == 0 {
+            println!("📦 Scanned {} entries...", stats.entries_scanned);
+        }
+    }
+
+    if !write_batch.is_empty() {
+        db.write(std::mem::take(&mut write_batch))?;
+        pending = 0;
+    }
+
+    // Pass 2: every index key must point at an existing entry with the same height/slot
+    println!("📦 Pass 2: checking index keys...");
+    for prefix in [BY_HEIGHT_PREFIX, BY_SLOT_PREFIX] {
+        let iter = db.iterator_cf(&entry_meta_cf, rocksdb::IteratorMode::From(prefix, rocksdb::Direction::Forward));
+        for item in iter {
+            let (key, value) = item?;
+            if !key.starts_with(prefix) {
+                break;
+            }
+            stats.index_keys_checked += 1;
+
+            // (reason, replacement value) for keys that need fixing; `None` replacement deletes the key
+            let problem: Option<(&str, Option<Vec<u8>>)> = match decode_index_key(&key) {
+                None => {
+                    stats.malformed_keys += 1;
+                    Some(("malformed key", None))
+                }
+                Some((_, _, hash)) if hash.len() == 64 && hash.iter().all(|b| b.is_ascii_hexdigit()) => {
+                    stats.hex_encoded_keys += 1;
+                    Some(("hex-encoded hash", None))
+                }
+                Some((_, number, hash)) => match db.get_cf(&entry_cf, hash)? {
+                    None => {
+                        stats.missing_entry_keys += 1;
+                        Some(("entry missing", None))
+                    }
+                    Some(entry_data) => match crate::parse_entry_metadata(&entry_data) {
+                        Ok((height, slot, _)) => {
+                            let expected = if prefix == BY_HEIGHT_PREFIX { height } else { slot };
+                            if number != expected {
+                                stats.wrong_number_keys += 1;
+                                Some(("height/slot differs from entry header", None))
+                            } else if value.as_ref() != hash {
+                                stats.value_mismatch_keys += 1;
+                                Some(("value differs from key hash", Some(hash.to_vec())))
+                            } else {
+                                None
+                            }
+                        }
+                        // Undecodable entries were already reported in pass 1, keep their index
+                        Err(_) => None,
+                    },
+                },
+            };
+
+            if let Some((reason, replacement)) = problem {
+                println!("🗑️  Stray index key {} ({})", describe_index_key(&key), reason);
+                if !dry_run {
+                    match replacement {
+                        Some(value) => write_batch.put_cf(&entry_meta_cf, &key, value),
+                        None => write_batch.delete_cf(&entry_meta_cf, &key),
+                    }
+                    pending += 1;
+                    if pending >= batch_size {
+                        db.write(std::mem::take(&mut write_batch))?;
+                        pending = 0;
+                    }
+                }
+            }
+        }
+    }
+
+    if !write_batch.is_empty() {
+        db.write(write_batch)?;
+    }
+
+    let stray = stats.malformed_keys + stats.hex_encoded_keys + stats.missing_entry_keys + stats.wrong_number_keys;
+    println!("✅ Reindex complete{}:", if dry_run { " (dry run, nothing written)" } else { "" });
+    println!("   - Entries scanned: {}", stats.entries_scanned);
+    println!("   - Undecodable entries: {}", stats.undecodable_entries);
+    println!("   - Entries whose hash differs from their key: {}", stats.hash_mismatches);
+    println!("   - by_height keys (re)written: {}", stats.height_keys_written);
+    println!("   - by_slot keys (re)written: {}", stats.slot_keys_written);
+    println!("   - Index keys checked: {}", stats.index_keys_checked);
+    println!("   - Stray index keys {}: {}", if dry_run { "found" } else { "removed" }, stray);
+    println!("     - malformed: {}", stats.malformed_keys);
+    println!("     - hex-encoded hash: {}", stats.hex_encoded_keys);
+    println!("     - entry missing: {}", stats.missing_entry_keys);
+    println!("     - height/slot mismatch: {}", stats.wrong_number_keys);
+    println!("   - Index values {}: {}", if dry_run { "to fix" } else { "fixed" }, stats.value_mismatch_keys);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_key_round_trip() {
+        let hash = [b':'; 32];
+        let key = height_index_key(39434469, &hash);
+        assert_eq!(&key[..23], b"by_height:000039434469:");
+        assert_eq!(decode_index_key(&key), Some((BY_HEIGHT_PREFIX, 39434469, &hash[..])));
+
+        let key = slot_index_key(7, &hash);
+        assert_eq!(decode_index_key(&key), Some((BY_SLOT_PREFIX, 7, &hash[..])));
+        assert_eq!(decode_index_key(b"by_height:12:abc"), None);
+    }
+}
@@ -0,0 +1,426 @@
+use anyhow::{anyhow, Result};
+use amadeus_fabric_doctor::vecpak::{self, Term as VTerm};
+use eetf::Term;
+use rocksdb::DB;
+use serde_json::json;
+
+use crate::entry_index;
+use crate::mutation::{self, Mutation};
+use crate::reencode::etf_to_vecpak;
+use crate::tx::{self, etf_binary, etf_get, vecpak_binary, vecpak_get};
+
+/// How the entry to show is found
+#[derive(Debug, Clone)]
+pub enum EntrySelector {
+    Hash(Vec<u8>),
+    Height(u64),
+    Slot(u64),
+}
+
+/// Sections added to the entry's header, transactions and hashes
+#[derive(Debug, Default)]
+pub struct EntrySections {
+    pub muts: bool,
+    pub muts_rev: bool,
+    pub consensus: bool,
+    pub raw: bool,
+}
+
+/// An entry decoded into the same shape whether it is stored as ETF or vecpak
+struct EntryView {
+    format: &'static str,
+    header_format: &'static str,
+    header_bin: Vec<u8>,
+    header: VTerm,
+    stored_hash: Option<Vec<u8>>,
+    signature: Option<Vec<u8>>,
+    /// Mask bytes and its length in bits
+    mask: Option<(Vec<u8>, u64)>,
+    /// Fields other than header, hash, signature, mask and txs
+    other_fields: Vec<(String, VTerm)>,
+}
+
+const KNOWN_FIELDS: &[&str] = &["header", "hash", "signature", "mask", "txs"];
+
+fn decode_entry(entry_data: &[u8]) -> Result<EntryView> {
+    if let Ok(entry @ VTerm::PropList(_)) = vecpak::decode_term_from_slice(entry_data) {
+        let (header_format, header_bin, header) = match vecpak_get(&entry, b"header") {
+            Some(VTerm::Binary(header_bin)) => match vecpak::decode_term_from_slice(header_bin) {
+                Ok(header) => ("vecpak", header_bin.clone(), header),
+                Err(_) => ("ETF", header_bin.clone(), etf_to_vecpak(&Term::decode(&header_bin[..])?)?),
+            },
+            Some(header @ VTerm::PropList(_)) => {
+                let mut header_bin = Vec::new();
+                vecpak::encode_term(&mut header_bin, header.clone());
+                ("vecpak (inline)", header_bin, header.clone())
+            }
+            _ => return Err(anyhow!("vecpak entry has no header")),
+        };
+        let VTerm::PropList(props) = &entry else { unreachable!() };
+        let other_fields = props
+            .iter()
+            .filter_map(|(key, value)| match key {
+                VTerm::Binary(name) if !KNOWN_FIELDS.contains(&String::from_utf8_lossy(name).as_ref()) => {
+                    Some((String::from_utf8_lossy(name).to_string(), value.clone()))
+                }
+                _ => None,
+            })
+            .collect();
+        return Ok(EntryView {
+            format: "vecpak",
+            header_format,
+            header_bin,
+            header,
+            stored_hash: vecpak_binary(&entry, b"hash"),
+            signature: vecpak_binary(&entry, b"signature"),
+            mask: vecpak_binary(&entry, b"mask").map(|mask| {
+                let bits = mask.len() as u64 * 8;
+                (mask, bits)
+            }),
+            other_fields,
+        });
+    }
+
+    let entry = Term::decode(entry_data).map_err(|e| anyhow!("Entry is neither vecpak nor ETF: {}", e))?;
+    let Term::Map(map) = &entry else {
+        return Err(anyhow!("ETF entry is not a map"));
+    };
+    let header_bin = etf_binary(&entry, "header").ok_or_else(|| anyhow!("ETF entry has no header"))?;
+    let header = etf_to_vecpak(&Term::decode(&header_bin[..])?)?;
+    let mask = match etf_get(&entry, "mask") {
+        Some(Term::Binary(binary)) => Some((binary.bytes.clone(), binary.bytes.len() as u64 * 8)),
+        Some(Term::BitBinary(bits)) => {
+            let full_bytes = bits.bytes.len().saturating_sub(1) as u64;
+            Some((bits.bytes.clone(), full_bytes * 8 + bits.tail_bits_size as u64))
+        }
+        _ => None,
+    };
+    let other_fields = map
+        .map
+        .iter()
+        .filter_map(|(key, value)| match key {
+            Term::Atom(atom) if !KNOWN_FIELDS.contains(&atom.name.as_str()) => {
+                Some((atom.name.clone(), etf_to_vecpak(value).unwrap_or_else(|_| VTerm::Binary(value.to_string().into_bytes()))))
+            }
+            _ => None,
+        })
+        .collect();
+    Ok(EntryView {
+        format: "ETF",
+        header_format: "ETF",
+        header_bin,
+        header,
+        stored_hash: etf_binary(&entry, "hash"),
+        signature: etf_binary(&entry, "signature"),
+        mask,
+        other_fields,
+    })
+}
+
+/// 48-byte binaries (public keys) as Base58, printable binaries as text, others as hex
+fn vterm_json(term: &VTerm) -> serde_json::Value {
+    match term {
+        VTerm::Nil() => json!(null),
+        VTerm::Bool(flag) => json!(flag),
+        VTerm::VarInt(n) => i64::try_from(*n).map(|n| json!(n)).unwrap_or_else(|_| json!(n.to_string())),
+        VTerm::Binary(bytes) if bytes.len() == 48 => json!(bs58::encode(bytes).into_string()),
+        VTerm::Binary(bytes) => match std::str::from_utf8(bytes) {
+            Ok(text) if !text.is_empty() && !text.chars().any(char::is_control) => json!(text),
+            _ => json!(hex::encode(bytes)),
+        },
+        VTerm::List(items) => json!(items.iter().map(vterm_json).collect::<Vec<_>>()),
+        VTerm::PropList(props) => {
+            let mut object = serde_json::Map::new();
+            for (key, value) in props {
+                let key = match key {
+                    VTerm::Binary(name) => String::from_utf8_lossy(name).to_string(),
+                    other => vterm_json(other).to_string(),
+                };
+                object.insert(key, vterm_json(value));
+            }
+            json!(object)
+        }
+    }
+}
+
+/// A stored value (mutation value, consensus record) decoded like contractstate values, falling
+/// back to vecpak and then hex
+fn value_json(value: &[u8]) -> serde_json::Value {
+    if value.starts_with(&[131]) || std::str::from_utf8(value).is_ok() {
+        if let Ok(parsed) = crate::parse_etf_to_json(value) {
+            return parsed;
+        }
+    }
+    match vecpak::decode_term_from_slice(value) {
+        Ok(term) => vterm_json(&term),
+        Err(_) => json!(hex::encode(value)),
+    }
+}
+
+fn find_offset(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() {
+        return None;
+    }
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+/// Classic hex dump: offset, 16 bytes in hex, printable ASCII
+fn hex_dump(data: &[u8]) -> Vec<String> {
+    data.chunks(16)
+        .enumerate()
+        .map(|(row, chunk)| {
+            let hex: Vec<String> = chunk.iter().map(|byte| format!("{:02x}", byte)).collect();
+            let ascii: String = chunk
+                .iter()
+                .map(|&byte| if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' })
+                .collect();
+            format!("{:08x}  {:<47}  |{}|", row * 16, hex.join(" "), ascii)
+        })
+        .collect()
+}
+
+/// Hashes of the entries indexed at a height or slot, forks included
+fn indexed_hashes(db: &DB, prefix: &[u8], number: u64) -> Result<Vec<Vec<u8>>> {
+    let entry_meta_cf = db
+        .cf_handle("entry_meta")
+        .ok_or_else(|| anyhow!("entry_meta CF not found"))?;
+    let mut index_prefix = prefix.to_vec();
+    index_prefix.extend_from_slice(format!("{:012}:", number).as_bytes());
+
+    let mut hashes = Vec::new();
+    for item in db.iterator_cf(&entry_meta_cf, rocksdb::IteratorMode::From(&index_prefix, rocksdb::Direction::Forward)) {
+        let (key, _) = item?;
+        if !key.starts_with(&index_prefix) {
+            break;
+        }
+        if let Some((_, _, hash)) = entry_index::decode_index_key(&key) {
+            // Older databases store the hash part hex-encoded
+            match hex::decode(hash) {
+                Ok(decoded) if hash.len() == 64 => hashes.push(decoded),
+                _ => hashes.push(hash.to_vec()),
+            }
+        }
+    }
+    Ok(hashes)
+}
+
+fn print_mutations(db: &DB, entry_meta_cf: &impl rocksdb::AsColumnFamilyRef, entry_hash: &[u8], suffix: &str) -> Result<()> {
+    let key = [b"entry:".as_slice(), entry_hash, b":", suffix.as_bytes()].concat();
+    let Some(data) = db.get_cf(entry_meta_cf, &key)? else {
+        println!("\n🔀 {}: (none)", suffix);
+        return Ok(());
+    };
+    match mutation::decode_mutations(&data) {
+        Ok(mutations) => {
+            println!("\n🔀 {} ({} mutations, {} bytes)", suffix, mutations.len(), data.len());
+            for (position, mutation) in mutations.iter().enumerate() {
+                let key = crate::decode_contractstate_key(mutation.key());
+                match mutation {
+                    Mutation::Put { value, .. } => println!("   [{}] put {} = {}", position, key, value_json(value)),
+                    Mutation::Delete { .. } => println!("   [{}] delete {}", position, key),
+                    Mutation::SetBit { bit, bloom_size, .. } => println!("   [{}] set_bit {} bit {} of {}", position, key, bit, bloom_size),
+                    Mutation::ClearBit { bit, .. } => println!("   [{}] clear_bit {} bit {}", position, key, bit),
+                }
+            }
+        }
+        Err(e) => println!("\n🔀 {} ({} bytes, undecodable: {}): {}", suffix, data.len(), e, hex::encode(&data)),
+    }
+    Ok(())
+}
+
+fn print_entry(db: &DB, entry_hash: &[u8], entry_data: &[u8], sections: &EntrySections) -> Result<()> {
+    let view = decode_entry(entry_data)?;
+    let computed_hash = blake3::hash(&view.header_bin).as_bytes().to_vec();
+
+    println!("📦 Entry {}", hex::encode(entry_hash));
+    println!("{}", "=".repeat(80));
+    println!("{:<20} {} (header {})", "Format:", view.format, view.header_format);
+    println!("{:<20} {} bytes (header {} bytes)", "Size:", entry_data.len(), view.header_bin.len());
+    println!("{:<20} {}", "Stored hash:", view.stored_hash.as_deref().map(hex::encode).unwrap_or_else(|| "(none)".to_string()));
+    println!(
+        "{:<20} {} {}",
+        "Computed hash:",
+        hex::encode(&computed_hash),
+        match &view.stored_hash {
+            Some(stored) if *stored == computed_hash => "✅",
+            Some(_) => "❌ differs from the stored hash",
+            None => "",
+        }
+    );
+    if computed_hash != entry_hash {
+        println!("{:<20} ⚠️  the entry is stored under a different key than its computed hash", "");
+    }
+    println!("{:<20} {}", "Signature:", view.signature.as_deref().map(hex::encode).unwrap_or_else(|| "(none)".to_string()));
+    match &view.mask {
+        Some((mask, bits)) => {
+            let set: u32 = mask.iter().map(|byte| byte.count_ones()).sum();
+            println!("{:<20} {} ({} of {} bits set)", "Mask:", hex::encode(mask), set, bits);
+        }
+        None => println!("{:<20} (none)", "Mask:"),
+    }
+
+    let (height, slot, _) = crate::parse_entry_metadata(entry_data)?;
+    let entry_meta_cf = db
+        .cf_handle("entry_meta")
+        .ok_or_else(|| anyhow!("entry_meta CF not found"))?;
+    // Index keys hold the binary hash or, in some databases, its hex form
+    let hex_hash = hex::encode(entry_hash);
+    let indexed = |index_key: fn(u64, &[u8]) -> Vec<u8>, at: u64| -> Result<&'static str> {
+        let found = db.get_cf(&entry_meta_cf, index_key(at, entry_hash))?.is_some()
+            || db.get_cf(&entry_meta_cf, index_key(at, hex_hash.as_bytes()))?.is_some();
+        Ok(if found { "✅" } else { "❌" })
+    };
+    println!(
+        "{:<20} by_height {} by_slot {}",
+        "Indexed:",
+        indexed(entry_index::height_index_key, height)?,
+        indexed(entry_index::slot_index_key, slot)?
+    );
+
+    println!("\n🧾 Header");
+    if let VTerm::PropList(props) = &view.header {
+        for (key, value) in props {
+            let name = match key {
+                VTerm::Binary(name) => String::from_utf8_lossy(name).to_string(),
+                other => vterm_json(other).to_string(),
+            };
+            println!("   {:<16} {}", name, vterm_json(value));
+        }
+    }
+    for (name, value) in &view.other_fields {
+        println!("   {:<16} {} (entry field)", name, vterm_json(value));
+    }
+
+    let txs = tx::entry_txs(entry_data).unwrap_or_default();
+    println!("\n💸 Transactions ({})", txs.len());
+    for (position, packed) in txs.iter().enumerate() {
+        match tx::decode_tx(packed) {
+            Ok(decoded) => {
+                println!(
+                    "   [{}] {} {} signer {} nonce {}",
+                    position,
+                    hex::encode(&decoded.hash),
+                    if decoded.hash_matches() { "✅" } else { "❌" },
+                    bs58::encode(&decoded.signer).into_string(),
+                    decoded.nonce
+                );
+                for action in &decoded.actions {
+                    let args: Vec<_> = action.args.iter().map(tx::TxArg::to_json).collect();
+                    println!("       {}.{}({})", action.contract, action.function, json!(args));
+                }
+            }
+            Err(e) => println!("   [{}] undecodable ({} bytes): {}", position, packed.len(), e),
+        }
+    }
+
+    if sections.muts {
+        print_mutations(db, &entry_meta_cf, entry_hash, "muts")?;
+    }
+    if sections.muts_rev {
+        print_mutations(db, &entry_meta_cf, entry_hash, "muts_rev")?;
+    }
+    if sections.consensus {
+        let attestation_cf = db
+            .cf_handle("attestation")
+            .ok_or_else(|| anyhow!("attestation CF not found"))?;
+        let prefix = [b"consensus:".as_slice(), entry_hash, b":"].concat();
+        let mut records = Vec::new();
+        for item in db.iterator_cf(&attestation_cf, rocksdb::IteratorMode::From(&prefix, rocksdb::Direction::Forward)) {
+            let (key, value) = item?;
+            if !key.starts_with(&prefix) {
+                break;
+            }
+            records.push((key[prefix.len()..].to_vec(), value));
+        }
+        println!("\n🤝 Consensus ({})", records.len());
+        for (muts_hash, value) in records {
+            println!("   mutations hash {}", hex::encode(&muts_hash));
+            println!("      {}", value_json(&value));
+        }
+    }
+
+    if sections.raw {
+        println!("\n🔬 Raw bytes ({})", entry_data.len());
+        if let Some(offset) = find_offset(entry_data, &view.header_bin) {
+            println!("   header at offset {} ({} bytes)", offset, view.header_bin.len());
+        }
+        for (name, field) in [("hash", &view.stored_hash), ("signature", &view.signature)] {
+            if let Some(offset) = field.as_deref().and_then(|field| find_offset(entry_data, field)) {
+                println!("   {} at offset {} ({} bytes)", name, offset, field.as_ref().map_or(0, Vec::len));
+            }
+        }
+        for (position, packed) in txs.iter().enumerate() {
+            if let Some(offset) = find_offset(entry_data, packed) {
+                println!("   tx {} at offset {} ({} bytes)", position, offset, packed.len());
+            }
+        }
+        for line in hex_dump(entry_data) {
+            println!("{}", line);
+        }
+    }
+    println!("{}", "=".repeat(80));
+    Ok(())
+}
+
+/// Show an entry found by hash, or every entry indexed at a height or slot
+pub fn show_entry(db: &DB, selector: &EntrySelector, sections: &EntrySections) -> Result<()> {
+    let entry_cf = db
+        .cf_handle("entry")
+        .ok_or_else(|| anyhow!("entry CF not found"))?;
+
+    let hashes = match selector {
+        EntrySelector::Hash(hash) => vec![hash.clone()],
+        EntrySelector::Height(height) => indexed_hashes(db, entry_index::BY_HEIGHT_PREFIX, *height)?,
+        EntrySelector::Slot(slot) => indexed_hashes(db, entry_index::BY_SLOT_PREFIX, *slot)?,
+    };
+    if hashes.is_empty() {
+        return Err(anyhow!("No entry indexed at {:?}", selector));
+    }
+    if hashes.len() > 1 {
+        println!("🍴 {} entries are indexed at {:?}", hashes.len(), selector);
+    }
+
+    for hash in hashes {
+        match db.get_cf(&entry_cf, &hash)? {
+            Some(entry_data) => print_entry(db, &hash, &entry_data, sections)?,
+            None => println!("❌ Entry {} is indexed but missing from the entry CF", hex::encode(&hash)),
+        }
+    }
+    Ok(())
+}
+
+/// A 32-byte entry hash given as hex, optionally prefixed with `hex:` or `0x`
+pub fn parse_hash_arg(text: &str) -> Result<Vec<u8>> {
+    let hex_text = text.strip_prefix("hex:").or_else(|| text.strip_prefix("0x")).unwrap_or(text);
+    let hash = hex::decode(hex_text).map_err(|e| anyhow!("Invalid hash '{}': {}", text, e))?;
+    if hash.len() != 32 {
+        return Err(anyhow!("Hash '{}' is {} bytes, expected 32", text, hash.len()));
+    }
+    Ok(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vterm_json_and_hex_dump() {
+        let header = VTerm::PropList(vec![
+            (VTerm::Binary(b"height".to_vec()), VTerm::VarInt(7)),
+            (VTerm::Binary(b"signer".to_vec()), VTerm::Binary(vec![0x80; 48])),
+            (VTerm::Binary(b"prev_hash".to_vec()), VTerm::Binary(vec![0; 32])),
+        ]);
+        let shown = vterm_json(&header);
+        assert_eq!(shown["height"], json!(7));
+        assert_eq!(shown["signer"], json!(bs58::encode(vec![0x80; 48]).into_string()));
+        assert_eq!(shown["prev_hash"], json!("00".repeat(32)));
+
+        let dump = hex_dump(b"0123456789abcdefXY");
+        assert_eq!(dump.len(), 2);
+        assert!(dump[0].starts_with("00000000  30 31"));
+        assert!(dump[1].starts_with("00000010  58 59") && dump[1].ends_with("|XY|"));
+
+        assert!(parse_hash_arg(&"ab".repeat(32)).is_ok());
+        assert!(parse_hash_arg("0xabcd").is_err());
+    }
+}
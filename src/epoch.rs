@@ -0,0 +1,263 @@
+use anyhow::{anyhow, Result};
+use amadeus_fabric_doctor::vecpak::{self, Term as VTerm};
+use rocksdb::DB;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Heights per epoch
+pub const EPOCH_LENGTH: u64 = 100_000;
+
+const TRAINERS_PREFIX: &[u8] = b"bic:epoch:trainers:height:";
+const POP_PREFIX: &[u8] = b"bic:epoch:pop:";
+const EMISSION_ADDRESS_PREFIX: &[u8] = b"bic:epoch:emission_address:";
+const SOLUTIONS_COUNT_PREFIX: &[u8] = b"bic:epoch:solutions_count:";
+const SEGMENT_VR_HASH_KEY: &[u8] = b"bic:epoch:segment_vr_hash";
+
+/// Proofs of possession are BLS12-381 signatures
+const POP_SIZE: usize = 96;
+
+/// Trainer public keys stored as an ETF or vecpak list of binaries
+fn decode_public_keys(value: &[u8]) -> Option<Vec<Vec<u8>>> {
+    if let Ok(eetf::Term::List(list)) = eetf::Term::decode(value) {
+        return list
+            .elements
+            .into_iter()
+            .map(|element| match element {
+                eetf::Term::Binary(binary) => Some(binary.bytes),
+                _ => None,
+            })
+            .collect();
+    }
+    match vecpak::decode_term_from_slice(value).ok()? {
+        VTerm::List(items) => items
+            .into_iter()
+            .map(|item| match item {
+                VTerm::Binary(bytes) => Some(bytes),
+                _ => None,
+            })
+            .collect(),
+        _ => None,
+    }
+}
+
+/// Every trainer set in contractstate, by the height it takes effect at
+fn trainer_sets(db: &DB, contractstate_cf: &impl rocksdb::AsColumnFamilyRef) -> Result<BTreeMap<u64, Vec<Vec<u8>>>> {
+    let mut sets = BTreeMap::new();
+    for (key, value) in prefixed(db, contractstate_cf, TRAINERS_PREFIX)? {
+        let height = std::str::from_utf8(&key[TRAINERS_PREFIX.len()..]).ok().and_then(|height| height.parse().ok());
+        match (height, decode_public_keys(&value)) {
+            (Some(height), Some(trainers)) => {
+                sets.insert(height, trainers);
+            }
+            _ => println!("⚠️  Could not decode trainer set {}", crate::decode_contractstate_key(&key)),
+        }
+    }
+    Ok(sets)
+}
+
+/// Set in effect at `height`: the last one taking effect at or below it, or the first one, the way
+/// the node looks it up
+fn set_at(sets: &BTreeMap<u64, Vec<Vec<u8>>>, height: u64) -> Option<(u64, &Vec<Vec<u8>>)> {
+    sets.range(..=height)
+        .next_back()
+        .or_else(|| sets.iter().next())
+        .map(|(height, trainers)| (*height, trainers))
+}
+
+/// Trainers added and removed between two sets, in the order of the set they appear in
+#[derive(Debug, PartialEq, Eq)]
+struct SetDiff {
+    added: Vec<Vec<u8>>,
+    removed: Vec<Vec<u8>>,
+    kept: usize,
+}
+
+fn diff_sets(from: &[Vec<u8>], to: &[Vec<u8>]) -> SetDiff {
+    let from_set: HashSet<&Vec<u8>> = from.iter().collect();
+    let to_set: HashSet<&Vec<u8>> = to.iter().collect();
+    SetDiff {
+        added: to.iter().filter(|pk| !from_set.contains(pk)).cloned().collect(),
+        removed: from.iter().filter(|pk| !to_set.contains(pk)).cloned().collect(),
+        kept: to.iter().filter(|pk| from_set.contains(pk)).count(),
+    }
+}
+
+/// Keys and values under `prefix`
+fn prefixed(db: &DB, cf: &impl rocksdb::AsColumnFamilyRef, prefix: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+    let mut records = Vec::new();
+    let iter = db.iterator_cf(cf, rocksdb::IteratorMode::From(prefix, rocksdb::Direction::Forward));
+    for item in iter {
+        let (key, value) = item?;
+        if !key.starts_with(prefix) {
+            break;
+        }
+        records.push((key.to_vec(), value.to_vec()));
+    }
+    Ok(records)
+}
+
+/// Values of `prefix<pk48>` keys by public key
+fn per_trainer(db: &DB, cf: &impl rocksdb::AsColumnFamilyRef, prefix: &[u8]) -> Result<HashMap<Vec<u8>, Vec<u8>>> {
+    Ok(prefixed(db, cf, prefix)?
+        .into_iter()
+        .filter(|(key, _)| key.len() >= prefix.len() + 48)
+        .map(|(key, value)| (key[prefix.len()..prefix.len() + 48].to_vec(), value))
+        .collect())
+}
+
+/// First and last height of an epoch, or an error if they don't fit in a u64
+fn epoch_heights(epoch: u64) -> Result<(u64, u64)> {
+    let start = epoch.checked_mul(EPOCH_LENGTH).ok_or_else(|| anyhow!("Epoch {} starts beyond the highest possible height", epoch))?;
+    let end = start.checked_add(EPOCH_LENGTH - 1).ok_or_else(|| anyhow!("Epoch {} ends beyond the highest possible height", epoch))?;
+    Ok((start, end))
+}
+
+fn base58(public_key: &[u8]) -> String {
+    bs58::encode(public_key).into_string()
+}
+
+/// Show the trainer set of an epoch with each trainer's proof of possession, emission address and
+/// solution count, the segment VR hash, and optionally how the set differs from another epoch's
+pub fn show_epoch(db: &DB, epoch: u64, diff_epoch: Option<u64>) -> Result<()> {
+    let contractstate_cf = db
+        .cf_handle("contractstate")
+        .ok_or_else(|| anyhow!("contractstate CF not found"))?;
+    let (temporal_height, _) = crate::extract_heights(db)?;
+    let current_epoch = temporal_height / EPOCH_LENGTH;
+
+    let sets = trainer_sets(db, &contractstate_cf)?;
+    let (start, end) = epoch_heights(epoch)?;
+    let (set_height, trainers) =
+        set_at(&sets, start).ok_or_else(|| anyhow!("No bic:epoch:trainers:height: keys in contractstate"))?;
+
+    let pops = per_trainer(db, &contractstate_cf, POP_PREFIX)?;
+    let emission_addresses = per_trainer(db, &contractstate_cf, EMISSION_ADDRESS_PREFIX)?;
+    let solutions = per_trainer(db, &contractstate_cf, SOLUTIONS_COUNT_PREFIX)?;
+    let vr_hash = db.get_cf(&contractstate_cf, SEGMENT_VR_HASH_KEY)?;
+
+    println!("🗓️  Epoch {} (heights {}..{})", epoch, start, end);
+    println!("{}", "=".repeat(80));
+    println!(
+        "{:<20} taking effect at height {}{}",
+        "Trainer set:",
+        set_height,
+        if set_height > start { " (no set recorded before the epoch, showing the first one)" } else { "" }
+    );
+    let changes: Vec<String> = sets.range(start + 1..=end).map(|(height, _)| height.to_string()).collect();
+    if !changes.is_empty() {
+        println!("{:<20} at heights {}", "Changed within:", changes.join(", "));
+    }
+    println!("{:<20} {}", "Segment VR hash:", vr_hash.as_deref().map(hex::encode).unwrap_or_else(|| "(none)".to_string()));
+    if epoch != current_epoch {
+        println!(
+            "ℹ️  PoP, emission addresses, solution counts and the VR hash are the current values (epoch {}, temporal height {})",
+            current_epoch, temporal_height
+        );
+    }
+
+    println!("\n👥 Trainers ({})", trainers.len());
+    println!("   {:>4}  {:<66} {:<10} {:>9}  emission address", "#", "trainer", "pop", "solutions");
+    let mut total_solutions = 0i128;
+    for (position, trainer) in trainers.iter().enumerate() {
+        let pop = match pops.get(trainer) {
+            Some(pop) if pop.len() == POP_SIZE => "✅".to_string(),
+            Some(pop) => format!("⚠️  {} bytes", pop.len()),
+            None => "❌ missing".to_string(),
+        };
+        let count = solutions.get(trainer).and_then(|value| crate::account::parse_integer(value));
+        total_solutions += count.unwrap_or(0);
+        let emission_address = match emission_addresses.get(trainer) {
+            Some(address) if address.len() == 48 => base58(address),
+            Some(address) => format!("hex:{}", hex::encode(address)),
+            None => "(self)".to_string(),
+        };
+        println!(
+            "   {:>4}  {:<66} {:<10} {:>9}  {}",
+            position,
+            base58(trainer),
+            pop,
+            count.map(|count| count.to_string()).unwrap_or_else(|| "-".to_string()),
+            emission_address
+        );
+    }
+
+    // Records for keys outside the set: applicants with a PoP, former trainers, stale counts
+    let in_set: HashSet<&Vec<u8>> = trainers.iter().collect();
+    let outside: Vec<(&str, usize)> = [("PoP", &pops), ("emission address", &emission_addresses), ("solution count", &solutions)]
+        .into_iter()
+        .map(|(name, records)| (name, records.keys().filter(|pk| !in_set.contains(pk)).count()))
+        .filter(|(_, count)| *count > 0)
+        .collect();
+
+    println!("\n📊 Summary:");
+    println!("   - Trainers: {}", trainers.len());
+    println!("   - With a valid-length PoP: {}", trainers.iter().filter(|pk| pops.get(*pk).is_some_and(|pop| pop.len() == POP_SIZE)).count());
+    println!("   - With an emission address: {}", trainers.iter().filter(|pk| emission_addresses.contains_key(*pk)).count());
+    println!("   - Solutions: {}", total_solutions);
+    for (name, count) in outside {
+        println!("   - Keys outside the set with a {}: {}", name, count);
+    }
+
+    if let Some(other) = diff_epoch {
+        let (other_start, _) = epoch_heights(other)?;
+        let (other_height, other_trainers) = set_at(&sets, other_start).ok_or_else(|| anyhow!("No trainer set for epoch {}", other))?;
+        let diff = diff_sets(trainers, other_trainers);
+        println!(
+            "\n🔀 Trainer set epoch {} (height {}) -> epoch {} (height {}): +{} -{}, {} kept",
+            epoch,
+            set_height,
+            other,
+            other_height,
+            diff.added.len(),
+            diff.removed.len(),
+            diff.kept
+        );
+        for added in &diff.added {
+            println!("   + {}", base58(added));
+        }
+        for removed in &diff.removed {
+            println!("   - {}", base58(removed));
+        }
+    }
+    println!("{}", "=".repeat(80));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_epoch_heights() {
+        assert_eq!(epoch_heights(0).unwrap(), (0, EPOCH_LENGTH - 1));
+        assert_eq!(epoch_heights(3).unwrap(), (3 * EPOCH_LENGTH, 4 * EPOCH_LENGTH - 1));
+        // The last partial epoch starts within a u64 but ends past it
+        let last = u64::MAX / EPOCH_LENGTH;
+        assert!(epoch_heights(last - 1).is_ok());
+        assert!(epoch_heights(last).is_err());
+        assert!(epoch_heights(last + 1).is_err());
+        assert!(epoch_heights(u64::MAX).is_err());
+    }
+
+    #[test]
+    fn test_trainer_sets() {
+        let pk = |byte: u8| vec![byte; 48];
+        let etf = eetf::Term::from(eetf::List::from(vec![
+            eetf::Term::from(eetf::Binary::from(pk(1))),
+            eetf::Term::from(eetf::Binary::from(pk(2))),
+        ]));
+        let mut encoded = Vec::new();
+        etf.encode(&mut encoded).unwrap();
+        assert_eq!(decode_public_keys(&encoded), Some(vec![pk(1), pk(2)]));
+        let mut packed = Vec::new();
+        vecpak::encode_term(&mut packed, VTerm::List(vec![VTerm::Binary(pk(3))]));
+        assert_eq!(decode_public_keys(&packed), Some(vec![pk(3)]));
+
+        let sets = BTreeMap::from([(150_000, vec![pk(1)]), (319_557, vec![pk(2)])]);
+        assert_eq!(set_at(&sets, 100_000).map(|(height, _)| height), Some(150_000));
+        assert_eq!(set_at(&sets, 300_000).map(|(height, _)| height), Some(150_000));
+        assert_eq!(set_at(&sets, 400_000).map(|(height, _)| height), Some(319_557));
+
+        let diff = diff_sets(&[pk(1), pk(2), pk(3)], &[pk(2), pk(3), pk(4)]);
+        assert_eq!(diff, SetDiff { added: vec![pk(4)], removed: vec![pk(1)], kept: 2 });
+    }
+}
@@ -0,0 +1,120 @@
+use anyhow::{anyhow, Result};
+use rocksdb::DB;
+use serde_json::json;
+use std::io::Write;
+
+/// Line-delimited output, plain or zstd-compressed
+enum ExportWriter {
+    Plain(std::io::BufWriter<std::fs::File>),
+    Zstd(zstd::Encoder<'static, std::io::BufWriter<std::fs::File>>),
+}
+
+impl ExportWriter {
+    fn create(path: &str, compress: bool) -> Result<Self> {
+        let file = std::fs::File::create(path).map_err(|e| anyhow!("Failed to create export file {}: {}", path, e))?;
+        let writer = std::io::BufWriter::new(file);
+        if compress {
+            Ok(ExportWriter::Zstd(zstd::Encoder::new(writer, 3)?))
+        } else {
+            Ok(ExportWriter::Plain(writer))
+        }
+    }
+
+    fn write_line(&mut self, line: &serde_json::Value) -> Result<()> {
+        match self {
+            ExportWriter::Plain(writer) => writeln!(writer, "{}", line)?,
+            ExportWriter::Zstd(writer) => writeln!(writer, "{}", line)?,
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> {
+        match self {
+            ExportWriter::Plain(mut writer) => writer.flush()?,
+            ExportWriter::Zstd(writer) => writer.finish()?.flush()?,
+        }
+        Ok(())
+    }
+}
+
+/// One export record. `cf`, `key_hex` and `value_hex` are the fields of the prune archive, so both
+/// can be read back the same way.
+fn export_record(cf_name: &str, key: &[u8], value: &[u8], raw: bool) -> (serde_json::Value, bool) {
+    let mut record = json!({
+        "cf": cf_name,
+        "key_hex": hex::encode(key),
+        "key": crate::describe_key(cf_name, key),
+        "value_hex": hex::encode(value),
+    });
+    if raw {
+        return (record, false);
+    }
+    match crate::parse_etf_to_json(value) {
+        Ok(parsed) => {
+            record["value"] = parsed;
+            (record, false)
+        }
+        Err(e) => {
+            record["error"] = json!(format!("Failed to parse: {}", e));
+            (record, true)
+        }
+    }
+}
+
+/// Stream every key of a column family to an NDJSON file (zstd-compressed with `compress`),
+/// one record per raw key so keys that decode to the same string stay separate
+pub fn export_ndjson(
+    db: &DB,
+    cf_name: &str,
+    cf: &impl rocksdb::AsColumnFamilyRef,
+    output_file: &str,
+    raw: bool,
+    compress: bool,
+) -> Result<()> {
+    println!("📤 Exporting {} to {}{}...", cf_name, output_file, if compress { " (zstd)" } else { "" });
+
+    let mut writer = ExportWriter::create(output_file, compress)?;
+    let mut count = 0u64;
+    let mut failed_parse_count = 0u64;
+
+    for item in db.iterator_cf(cf, rocksdb::IteratorMode::Start) {
+        let (key, value) = item?;
+        let (record, failed) = export_record(cf_name, &key, &value, raw);
+        writer.write_line(&record)?;
+        if failed {
+            failed_parse_count += 1;
+        }
+
+        count += 1;
+        if count.is_multiple_of(100_000) {
+            println!("📦 Exported {} records so far...", count);
+        }
+    }
+    writer.finish()?;
+
+    let size = std::fs::metadata(output_file).map(|meta| meta.len()).unwrap_or(0);
+    println!("✅ Export complete:");
+    println!("   - Records: {}", count);
+    println!("   - Failed parses: {}", failed_parse_count);
+    println!("   - File size: {} bytes", size);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_record_fields() {
+        let (record, failed) = export_record("contractstate", b"bic:coin:total", b"42", false);
+        assert!(!failed);
+        assert_eq!(record["key_hex"], json!(hex::encode(b"bic:coin:total")));
+        assert_eq!(record["key"], json!("bic:coin:total"));
+        assert_eq!(record["value_hex"], json!("3432"));
+        assert_eq!(record["value"], json!(42));
+
+        let (record, _) = export_record("entry", &[1, 2], &[131, 0], true);
+        assert_eq!(record["cf"], json!("entry"));
+        assert!(record.get("value").is_none());
+    }
+}
@@ -0,0 +1,306 @@
+use anyhow::{anyhow, Result};
+use rocksdb::DB;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::sysconf::{self, SysconfValue};
+
+/// Entry of the source's canonical chain among the `candidates` at `height`: the only one, or the
+/// one reached by following prev_hash down from the source temporal tip
+fn canonical_at(
+    source_db: &DB,
+    source_entry_cf: &impl rocksdb::AsColumnFamilyRef,
+    height: u64,
+    candidates: &[Vec<u8>],
+) -> Result<Option<Vec<u8>>> {
+    if let [only] = candidates {
+        return Ok(Some(only.clone()));
+    }
+    let Some(mut hash) = sysconf::read_hash(source_db, sysconf::TEMPORAL_TIP)? else {
+        return Ok(None);
+    };
+    loop {
+        let Some(entry_data) = source_db.get_cf(source_entry_cf, &hash)? else {
+            return Ok(None);
+        };
+        let (entry_height, _, _) = crate::parse_entry_metadata(&entry_data)?;
+        if entry_height <= height {
+            return Ok(candidates.contains(&hash).then_some(hash));
+        }
+        match crate::entry_header_binary(&entry_data, "prev_hash") {
+            Some(prev_hash) => hash = prev_hash,
+            None => return Ok(None),
+        }
+    }
+}
+
+/// Point the target's tips at the extracted chain: the temporal tip at the highest extracted height
+/// up to `to_height` and the source temporal height, the rooted tip at the source rooted height when
+/// it was extracted, otherwise at the nearest extracted height
+fn write_tips(
+    source_db: &DB,
+    target_db: &DB,
+    hashes_by_height: &BTreeMap<u64, Vec<Vec<u8>>>,
+    to_height: u64,
+    source_heights: (Option<u64>, Option<u64>),
+) -> Result<()> {
+    let source_entry_cf = source_db
+        .cf_handle("entry")
+        .ok_or_else(|| anyhow!("entry CF not found in source"))?;
+    let (source_temporal, source_rooted) = source_heights;
+
+    let temporal_limit = to_height.min(source_temporal.unwrap_or(to_height));
+    let Some((&temporal_height, _)) = hashes_by_height.range(..=temporal_limit).next_back() else {
+        println!("⚠️  No entry extracted at or below the source temporal height, sysconf tips not written");
+        return Ok(());
+    };
+    let rooted_limit = temporal_height.min(source_rooted.unwrap_or(temporal_height));
+    let rooted_height = match hashes_by_height.range(..=rooted_limit).next_back() {
+        Some((&height, _)) => height,
+        None => {
+            let lowest = *hashes_by_height.keys().next().unwrap_or(&temporal_height);
+            println!("ℹ️  Source rooted tip is below the extracted range, rooted tip set to height {}", lowest);
+            lowest
+        }
+    };
+
+    for (tip_key, height_key, height) in [
+        (sysconf::TEMPORAL_TIP, sysconf::TEMPORAL_HEIGHT, temporal_height),
+        (sysconf::ROOTED_TIP, sysconf::ROOTED_HEIGHT, rooted_height),
+    ] {
+        let candidates = &hashes_by_height[&height];
+        let tip = match canonical_at(source_db, &source_entry_cf, height, candidates)? {
+            Some(tip) => tip,
+            None => {
+                println!("⚠️  Could not tell which of {} entries at height {} is canonical, using the first", candidates.len(), height);
+                candidates[0].clone()
+            }
+        };
+        sysconf::write(target_db, tip_key, &SysconfValue::Hash(tip.clone()))?;
+        sysconf::write(target_db, height_key, &SysconfValue::Height(height))?;
+        println!("📍 {} set to {} (height {})", tip_key, hex::encode(&tip), height);
+    }
+    Ok(())
+}
+
+/// Copy the chain segment at heights `from_height..=to_height` into a new database:
+/// entries (forks included) with their by_height indexes, muts/muts_rev, consensus and
+/// tx pointers, optionally with the current contractstate. sysconf gets tips and heights
+/// pointing into the extracted segment.
+pub fn extract_height_range(
+    source_db_path: &str,
+    target_db_path: &str,
+    from_height: u64,
+    to_height: u64,
+    with_contractstate: bool,
+) -> Result<()> {
+    if from_height > to_height {
+        return Err(anyhow!("--from-height {} is above --to-height {}", from_height, to_height));
+    }
+    if !Path::new(source_db_path).exists() {
+        return Err(anyhow!("Source database path does not exist: {}", source_db_path));
+    }
+
+    println!(
+        "🔄 Extracting heights {}..={} from {} to {}",
+        from_height, to_height, source_db_path, target_db_path
+    );
+
+    crate::create_target_database(target_db_path)?;
+
+    println!("📖 Opening source database...");
+    let source_db = crate::open_source_database_readonly(source_db_path)?;
+    println!("🎯 Opening target database...");
+    let target_db = crate::open_target_database_readwrite(target_db_path)?;
+    let mut manifest = crate::manifest::MigrationManifest::start(
+        "extract",
+        &source_db,
+        source_db_path,
+        target_db_path,
+        serde_json::json!({
+            "from_height": from_height,
+            "to_height": to_height,
+            "with_contractstate": with_contractstate,
+        }),
+    )?;
+
+    let source_entry_cf = source_db
+        .cf_handle("entry")
+        .ok_or_else(|| anyhow!("entry CF not found in source"))?;
+    let source_entry_meta_cf = source_db
+        .cf_handle("entry_meta")
+        .ok_or_else(|| anyhow!("entry_meta CF not found in source"))?;
+    let target_entry_cf = target_db
+        .cf_handle("entry")
+        .ok_or_else(|| anyhow!("entry CF not found in target"))?;
+    let target_entry_meta_cf = target_db
+        .cf_handle("entry_meta")
+        .ok_or_else(|| anyhow!("entry_meta CF not found in target"))?;
+
+    let mut hashes_by_height: BTreeMap<u64, Vec<Vec<u8>>> = BTreeMap::new();
+    let mut extracted = 0;
+    let mut empty_heights = Vec::new();
+    let mut fork_heights = 0;
+    let mut write_batch = rocksdb::WriteBatch::default();
+    let batch_size = 1000;
+
+    for height in from_height..=to_height {
+        let hashes_at_height = crate::copy_entries_at_height(
+            &source_db, &source_entry_meta_cf, &source_entry_cf,
+            &mut write_batch, &target_entry_cf, &target_entry_meta_cf,
+            height,
+        )?;
+
+        match hashes_at_height.len() {
+            0 => empty_heights.push(height),
+            1 => {}
+            _ => fork_heights += 1,
+        }
+        extracted += hashes_at_height.len();
+        if !hashes_at_height.is_empty() {
+            hashes_by_height.insert(height, hashes_at_height);
+        }
+
+        if write_batch.len() >= batch_size {
+            target_db.write(std::mem::take(&mut write_batch))?;
+            println!("📦 Extracted {} entries (up to height {})...", extracted, height);
+        }
+    }
+
+    if !write_batch.is_empty() {
+        target_db.write(write_batch)?;
+    }
+
+    println!("✅ Extracted {} entries for {} heights", extracted, to_height - from_height + 1);
+    if fork_heights > 0 {
+        println!("ℹ️  {} heights have more than one entry, all of them were extracted", fork_heights);
+    }
+    if !empty_heights.is_empty() {
+        let shown: Vec<String> = empty_heights.iter().take(20).map(|h| h.to_string()).collect();
+        println!(
+            "⚠️  {} heights have no entries in the source: {}{}",
+            empty_heights.len(),
+            shown.join(", "),
+            if empty_heights.len() > shown.len() { ", ..." } else { "" }
+        );
+    }
+
+    let entry_hashes: Vec<Vec<u8>> = hashes_by_height.values().flatten().cloned().collect();
+    crate::migrate_muts_rev_selective(&source_db, &target_db, &entry_hashes)?;
+    crate::migrate_muts_selective(&source_db, &target_db, &entry_hashes)?;
+    crate::migrate_consensus_selective(&source_db, &target_db, &entry_hashes)?;
+    crate::migrate_tx_selective(&source_db, &target_db, &entry_hashes)?;
+
+    if with_contractstate {
+        println!("ℹ️  contractstate reflects the source tip, not the state at height {}", to_height);
+        if let Some(cf_digest) = crate::migrate_contractstate_full(&source_db, &target_db, false, false)? {
+            manifest.record_digest(&cf_digest);
+        }
+    }
+
+    write_tips(
+        &source_db,
+        &target_db,
+        &hashes_by_height,
+        to_height,
+        (manifest.source.temporal_height, manifest.source.rooted_height),
+    )?;
+    manifest.finish(&target_db)?;
+
+    println!("✅ Height range extraction completed successfully!");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amadeus_fabric_doctor::vecpak::{encode_term, Term};
+
+    fn binary(bytes: &[u8]) -> Term {
+        Term::Binary(bytes.to_vec())
+    }
+
+    /// vecpak entry at `height` on top of `prev_hash`, with its hash
+    fn entry(height: u64, slot: u64, prev_hash: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        let mut header = Vec::new();
+        encode_term(&mut header, Term::PropList(vec![
+            (binary(b"height"), Term::VarInt(height as i128)),
+            (binary(b"slot"), Term::VarInt(slot as i128)),
+            (binary(b"prev_hash"), binary(prev_hash)),
+        ]));
+        let hash = blake3::hash(&header).as_bytes().to_vec();
+        let mut data = Vec::new();
+        encode_term(&mut data, Term::PropList(vec![
+            (binary(b"header"), Term::Binary(header)),
+            (binary(b"hash"), binary(&hash)),
+            (binary(b"signature"), binary(&[2u8; 96])),
+            (binary(b"txs"), Term::List(Vec::new())),
+        ]));
+        (hash, data)
+    }
+
+    #[test]
+    fn test_extract_height_range() {
+        let source_path = std::env::temp_dir().join(format!("fabric_doctor_extract_source_{}", std::process::id()));
+        let source_path = source_path.to_string_lossy().to_string();
+        let target_path = format!("{}_target", source_path);
+        let _ = std::fs::remove_dir_all(&source_path);
+        let _ = std::fs::remove_dir_all(&target_path);
+
+        // Chain 1..=6 with a fork at height 3 hanging off height 2; rooted at 3, temporal at 6
+        let mut chain = Vec::new();
+        let (fork, fork_data);
+        {
+            crate::create_target_database(&source_path).unwrap();
+            let db = crate::open_target_database_readwrite(&source_path).unwrap();
+            let entry_cf = db.cf_handle("entry").unwrap();
+            let entry_meta_cf = db.cf_handle("entry_meta").unwrap();
+            let mut prev_hash = vec![0u8; 32];
+            for height in 1..=6u64 {
+                let (hash, data) = entry(height, height * 2, &prev_hash);
+                db.put_cf(&entry_cf, &hash, &data).unwrap();
+                db.put_cf(&entry_meta_cf, crate::entry_index::height_index_key(height, &hash), &hash).unwrap();
+                db.put_cf(&entry_meta_cf, [b"entry:".as_slice(), &hash, b":muts"].concat(), b"muts").unwrap();
+                prev_hash = hash.clone();
+                chain.push(hash);
+            }
+            (fork, fork_data) = entry(3, 99, &chain[1]);
+            db.put_cf(&entry_cf, &fork, &fork_data).unwrap();
+            db.put_cf(&entry_meta_cf, crate::entry_index::height_index_key(3, &fork), &fork).unwrap();
+            sysconf::write(&db, sysconf::TEMPORAL_TIP, &SysconfValue::Hash(chain[5].clone())).unwrap();
+            sysconf::write(&db, sysconf::ROOTED_TIP, &SysconfValue::Hash(chain[2].clone())).unwrap();
+        }
+
+        extract_height_range(&source_path, &target_path, 2, 4, false).unwrap();
+
+        let target = crate::open_source_database_readonly(&target_path).unwrap();
+        let entry_cf = target.cf_handle("entry").unwrap();
+        let entry_meta_cf = target.cf_handle("entry_meta").unwrap();
+        let mut extracted: Vec<Vec<u8>> = target
+            .iterator_cf(&entry_cf, rocksdb::IteratorMode::Start)
+            .map(|item| item.unwrap().0.to_vec())
+            .collect();
+        extracted.sort();
+        let mut expected = vec![chain[1].clone(), chain[2].clone(), chain[3].clone(), fork.clone()];
+        expected.sort();
+        assert_eq!(extracted, expected);
+        for (height, hash) in [(2, &chain[1]), (3, &chain[2]), (3, &fork), (4, &chain[3])] {
+            let index_key = crate::entry_index::height_index_key(height, hash);
+            assert_eq!(target.get_cf(&entry_meta_cf, index_key).unwrap().as_deref(), Some(hash.as_slice()));
+            let muts = target.get_cf(&entry_meta_cf, [b"entry:".as_slice(), hash, b":muts"].concat()).unwrap();
+            assert_eq!(muts.is_some(), *hash != fork);
+        }
+        assert!(target.get_cf(&entry_meta_cf, crate::entry_index::height_index_key(5, &chain[4])).unwrap().is_none());
+
+        // Temporal tip at the top of the range; the rooted tip picks the canonical entry at height 3, not the fork
+        assert_eq!(sysconf::read_hash(&target, sysconf::TEMPORAL_TIP).unwrap(), Some(chain[3].clone()));
+        assert_eq!(sysconf::read_hash(&target, sysconf::ROOTED_TIP).unwrap(), Some(chain[2].clone()));
+        let height = |name| sysconf::read(&target, name).unwrap().map(|decoded| decoded.value);
+        assert_eq!(height(sysconf::TEMPORAL_HEIGHT), Some(SysconfValue::Height(4)));
+        assert_eq!(height(sysconf::ROOTED_HEIGHT), Some(SysconfValue::Height(3)));
+
+        drop(target);
+        let _ = std::fs::remove_dir_all(&source_path);
+        let _ = std::fs::remove_dir_all(&target_path);
+    }
+}
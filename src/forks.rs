@@ -0,0 +1,491 @@
+use anyhow::{anyhow, Result};
+use rocksdb::DB;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::attestation;
+use crate::entry_index;
+use crate::prune;
+use crate::sysconf;
+use crate::tx;
+
+/// An entry hash and the by_height keys (binary and/or hex hash) listing it at one height
+struct IndexedEntry {
+    hash: Vec<u8>,
+    index_keys: Vec<Vec<u8>>,
+}
+
+/// Where an entry at a fork height stands relative to the chain from the temporal tip
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ForkStatus {
+    Canonical,
+    Orphan,
+    /// Listed at a height its header doesn't carry
+    Misindexed,
+    /// Above the temporal tip, not applied yet
+    AboveTemporal,
+    /// Below the lowest height the walk from the temporal tip reached
+    Unknown,
+    /// Listed by by_height but absent from the entry CF
+    Missing,
+}
+
+/// Height and prev_hash of every orphan, by entry hash
+type OrphanLinks = HashMap<Vec<u8>, (u64, Option<Vec<u8>>)>;
+
+/// Orphans sharing the same lowest entry, i.e. one branch off the canonical chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct OrphanBranch {
+    base: Vec<u8>,
+    base_height: u64,
+    tip_height: u64,
+    entries: usize,
+    /// prev_hash of the lowest orphan: the entry the branch forked off
+    parent: Option<Vec<u8>>,
+}
+
+impl OrphanBranch {
+    fn depth(&self) -> u64 {
+        self.tip_height - self.base_height + 1
+    }
+}
+
+/// Lowest orphan reached from `hash` by following prev_hash through other orphans
+fn branch_base<'a>(orphans: &'a OrphanLinks, mut hash: &'a [u8]) -> &'a [u8] {
+    while let Some((height, Some(prev))) = orphans.get(hash) {
+        // Only follow links that go down, so a bad prev_hash can't loop forever
+        match orphans.get(prev) {
+            Some((prev_height, _)) if prev_height < height => hash = prev,
+            _ => break,
+        }
+    }
+    hash
+}
+
+/// Group orphans (hash -> height and prev_hash) into branches, lowest branch first
+fn orphan_branches(orphans: &OrphanLinks) -> Vec<OrphanBranch> {
+    let mut branches: HashMap<&[u8], OrphanBranch> = HashMap::new();
+    for (hash, (height, _)) in orphans {
+        let base = branch_base(orphans, hash);
+        let (base_height, parent) = &orphans[base];
+        let branch = branches.entry(base).or_insert_with(|| OrphanBranch {
+            base: base.to_vec(),
+            base_height: *base_height,
+            tip_height: *base_height,
+            entries: 0,
+            parent: parent.clone(),
+        });
+        branch.tip_height = branch.tip_height.max(*height);
+        branch.entries += 1;
+    }
+    let mut branches: Vec<_> = branches.into_values().collect();
+    branches.sort_by(|a, b| (a.base_height, &a.base).cmp(&(b.base_height, &b.base)));
+    branches
+}
+
+/// Heights the by_height index lists more than one distinct entry for
+fn fork_heights(db: &DB, entry_meta_cf: &impl rocksdb::AsColumnFamilyRef) -> Result<BTreeMap<u64, Vec<IndexedEntry>>> {
+    let mut forks = BTreeMap::new();
+    let mut current: Option<(u64, Vec<IndexedEntry>)> = None;
+    let iter = db.iterator_cf(entry_meta_cf, rocksdb::IteratorMode::From(entry_index::BY_HEIGHT_PREFIX, rocksdb::Direction::Forward));
+    for item in iter {
+        let (key, _) = item?;
+        if !key.starts_with(entry_index::BY_HEIGHT_PREFIX) {
+            break;
+        }
+        let Some((_, height, hash)) = entry_index::decode_index_key(&key) else {
+            continue;
+        };
+        // Older databases store the hash part hex-encoded
+        let hash = match hex::decode(hash) {
+            Ok(decoded) if hash.len() == 64 => decoded,
+            _ => hash.to_vec(),
+        };
+        match current.as_mut() {
+            Some((current_height, entries)) if *current_height == height => match entries.iter_mut().find(|indexed| indexed.hash == hash) {
+                Some(indexed) => indexed.index_keys.push(key.to_vec()),
+                None => entries.push(IndexedEntry { hash, index_keys: vec![key.to_vec()] }),
+            },
+            _ => {
+                let indexed = IndexedEntry { hash, index_keys: vec![key.to_vec()] };
+                if let Some((current_height, entries)) = current.replace((height, vec![indexed])) {
+                    if entries.len() > 1 {
+                        forks.insert(current_height, entries);
+                    }
+                }
+            }
+        }
+    }
+    if let Some((height, entries)) = current {
+        if entries.len() > 1 {
+            forks.insert(height, entries);
+        }
+    }
+    Ok(forks)
+}
+
+/// Entry hash per height on the chain from `tip` down to `lowest_height` (or as far as it goes)
+fn canonical_chain(db: &DB, entry_cf: &impl rocksdb::AsColumnFamilyRef, tip: Vec<u8>, lowest_height: u64) -> Result<BTreeMap<u64, Vec<u8>>> {
+    let mut chain = BTreeMap::new();
+    let mut hash = tip;
+    let mut last_height = u64::MAX;
+    loop {
+        let Some(entry_data) = db.get_cf(entry_cf, &hash)? else {
+            println!("⚠️  Canonical chain broken below height {}: entry {} is missing", last_height, hex::encode(&hash));
+            break;
+        };
+        let (height, _, _) = crate::parse_entry_metadata(&entry_data)?;
+        if height >= last_height {
+            println!("⚠️  Canonical chain height doesn't decrease below {}, stopping there", last_height);
+            break;
+        }
+        chain.insert(height, hash);
+        last_height = height;
+        if height <= lowest_height {
+            break;
+        }
+        match crate::entry_header_binary(&entry_data, "prev_hash") {
+            Some(prev_hash) if !prev_hash.iter().all(|byte| *byte == 0) => hash = prev_hash,
+            _ => break,
+        }
+    }
+    Ok(chain)
+}
+
+#[derive(Debug, Default)]
+struct DeleteStats {
+    entries: u64,
+    index_keys: u64,
+    muts: u64,
+    consensus: u64,
+    attestations: u64,
+    my_attestations: u64,
+    tx_pointers: u64,
+}
+
+/// List every height with more than one entry in by_height, mark the entry on the chain from the
+/// temporal tip and group the others into orphan branches. With `delete_orphans`, orphans at or below
+/// the rooted tip are removed with their index keys, muts, consensus and attestations.
+pub fn report_forks(db_path: &str, delete_orphans: bool, dry_run: bool) -> Result<()> {
+    println!("🍴 Looking for forks in the by_height index of {}", db_path);
+    let db = if delete_orphans && !dry_run {
+        crate::open_target_database_readwrite(db_path)?
+    } else {
+        crate::open_source_database_readonly(db_path)?
+    };
+    let (temporal_height, rooted_height) = crate::extract_heights(&db)?;
+    println!("📊 Heights - Temporal: {}, Rooted: {}", temporal_height, rooted_height);
+
+    let entry_cf = db
+        .cf_handle("entry")
+        .ok_or_else(|| anyhow!("entry CF not found"))?;
+    let entry_meta_cf = db
+        .cf_handle("entry_meta")
+        .ok_or_else(|| anyhow!("entry_meta CF not found"))?;
+
+    let forks = fork_heights(&db, &entry_meta_cf)?;
+    let Some(lowest_fork) = forks.keys().next().copied() else {
+        println!("✅ No height lists more than one entry");
+        return Ok(());
+    };
+
+    let temporal_tip = sysconf::read_hash(&db, sysconf::TEMPORAL_TIP)?.ok_or_else(|| anyhow!("temporal_tip not found in sysconf"))?;
+    // One height further down, so the branch off the lowest fork height finds its parent
+    let canonical = canonical_chain(&db, &entry_cf, temporal_tip, lowest_fork.saturating_sub(1))?;
+    let chain_floor = canonical.keys().next().copied().unwrap_or(u64::MAX);
+    let on_chain: HashSet<&Vec<u8>> = canonical.values().collect();
+
+    // The rooted tip must be on the same chain, otherwise nothing here can be trusted for deletion
+    let mut rooted_on_chain = true;
+    if let Some(rooted_tip) = sysconf::read_hash(&db, sysconf::ROOTED_TIP)? {
+        if rooted_height >= chain_floor && canonical.get(&rooted_height) != Some(&rooted_tip) {
+            rooted_on_chain = false;
+            println!("❌ rooted_tip {} is not on the chain from temporal_tip at height {}", hex::encode(&rooted_tip), rooted_height);
+        }
+    }
+
+    // Printed rows per height: index entry, slot and status
+    let mut orphans = OrphanLinks::new();
+    let mut rows: Vec<(u64, Vec<_>)> = Vec::new();
+    let mut status_counts: HashMap<ForkStatus, u64> = HashMap::new();
+    for (height, entries) in &forks {
+        let mut height_rows = Vec::new();
+        for indexed in entries {
+            let (slot, status) = match db.get_cf(&entry_cf, &indexed.hash)? {
+                None => (None, ForkStatus::Missing),
+                Some(entry_data) => {
+                    let metadata = crate::parse_entry_metadata(&entry_data).ok();
+                    let slot = metadata.as_ref().map(|(_, slot, _)| *slot);
+                    let status = if *height > temporal_height {
+                        ForkStatus::AboveTemporal
+                    } else if metadata.map(|(header_height, _, _)| header_height) != Some(*height) {
+                        ForkStatus::Misindexed
+                    } else if *height < chain_floor {
+                        ForkStatus::Unknown
+                    } else if canonical.get(height) == Some(&indexed.hash) {
+                        ForkStatus::Canonical
+                    } else if on_chain.contains(&indexed.hash) {
+                        // On the chain at another height: a bad index key, not an orphan to delete
+                        ForkStatus::Misindexed
+                    } else {
+                        orphans.insert(indexed.hash.clone(), (*height, crate::entry_header_binary(&entry_data, "prev_hash")));
+                        ForkStatus::Orphan
+                    };
+                    (slot, status)
+                }
+            };
+            *status_counts.entry(status).or_default() += 1;
+            height_rows.push((indexed, slot, status));
+        }
+        rows.push((*height, height_rows));
+    }
+
+    let branches = orphan_branches(&orphans);
+    let branch_of: HashMap<&[u8], &OrphanBranch> = orphans
+        .keys()
+        .filter_map(|hash| {
+            let base = branch_base(&orphans, hash);
+            branches.iter().find(|branch| branch.base == base).map(|branch| (hash.as_slice(), branch))
+        })
+        .collect();
+
+    for (height, height_rows) in &rows {
+        println!("\n🍴 Height {} ({} entries)", height, height_rows.len());
+        for (indexed, slot, status) in height_rows {
+            let slot = slot.map(|slot| format!("slot {}", slot)).unwrap_or_else(|| "slot ?".to_string());
+            let label = match status {
+                ForkStatus::Canonical => "✅ canonical".to_string(),
+                ForkStatus::Orphan => match branch_of.get(indexed.hash.as_slice()) {
+                    Some(branch) => format!("🌿 orphan, branch from height {} ({} deep)", branch.base_height, branch.depth()),
+                    None => "🌿 orphan".to_string(),
+                },
+                ForkStatus::Misindexed => "⚠️  indexed at a height its header doesn't carry".to_string(),
+                ForkStatus::AboveTemporal => "⏳ above the temporal tip".to_string(),
+                ForkStatus::Unknown => "❔ below the walked chain".to_string(),
+                ForkStatus::Missing => "❌ missing from the entry CF".to_string(),
+            };
+            println!("   {} {:<14} {}", hex::encode(&indexed.hash), slot, label);
+        }
+    }
+
+    if !branches.is_empty() {
+        println!("\n🌿 Orphan branches ({})", branches.len());
+        for branch in &branches {
+            let parent = match &branch.parent {
+                Some(parent) => match canonical.iter().find(|(_, hash)| *hash == parent) {
+                    Some((height, _)) => format!("canonical entry at height {}", height),
+                    None => format!("entry {} off the walked chain", hex::encode(parent)),
+                },
+                None => "an entry without prev_hash".to_string(),
+            };
+            println!(
+                "   heights {}..{} ({} deep, {} entries) from {}, forks off {}",
+                branch.base_height,
+                branch.tip_height,
+                branch.depth(),
+                branch.entries,
+                hex::encode(&branch.base),
+                parent
+            );
+        }
+    }
+
+    let count = |status| status_counts.get(&status).copied().unwrap_or(0);
+    let deletable: Vec<(&Vec<u8>, u64)> = orphans
+        .iter()
+        .filter(|(_, (height, _))| *height <= rooted_height)
+        .map(|(hash, (height, _))| (hash, *height))
+        .collect();
+    println!("\n📊 Forks:");
+    println!("   - Heights with more than one entry: {}", forks.len());
+    println!("   - Canonical entries: {}", count(ForkStatus::Canonical));
+    println!("   - Orphan entries: {} ({} at or below the rooted tip)", count(ForkStatus::Orphan), deletable.len());
+    println!("   - Indexed at the wrong height: {}", count(ForkStatus::Misindexed));
+    println!("   - Above the temporal tip: {}", count(ForkStatus::AboveTemporal));
+    println!("   - Below the walked chain: {}", count(ForkStatus::Unknown));
+    println!("   - Missing from the entry CF: {}", count(ForkStatus::Missing));
+    println!(
+        "   - Orphan branches: {} (deepest {})",
+        branches.len(),
+        branches.iter().map(OrphanBranch::depth).max().unwrap_or(0)
+    );
+
+    if !delete_orphans {
+        return Ok(());
+    }
+    if !rooted_on_chain {
+        return Err(anyhow!("Refusing to delete orphans: rooted_tip is not on the chain from temporal_tip"));
+    }
+    delete_orphan_entries(&db, &forks, &deletable, dry_run)
+}
+
+/// Delete orphans with their by_height/by_slot keys, muts, consensus and attestations
+fn delete_orphan_entries(db: &DB, forks: &BTreeMap<u64, Vec<IndexedEntry>>, orphans: &[(&Vec<u8>, u64)], dry_run: bool) -> Result<()> {
+    let entry_cf = db
+        .cf_handle("entry")
+        .ok_or_else(|| anyhow!("entry CF not found"))?;
+    let entry_meta_cf = db
+        .cf_handle("entry_meta")
+        .ok_or_else(|| anyhow!("entry_meta CF not found"))?;
+    let attestation_cf = db
+        .cf_handle("attestation")
+        .ok_or_else(|| anyhow!("attestation CF not found"))?;
+    attestation::check_layouts(db, &attestation_cf)?;
+    let tx_cf = db.cf_handle("tx").ok_or_else(|| anyhow!("tx CF not found"))?;
+
+    println!("\n🗑️  Deleting {} orphan entries at or below the rooted tip{}", orphans.len(), if dry_run { " (dry run)" } else { "" });
+    let mut stats = DeleteStats::default();
+    let mut point_deletes: Vec<(&str, Vec<u8>)> = Vec::new();
+    let mut range_deletes: Vec<(Vec<u8>, Vec<u8>)> = Vec::new();
+    for (hash, height) in orphans {
+        let hash = hash.as_slice();
+        let Some(entry_data) = db.get_cf(&entry_cf, hash)? else {
+            continue;
+        };
+        stats.entries += 1;
+        point_deletes.push(("entry", hash.to_vec()));
+
+        let mut meta_keys: Vec<Vec<u8>> = forks[height]
+            .iter()
+            .filter(|indexed| indexed.hash == hash)
+            .flat_map(|indexed| indexed.index_keys.iter().cloned())
+            .collect();
+        if let Ok((_, slot, _)) = crate::parse_entry_metadata(&entry_data) {
+            meta_keys.push(entry_index::slot_index_key(slot, hash));
+            meta_keys.push(entry_index::slot_index_key(slot, hex::encode(hash).as_bytes()));
+        }
+        for key in meta_keys {
+            if db.get_cf(&entry_meta_cf, &key)?.is_some() {
+                stats.index_keys += 1;
+                point_deletes.push(("entry_meta", key));
+            }
+        }
+        for suffix in [&b":muts"[..], &b":muts_rev"[..]] {
+            let key = [b"entry:".as_slice(), hash, suffix].concat();
+            if db.get_cf(&entry_meta_cf, &key)?.is_some() {
+                stats.muts += 1;
+                point_deletes.push(("entry_meta", key));
+            }
+        }
+
+        let my_key = attestation::my_attestation_key(hash);
+        if db.get_cf(&attestation_cf, &my_key)?.is_some() {
+            stats.my_attestations += 1;
+            point_deletes.push(("attestation", my_key));
+        }
+        let consensus_prefix = [attestation::CONSENSUS_PREFIX, hash, b":"].concat();
+        let attestation_prefix = [attestation::attestation_height_prefix(*height).as_slice(), hash, b":"].concat();
+        for (prefix, counter) in [(consensus_prefix, &mut stats.consensus), (attestation_prefix, &mut stats.attestations)] {
+            let end = prune::prefix_end(&prefix);
+            let mut keys = Vec::new();
+            prune::for_each_in_range(db, &attestation_cf, &prefix, end.as_deref(), |key, _| {
+                *counter += 1;
+                keys.push(key.to_vec());
+                Ok(())
+            })?;
+            match end {
+                Some(end) if !keys.is_empty() => range_deletes.push((prefix, end)),
+                // Nothing sorts after an all-0xFF prefix, so its keys are deleted one by one
+                _ => point_deletes.extend(keys.into_iter().map(|key| ("attestation", key))),
+            }
+        }
+
+        // tx pointers are left alone: a transaction may still be included by a canonical entry
+        for packed in tx::entry_txs(&entry_data).unwrap_or_default() {
+            let Ok(decoded) = tx::decode_tx(&packed) else {
+                continue;
+            };
+            if db.get_cf(&tx_cf, &decoded.hash)?.is_some_and(|pointer| pointer.starts_with(hash)) {
+                stats.tx_pointers += 1;
+                println!("   ⚠️  tx {} still points at orphan {}", hex::encode(&decoded.hash), hex::encode(hash));
+            }
+        }
+    }
+
+    println!("📊 Orphan records:");
+    println!("   - Entries: {}", stats.entries);
+    println!("   - by_height/by_slot keys: {}", stats.index_keys);
+    println!("   - muts/muts_rev: {}", stats.muts);
+    println!("   - consensus: {}", stats.consensus);
+    println!("   - attestations: {}", stats.attestations);
+    println!("   - my_attestation: {}", stats.my_attestations);
+    println!("   - tx pointers into orphans (kept): {}", stats.tx_pointers);
+
+    if dry_run {
+        println!("ℹ️  Dry run, nothing deleted");
+        return Ok(());
+    }
+
+    let mut write_batch = rocksdb::WriteBatch::default();
+    for (start, end) in &range_deletes {
+        write_batch.delete_range_cf(&attestation_cf, start, end);
+        if write_batch.len() >= 1000 {
+            db.write(std::mem::take(&mut write_batch))?;
+        }
+    }
+    for (cf_name, key) in &point_deletes {
+        match *cf_name {
+            "entry" => write_batch.delete_cf(&entry_cf, key),
+            "entry_meta" => write_batch.delete_cf(&entry_meta_cf, key),
+            _ => write_batch.delete_cf(&attestation_cf, key),
+        }
+        if write_batch.len() >= 1000 {
+            db.write(std::mem::take(&mut write_batch))?;
+        }
+    }
+    if !write_batch.is_empty() {
+        db.write(write_batch)?;
+    }
+    println!("✅ Deleted {} keys and {} ranges", point_deletes.len(), range_deletes.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_orphan_branches() {
+        let hash = |byte: u8| vec![byte; 32];
+        let mut orphans = HashMap::new();
+        // 1 <- 10 <- 11 <- 12 and 11 <- 13 form one branch off canonical 1; 20 hangs off canonical 2
+        orphans.insert(hash(10), (5, Some(hash(1))));
+        orphans.insert(hash(11), (6, Some(hash(10))));
+        orphans.insert(hash(12), (7, Some(hash(11))));
+        orphans.insert(hash(13), (7, Some(hash(11))));
+        orphans.insert(hash(20), (9, Some(hash(2))));
+
+        let branches = orphan_branches(&orphans);
+        assert_eq!(branches.len(), 2);
+        assert_eq!(branches[0].base, hash(10));
+        assert_eq!((branches[0].base_height, branches[0].tip_height, branches[0].entries), (5, 7, 4));
+        assert_eq!(branches[0].depth(), 3);
+        assert_eq!(branches[0].parent, Some(hash(1)));
+        assert_eq!((branches[1].base_height, branches[1].depth(), branches[1].entries), (9, 1, 1));
+        assert_eq!(branch_base(&orphans, &hash(13)), hash(10).as_slice());
+    }
+
+    #[test]
+    fn test_fork_heights_dedupes_hex_and_binary_keys() {
+        let path = std::env::temp_dir().join(format!("fabric_doctor_fork_heights_{}", std::process::id()));
+        let path = path.to_string_lossy().to_string();
+        let _ = std::fs::remove_dir_all(&path);
+        crate::create_target_database(&path).unwrap();
+        let db = crate::open_target_database_readwrite(&path).unwrap();
+        let entry_meta_cf = db.cf_handle("entry_meta").unwrap();
+
+        let (a, b) = ([1u8; 32], [2u8; 32]);
+        // Height 5 lists one entry twice (binary and hex hash), height 6 lists two entries
+        db.put_cf(&entry_meta_cf, entry_index::height_index_key(5, &a), b"").unwrap();
+        db.put_cf(&entry_meta_cf, entry_index::height_index_key(5, hex::encode(a).as_bytes()), b"").unwrap();
+        db.put_cf(&entry_meta_cf, entry_index::height_index_key(6, &a), b"").unwrap();
+        db.put_cf(&entry_meta_cf, entry_index::height_index_key(6, hex::encode(b).as_bytes()), b"").unwrap();
+
+        let forks = fork_heights(&db, &entry_meta_cf).unwrap();
+        assert_eq!(forks.keys().copied().collect::<Vec<_>>(), vec![6]);
+        let hashes: Vec<&[u8]> = forks[&6].iter().map(|indexed| indexed.hash.as_slice()).collect();
+        assert_eq!(hashes, vec![&a[..], &b[..]]);
+
+        drop(db);
+        let _ = std::fs::remove_dir_all(&path);
+    }
+}
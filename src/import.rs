@@ -0,0 +1,482 @@
+use anyhow::{anyhow, Result};
+use eetf::Term;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::{BufRead, Read};
+
+use amadeus_fabric_doctor::vecpak::{self, Term as VTerm};
+
+use crate::digest::{DigestBuilder, DEFAULT_RANGE_SIZE};
+use crate::utils::safe_etf;
+
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+/// What to do with a key that already holds a different value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OnConflict {
+    /// Abort before anything is written
+    Fail,
+    /// Keep the value already in the database
+    Skip,
+    /// Replace it with the imported value
+    Overwrite,
+}
+
+/// How decoded values are turned back into bytes when a record has no `value_hex`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ValueEncoding {
+    /// Numbers and strings as text, `raw_hex` objects as their bytes, lists and maps as ETF
+    Auto,
+    /// Deterministic ETF with small atoms, as the node writes it
+    Etf,
+    /// vecpak
+    Vecpak,
+}
+
+/// One line of an NDJSON export or prune archive, or one pair of a legacy JSON export
+#[derive(Debug, Default)]
+struct ImportRecord {
+    cf: Option<String>,
+    key_hex: Option<String>,
+    key: Option<String>,
+    value_hex: Option<String>,
+    value: Option<Value>,
+}
+
+impl ImportRecord {
+    fn from_json(line: &Value) -> Result<Self> {
+        let object = line.as_object().ok_or_else(|| anyhow!("Record is not a JSON object"))?;
+        let text = |field: &str| object.get(field).and_then(Value::as_str).map(str::to_string);
+        Ok(ImportRecord {
+            cf: text("cf"),
+            key_hex: text("key_hex"),
+            key: text("key"),
+            value_hex: text("value_hex"),
+            value: object.get("value").cloned(),
+        })
+    }
+
+    /// Raw key, from `key_hex` or else the decoded key of the CF the record was exported from
+    fn key_bytes(&self, source_cf: &str) -> Result<Vec<u8>> {
+        if let Some(key_hex) = &self.key_hex {
+            return hex::decode(key_hex).map_err(|e| anyhow!("Invalid key_hex '{}': {}", key_hex, e));
+        }
+        let key = self.key.as_deref().ok_or_else(|| anyhow!("Record has neither key_hex nor key"))?;
+        crate::scan::parse_key_arg(source_cf, key)
+    }
+
+    /// Raw value, from `value_hex` or else the decoded value re-encoded with `encoding`
+    fn value_bytes(&self, encoding: ValueEncoding) -> Result<Vec<u8>> {
+        if let Some(value_hex) = &self.value_hex {
+            return hex::decode(value_hex).map_err(|e| anyhow!("Invalid value_hex: {}", e));
+        }
+        let value = self.value.as_ref().ok_or_else(|| anyhow!("Record has neither value_hex nor value"))?;
+        encode_value(value, encoding)
+    }
+}
+
+fn json_to_etf(value: &Value) -> Result<Term> {
+    Ok(match value {
+        Value::Null => Term::from(eetf::Atom::from("nil")),
+        Value::Bool(flag) => Term::from(eetf::Atom::from(if *flag { "true" } else { "false" })),
+        Value::Number(number) => {
+            if let Some(int) = number.as_i64() {
+                match i32::try_from(int) {
+                    Ok(small) => Term::from(eetf::FixInteger::from(small)),
+                    Err(_) => Term::from(eetf::BigInteger::from(int)),
+                }
+            } else if let Some(int) = number.as_u64() {
+                Term::from(eetf::BigInteger::from(int))
+            } else {
+                let float = number.as_f64().ok_or_else(|| anyhow!("Unsupported number {}", number))?;
+                Term::from(eetf::Float::try_from(float).map_err(|e| anyhow!("Invalid float {}: {:?}", float, e))?)
+            }
+        }
+        Value::String(text) => Term::from(eetf::Binary::from(text.as_bytes().to_vec())),
+        Value::Array(items) => Term::from(eetf::List::from(items.iter().map(json_to_etf).collect::<Result<Vec<_>>>()?)),
+        // Map keys are decoded from atoms, or shown as `binary:<hex>` when they were non-UTF-8 binaries
+        Value::Object(map) => Term::from(eetf::Map::from(
+            map.iter()
+                .map(|(key, value)| {
+                    let key = match key.strip_prefix("binary:").and_then(|hex_key| hex::decode(hex_key).ok()) {
+                        Some(bytes) => Term::from(eetf::Binary::from(bytes)),
+                        None => Term::from(eetf::Atom::from(key.as_str())),
+                    };
+                    Ok((key, json_to_etf(value)?))
+                })
+                .collect::<Result<HashMap<_, _>>>()?,
+        )),
+    })
+}
+
+fn json_to_vecpak(value: &Value) -> Result<VTerm> {
+    Ok(match value {
+        Value::Null => VTerm::Nil(),
+        Value::Bool(flag) => VTerm::Bool(*flag),
+        Value::Number(number) => VTerm::VarInt(
+            number
+                .as_i64()
+                .map(i128::from)
+                .or_else(|| number.as_u64().map(i128::from))
+                .ok_or_else(|| anyhow!("vecpak has no floats: {}", number))?,
+        ),
+        Value::String(text) => VTerm::Binary(text.as_bytes().to_vec()),
+        Value::Array(items) => VTerm::List(items.iter().map(json_to_vecpak).collect::<Result<_>>()?),
+        Value::Object(map) => VTerm::PropList(
+            map.iter()
+                .map(|(key, value)| Ok((VTerm::Binary(key.as_bytes().to_vec()), json_to_vecpak(value)?)))
+                .collect::<Result<_>>()?,
+        ),
+    })
+}
+
+/// Bytes of a decoded value; `Auto` inverts `parse_etf_to_json`, which shows non-ETF values as
+/// numbers, text or `raw_hex` objects
+fn encode_value(value: &Value, encoding: ValueEncoding) -> Result<Vec<u8>> {
+    match encoding {
+        ValueEncoding::Auto => match value {
+            Value::Number(number) => Ok(number.to_string().into_bytes()),
+            Value::String(text) => Ok(text.as_bytes().to_vec()),
+            Value::Object(map) if map.contains_key("raw_hex") => {
+                let raw_hex = map["raw_hex"].as_str().ok_or_else(|| anyhow!("raw_hex is not a string"))?;
+                hex::decode(raw_hex).map_err(|e| anyhow!("Invalid raw_hex: {}", e))
+            }
+            _ => encode_value(value, ValueEncoding::Etf),
+        },
+        ValueEncoding::Etf => Ok(safe_etf::encode_safe_deterministic(&json_to_etf(value)?)),
+        ValueEncoding::Vecpak => {
+            let mut buf = Vec::new();
+            vecpak::encode_term(&mut buf, json_to_vecpak(value)?);
+            Ok(buf)
+        }
+    }
+}
+
+/// The input file, transparently decompressed when it starts with the zstd magic
+fn open_input(input_file: &str) -> Result<Box<dyn BufRead>> {
+    let mut file = std::fs::File::open(input_file).map_err(|e| anyhow!("Failed to open {}: {}", input_file, e))?;
+    let mut magic = [0u8; 4];
+    let compressed = file.read_exact(&mut magic).is_ok() && magic == ZSTD_MAGIC;
+    let file = std::fs::File::open(input_file)?;
+    if compressed {
+        Ok(Box::new(std::io::BufReader::new(zstd::Decoder::new(file)?)))
+    } else {
+        Ok(Box::new(std::io::BufReader::new(file)))
+    }
+}
+
+/// Visit the records of an NDJSON export/archive one line at a time, or of a legacy
+/// `{"metadata", "data"}` JSON export, which is a single document and is parsed whole
+fn for_each_record(input_file: &str, mut visit: impl FnMut(ImportRecord) -> Result<()>) -> Result<&'static str> {
+    let mut reader = open_input(input_file)?;
+    let mut records = 0u64;
+    let mut line = String::new();
+    let mut line_number = 0u64;
+
+    // NDJSON lines are complete objects; a legacy export is one pretty-printed document
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok("ndjson");
+        }
+        line_number += 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Value>(&line) {
+            Ok(parsed) if parsed.get("data").is_none() => {
+                let record = ImportRecord::from_json(&parsed).map_err(|e| anyhow!("Line {}: {}", line_number, e))?;
+                records += 1;
+                visit(record)?;
+            }
+            _ if records == 0 => break,
+            Err(e) => return Err(anyhow!("Line {}: invalid JSON: {}", line_number, e)),
+            Ok(_) => return Err(anyhow!("Line {}: unexpected document in NDJSON", line_number)),
+        }
+    }
+
+    let mut document = line.clone();
+    reader.read_to_string(&mut document)?;
+    let document: Value = serde_json::from_str(&document).map_err(|e| anyhow!("Invalid JSON export: {}", e))?;
+    let data = document
+        .get("data")
+        .and_then(Value::as_object)
+        .ok_or_else(|| anyhow!("JSON export has no \"data\" object"))?;
+    let raw_mode = document["metadata"]["raw_mode"].as_bool().unwrap_or(false);
+    for (key, value) in data {
+        let mut record = ImportRecord { key: Some(key.clone()), ..Default::default() };
+        match value {
+            Value::String(value_hex) if raw_mode => record.value_hex = Some(value_hex.clone()),
+            _ => record.value = Some(value.clone()),
+        }
+        visit(record)?;
+    }
+    Ok("json")
+}
+
+/// Visit the target CF, key and value of every record in file order; returns the format and the
+/// number of records
+fn for_each_pair(
+    input_file: &str,
+    target_cf: Option<&str>,
+    encoding: ValueEncoding,
+    mut visit: impl FnMut(&str, Vec<u8>, Vec<u8>) -> Result<()>,
+) -> Result<(&'static str, u64)> {
+    let mut index = 0u64;
+    let format = for_each_record(input_file, |record| {
+        index += 1;
+        let source_cf = record.cf.as_deref().unwrap_or("contractstate");
+        let key = record.key_bytes(source_cf).map_err(|e| anyhow!("Record {}: {}", index, e))?;
+        let value = record.value_bytes(encoding).map_err(|e| anyhow!("Record {}: {}", index, e))?;
+        visit(target_cf.unwrap_or(source_cf), key, value)
+    })?;
+    Ok((format, index))
+}
+
+/// What the records of one column family do to the database
+struct CfImport {
+    records: u64,
+    new_keys: u64,
+    unchanged: u64,
+    conflicts: u64,
+    /// First conflicting keys, listed when the import stops on them
+    conflict_samples: Vec<Vec<u8>>,
+    written: u64,
+    /// Conflicting keys left as they were by `--on-conflict skip`, excluded from the verification
+    skipped: BTreeSet<Vec<u8>>,
+    import_digest: DigestBuilder,
+    stored_digest: DigestBuilder,
+}
+
+impl CfImport {
+    fn new(cf_name: &str) -> Self {
+        CfImport {
+            records: 0,
+            new_keys: 0,
+            unchanged: 0,
+            conflicts: 0,
+            conflict_samples: Vec::new(),
+            written: 0,
+            skipped: BTreeSet::new(),
+            import_digest: DigestBuilder::new(cf_name, DEFAULT_RANGE_SIZE),
+            stored_digest: DigestBuilder::new(cf_name, DEFAULT_RANGE_SIZE),
+        }
+    }
+}
+
+/// Write the records of an export file into a database (created when missing), each into
+/// `target_cf` or else the CF it was exported from, then read every written key back and compare
+/// it with the imported value.
+/// The file is streamed three times (check, write, verify), so memory does not grow with it; a key
+/// repeated with different values is only caught by the verification.
+pub fn import_file(
+    db_path: &str,
+    input_file: &str,
+    target_cf: Option<&str>,
+    encoding: ValueEncoding,
+    on_conflict: OnConflict,
+    dry_run: bool,
+) -> Result<()> {
+    println!("📥 Importing {} into {}{}", input_file, db_path, if dry_run { " (dry run)" } else { "" });
+
+    // A dry run neither creates the database nor opens it for writing; when the database does
+    // not exist yet, every record is new
+    let db = if !dry_run {
+        crate::create_target_database(db_path)?;
+        Some(crate::open_target_database_readwrite(db_path)?)
+    } else if std::path::Path::new(db_path).exists() {
+        Some(crate::open_source_database_readonly(db_path)?)
+    } else {
+        println!("ℹ️  {} does not exist, it would be created", db_path);
+        None
+    };
+
+    // Every record is checked before anything is written, so a failing conflict check leaves
+    // the database untouched
+    let mut cfs: BTreeMap<String, CfImport> = BTreeMap::new();
+    let (format, records) = for_each_pair(input_file, target_cf, encoding, |cf_name, key, value| {
+        let existing = match &db {
+            Some(db) => db.get_cf(crate::column_family(db, cf_name)?, &key)?,
+            None if crate::TARGET_COLUMN_FAMILIES.contains(&cf_name) => None,
+            None => return Err(anyhow!("Column family '{}' is not created with a new database", cf_name)),
+        };
+        let cf_import = cfs.entry(cf_name.to_string()).or_insert_with(|| CfImport::new(cf_name));
+        cf_import.records += 1;
+        cf_import.import_digest.update(&key, &value);
+        match existing {
+            None => cf_import.new_keys += 1,
+            Some(existing) if existing == value => cf_import.unchanged += 1,
+            Some(_) => {
+                cf_import.conflicts += 1;
+                if cf_import.conflict_samples.len() < 10 {
+                    cf_import.conflict_samples.push(key);
+                }
+            }
+        }
+        Ok(())
+    })?;
+    println!("📄 Read {} records ({})", records, format);
+
+    for (cf_name, cf_import) in &cfs {
+        println!(
+            "📊 {}: {} records, {} new, {} unchanged, {} conflicting",
+            cf_name, cf_import.records, cf_import.new_keys, cf_import.unchanged, cf_import.conflicts
+        );
+        if on_conflict == OnConflict::Fail {
+            for key in &cf_import.conflict_samples {
+                println!("   ⚠️  {}", crate::describe_key(cf_name, key));
+            }
+        }
+    }
+    let total_conflicts: u64 = cfs.values().map(|cf_import| cf_import.conflicts).sum();
+    if on_conflict == OnConflict::Fail && total_conflicts > 0 {
+        let cf_names: Vec<&str> = cfs.iter().filter(|(_, cf_import)| cf_import.conflicts > 0).map(|(cf_name, _)| cf_name.as_str()).collect();
+        return Err(anyhow!(
+            "{} keys of {} already hold different values; nothing was written (use --on-conflict skip or overwrite)",
+            total_conflicts,
+            cf_names.join(", ")
+        ));
+    }
+
+    let Some(db) = db.filter(|_| !dry_run) else {
+        for (cf_name, cf_import) in cfs {
+            let skipped = if on_conflict == OnConflict::Skip { cf_import.conflicts } else { 0 };
+            println!("{}:", cf_name);
+            println!("   - Written: {} (dry run, nothing written)", cf_import.records - skipped);
+            if on_conflict == OnConflict::Skip {
+                println!("   - Skipped conflicts: {}", skipped);
+            }
+            println!("   🔐 Import digest: {}", cf_import.import_digest.finish().hex());
+        }
+        println!("✅ Import preview complete (dry run)");
+        return Ok(());
+    };
+
+    // Conflicts are found again while writing rather than remembered from the check
+    let mut write_batch = rocksdb::WriteBatch::default();
+    for_each_pair(input_file, target_cf, encoding, |cf_name, key, value| {
+        let cf = crate::column_family(&db, cf_name)?;
+        if on_conflict == OnConflict::Skip && db.get_cf(cf, &key)?.is_some_and(|existing| existing != value) {
+            if let Some(cf_import) = cfs.get_mut(cf_name) {
+                cf_import.skipped.insert(key);
+            }
+            return Ok(());
+        }
+        write_batch.put_cf(cf, &key, &value);
+        if let Some(cf_import) = cfs.get_mut(cf_name) {
+            cf_import.written += 1;
+        }
+        if write_batch.len() >= 1000 {
+            db.write(std::mem::take(&mut write_batch))?;
+        }
+        Ok(())
+    })?;
+    if !write_batch.is_empty() {
+        db.write(write_batch)?;
+    }
+
+    // Read every written key back; it must hold the imported value byte for byte
+    for_each_pair(input_file, target_cf, encoding, |cf_name, key, value| {
+        let Some(cf_import) = cfs.get_mut(cf_name) else {
+            return Ok(());
+        };
+        if cf_import.skipped.contains(&key) {
+            return Ok(());
+        }
+        let stored = db
+            .get_cf(crate::column_family(&db, cf_name)?, &key)?
+            .ok_or_else(|| anyhow!("Imported key {} is missing after the write", crate::describe_key(cf_name, &key)))?;
+        if stored != value {
+            return Err(anyhow!(
+                "Verification failed: {} in {} holds a different value than imported",
+                crate::describe_key(cf_name, &key),
+                cf_name
+            ));
+        }
+        cf_import.stored_digest.update(&key, &stored);
+        Ok(())
+    })?;
+
+    for (cf_name, cf_import) in cfs {
+        println!("{}:", cf_name);
+        println!("   - Written: {}", cf_import.written);
+        if on_conflict == OnConflict::Skip {
+            println!("   - Skipped conflicts: {}", cf_import.skipped.len());
+        }
+        println!("   🔐 Import digest: {}", cf_import.import_digest.finish().hex());
+        println!("   🔐 Stored digest (written keys): {}", cf_import.stored_digest.finish().hex());
+        println!("   ✅ Verified: every written key holds the imported value");
+    }
+
+    println!("✅ Import complete");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_record_bytes_and_value_encodings() {
+        let record = ImportRecord::from_json(&json!({
+            "cf": "contractstate", "key_hex": "6b", "key": "ignored", "value_hex": "3432", "value": 1
+        }))
+        .unwrap();
+        assert_eq!(record.key_bytes("contractstate").unwrap(), b"k".to_vec());
+        assert_eq!(record.value_bytes(ValueEncoding::Etf).unwrap(), b"42".to_vec());
+
+        let record = ImportRecord { key: Some("bic:coin:total".into()), value: Some(json!(42)), ..Default::default() };
+        assert_eq!(record.key_bytes("contractstate").unwrap(), b"bic:coin:total".to_vec());
+        assert_eq!(record.value_bytes(ValueEncoding::Auto).unwrap(), b"42".to_vec());
+
+        // Decoded ETF values come back to the same term
+        let value = json!([1, "abc", {"a": 300}]);
+        let encoded = encode_value(&value, ValueEncoding::Auto).unwrap();
+        assert_eq!(crate::parse_etf_to_json(&encoded).unwrap(), value);
+        assert_eq!(encode_value(&json!({"raw_hex": "00ff"}), ValueEncoding::Auto).unwrap(), vec![0x00, 0xFF]);
+
+        let encoded = encode_value(&json!({"op": "put", "n": 5}), ValueEncoding::Vecpak).unwrap();
+        let VTerm::PropList(props) = vecpak::decode_term_from_slice(&encoded).unwrap() else {
+            panic!("expected a PropList");
+        };
+        assert_eq!(props.len(), 2);
+        assert!(encode_value(&json!(1.5), ValueEncoding::Vecpak).is_err());
+    }
+
+    #[test]
+    fn test_import_skip_verifies_written_keys() {
+        let dir = std::env::temp_dir().join(format!("fabric_doctor_import_skip_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join("db").to_string_lossy().to_string();
+        crate::create_target_database(&db_path).unwrap();
+        {
+            let db = crate::open_target_database_readwrite(&db_path).unwrap();
+            db.put_cf(db.cf_handle("contractstate").unwrap(), b"a", b"1").unwrap();
+        }
+
+        let write_file = |name: &str, pairs: &[(&[u8], &[u8])]| {
+            let path = dir.join(name);
+            let lines: Vec<String> = pairs
+                .iter()
+                .map(|(key, value)| json!({ "key_hex": hex::encode(key), "value_hex": hex::encode(value) }).to_string())
+                .collect();
+            std::fs::write(&path, lines.join("\n")).unwrap();
+            path.to_string_lossy().to_string()
+        };
+        let import = |file: &str| import_file(&db_path, file, None, ValueEncoding::Auto, OnConflict::Skip, false);
+
+        // The conflicting key keeps its value, the new one is verified
+        import(&write_file("skip.ndjson", &[(b"a", b"2"), (b"b", b"3")])).unwrap();
+        // A key imported twice with different values ends up holding only the last one
+        assert!(import(&write_file("repeated.ndjson", &[(b"c", b"4"), (b"c", b"5")])).is_err());
+
+        let db = crate::open_source_database_readonly(&db_path).unwrap();
+        let contractstate = db.cf_handle("contractstate").unwrap();
+        assert_eq!(db.get_cf(&contractstate, b"a").unwrap(), Some(b"1".to_vec()));
+        assert_eq!(db.get_cf(&contractstate, b"b").unwrap(), Some(b"3".to_vec()));
+        drop(db);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
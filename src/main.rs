@@ -1,5 +1,5 @@
 use anyhow::{anyhow, Result};
-use clap::Parser;
+use clap::{Args, Parser, Subcommand};
 use rocksdb::{ColumnFamilyDescriptor, DB, Options};
 use serde_json::json;
 use std::path::Path;
@@ -7,7 +7,31 @@ use eetf::Term;
 use amadeus_fabric_doctor::vecpak::decode_term_from_slice;
 use amadeus_fabric_doctor::vecpak_parser::get_prev_height_from_vecpak_entry;
 
+mod account;
+mod attestation;
+mod bulk_load;
+mod contractstate_filter;
+mod digest;
+mod entry_index;
+mod entry_view;
+mod epoch;
+mod export;
+mod extract;
+mod forks;
+mod import;
+mod manifest;
+mod mutation;
+mod profile;
+mod prune;
+mod sysconf;
+mod reencode;
+mod rewind;
+mod scan;
+mod sqlite_export;
+mod tx;
+mod tx_lookup;
 mod utils;
+mod walk;
 
 #[derive(Parser)]
 #[command(name = "amadeus-fabric-doctor")]
@@ -17,72 +41,527 @@ struct Cli {
     #[arg(short, long)]
     db_path: String,
 
-    /// Migrate contractstate column family from db-path to this target database path
-    #[arg(long, value_name = "TARGET_DB_PATH")]
-    migrate: Option<String>,
-
-    /// Migrate only contractstate and sysconf column families from db-path to this target database path
-    #[arg(long, value_name = "TARGET_DB_PATH")]
-    weakmigrate: Option<String>,
+    #[command(subcommand)]
+    command: Command,
+}
 
-    /// List all available keys in contractstate column family
-    #[arg(short, long)]
-    list_keys: bool,
+#[derive(Subcommand)]
+enum Command {
+    /// Read keys and values of any column family
+    Inspect {
+        #[command(subcommand)]
+        command: InspectCommand,
+    },
+    /// Copy db-path into a new database
+    Migrate {
+        #[command(subcommand)]
+        command: MigrateCommand,
+    },
+    /// Extract the heights --from-height..=--to-height into a new database
+    Extract {
+        /// Target database path
+        #[arg(value_name = "TARGET_DB_PATH")]
+        target_db_path: String,
+
+        /// First height of the range to extract (inclusive)
+        #[arg(long, value_name = "HEIGHT")]
+        from_height: u64,
+
+        /// Last height of the range to extract (inclusive)
+        #[arg(long, value_name = "HEIGHT")]
+        to_height: u64,
+
+        /// Also copy the full contractstate (as of the source tip)
+        #[arg(long)]
+        with_contractstate: bool,
+    },
+    /// Compare content digests of every column family in db-path against another database
+    Verify {
+        #[arg(value_name = "TARGET_DB_PATH")]
+        target_db_path: String,
+
+        /// Column families allowed to differ without failing the verification
+        #[arg(long, value_delimiter = ',', value_name = "CFS")]
+        expect_differ: Vec<String>,
+    },
+    /// Write contractstate, entries, transactions, muts and sysconf into a new SQLite file
+    ExportSqlite {
+        #[arg(value_name = "OUTPUT_FILE")]
+        output_file: String,
+    },
+    /// Write the records of an `inspect export` file, prune archive or legacy JSON export into a database
+    Import {
+        /// NDJSON (plain or zstd) or JSON export
+        #[arg(value_name = "INPUT_FILE")]
+        input_file: String,
+
+        /// Column family to write into (default: the `cf` of each record, else contractstate)
+        #[arg(long, value_name = "CF")]
+        cf: Option<String>,
+
+        /// Keys that already hold a different value
+        #[arg(long, value_enum, default_value = "fail")]
+        on_conflict: import::OnConflict,
+
+        /// Encoding of decoded values in records without `value_hex`
+        #[arg(long, value_enum, default_value = "auto")]
+        value_encoding: import::ValueEncoding,
+
+        #[command(flatten)]
+        dry_run: DryRunArg,
+    },
+    /// Show an entry found by hash, or every entry indexed at a height or slot
+    #[command(group(clap::ArgGroup::new("selector").required(true).args(["hash", "height", "slot"])))]
+    Entry {
+        /// Entry hash (hex)
+        #[arg(long, value_name = "HASH")]
+        hash: Option<String>,
+
+        #[arg(long, value_name = "HEIGHT")]
+        height: Option<u64>,
+
+        #[arg(long, value_name = "SLOT")]
+        slot: Option<u64>,
+
+        /// Add the entry's contractstate mutations
+        #[arg(long)]
+        muts: bool,
+
+        /// Add the entry's reverse mutations
+        #[arg(long)]
+        muts_rev: bool,
+
+        /// Add the entry's consensus records
+        #[arg(long)]
+        consensus: bool,
+
+        /// Add a hex dump of the stored bytes with field offsets
+        #[arg(long)]
+        raw: bool,
+    },
+    /// Follow prev_hash down the chain, reporting height gaps, broken links and by_height disagreements
+    Walk {
+        /// Entry hash to start from (hex, default: temporal_tip)
+        #[arg(long, value_name = "HASH")]
+        from: Option<String>,
+
+        /// Stop at this height (default: genesis)
+        #[arg(long, value_name = "HEIGHT")]
+        to_height: Option<u64>,
+
+        /// Only print problems and the summary
+        #[arg(long)]
+        issues_only: bool,
+    },
+    /// List heights with more than one entry, which one is canonical and how deep the orphan branches go
+    Forks {
+        /// Delete orphans at or below the rooted tip with their indexes, muts, consensus and attestations
+        #[arg(long)]
+        delete_orphans: bool,
+
+        #[command(flatten)]
+        dry_run: DryRunArg,
+    },
+    /// Show a transaction through its tx CF pointer, or list an account's transactions from the nonce indexes
+    #[command(group(clap::ArgGroup::new("selector").required(true).args(["hash", "account"])))]
+    Tx {
+        /// Transaction hash (hex)
+        #[arg(value_name = "HASH")]
+        hash: Option<String>,
+
+        /// List the transactions sent by this Base58 account (tx_account_nonce)
+        #[arg(long, value_name = "BASE58_PK")]
+        account: Option<String>,
+
+        /// With --account, list received transactions (tx_receiver_nonce) instead
+        #[arg(long, requires = "account")]
+        received: bool,
+
+        /// With --account, number of transactions to list, newest first
+        #[arg(long, value_name = "N", default_value_t = 50, requires = "account")]
+        limit: usize,
+    },
+    /// Show the balances, nonce, contract accounts and tx nonce index entries of a Base58 account
+    Account {
+        #[arg(value_name = "BASE58_PK")]
+        account: String,
+
+        /// Nonce index entries to show per index, newest first
+        #[arg(long, value_name = "N", default_value_t = 50)]
+        tx_limit: usize,
+
+        /// Also scan all of contractstate for other keys containing the public key
+        #[arg(long)]
+        all_keys: bool,
+
+        /// Print the report as JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show an epoch's trainer set with PoP status, emission addresses, solution counts and the VR hash
+    Epoch {
+        #[arg(value_name = "EPOCH")]
+        epoch: u64,
+
+        /// Also list the trainers added and removed between this epoch and another one
+        #[arg(long, value_name = "EPOCH")]
+        diff: Option<u64>,
+    },
+    /// Show the migration manifest recorded in sysconf (source, tips, parameters, per-CF digests)
+    Info,
+    /// Show temporal and rooted tips from sysconf
+    Tips,
+    /// Rebuild by_height/by_slot indexes in entry_meta from the entry column family
+    Reindex {
+        #[command(flatten)]
+        dry_run: DryRunArg,
+    },
+    /// Delete entries and their indexes, muts, consensus and attestations below a height, in place
+    Prune {
+        /// Prune everything below this height
+        #[arg(long, value_name = "HEIGHT")]
+        below_height: u64,
+
+        /// Write every removed record to this NDJSON file before deleting
+        #[arg(long, value_name = "ARCHIVE_FILE")]
+        archive: Option<String>,
+
+        #[command(flatten)]
+        dry_run: DryRunArg,
+    },
+    /// Convert ETF entries to vecpak in place, keeping the original header bytes and entry hashes
+    Reencode {
+        /// Convert the header too and recompute entry hashes, instead of keeping the original
+        /// header bytes (and hashes and signatures) unchanged
+        #[arg(long)]
+        convert_header: bool,
+
+        /// With --convert-header: write the entries even though their signatures, attestations
+        /// and consensus records only cover the old hashes
+        #[arg(long, requires = "convert_header")]
+        accept_invalid_signatures: bool,
+
+        #[command(flatten)]
+        dry_run: DryRunArg,
+    },
+    /// Undo the temporal entries above a height using their muts_rev, in one atomic batch
+    Rewind {
+        #[arg(long, value_name = "HEIGHT")]
+        to_height: u64,
+
+        #[command(flatten)]
+        dry_run: DryRunArg,
+    },
+    /// Test entry hash verification and output results to JSON file
+    Test {
+        #[arg(value_name = "OUTPUT_FILE")]
+        output_file: String,
+    },
+}
 
-    /// Get value for a specific key (hex format)
-    #[arg(short, long)]
-    key: Option<String>,
+#[derive(Subcommand)]
+enum InspectCommand {
+    /// Get the value of a key
+    Get {
+        #[command(flatten)]
+        cf: CfArg,
+
+        /// `hex:<hex>` (or `0x<hex>`), text, or for contractstate the decoded form with Base58 public keys
+        #[arg(value_name = "KEY")]
+        key: String,
+
+        /// Show raw binary data (don't parse ETF)
+        #[arg(short, long)]
+        raw: bool,
+    },
+    /// List keys of a column family, by prefix or range, one page at a time
+    ///
+    /// Keys and bounds are given as `hex:<hex>` (or `0x<hex>`), as text, or for contractstate in the
+    /// decoded form with Base58 public keys.
+    Scan {
+        #[command(flatten)]
+        cf: CfArg,
+
+        /// Only keys starting with this prefix
+        #[arg(long, value_name = "KEY")]
+        prefix: Option<String>,
+
+        /// First key of the range (inclusive)
+        #[arg(long, value_name = "KEY")]
+        start: Option<String>,
+
+        /// End of the range (exclusive)
+        #[arg(long, value_name = "KEY")]
+        end: Option<String>,
+
+        /// Walk the range from the last key down
+        #[arg(long)]
+        reverse: bool,
+
+        /// Number of keys per page
+        #[arg(long, value_name = "N", default_value_t = 100)]
+        limit: usize,
+
+        /// Continue after this key (hex), as printed at the end of the previous page
+        #[arg(long, value_name = "HEX")]
+        cursor: Option<String>,
+
+        /// Also print the decoded values
+        #[arg(long)]
+        values: bool,
+    },
+    /// Stream all keys and values of a column family to an NDJSON file, one record per key
+    Export {
+        #[command(flatten)]
+        cf: CfArg,
+
+        #[arg(value_name = "OUTPUT_FILE")]
+        output_file: String,
+
+        /// Only store values as hex (don't parse ETF)
+        #[arg(short, long)]
+        raw: bool,
+
+        /// Compress the output with zstd (implied by a `.zst` file name)
+        #[arg(long)]
+        zstd: bool,
+    },
+}
 
-    /// Export all contractstate data to JSON file
-    #[arg(short, long)]
-    export: Option<String>,
+#[derive(Subcommand)]
+enum MigrateCommand {
+    /// Contractstate and sysconf in full, entries and their data around the tips selectively
+    Full {
+        #[arg(value_name = "TARGET_DB_PATH")]
+        target_db_path: String,
+
+        /// Migration profile (TOML) with chain depth, phases and column family selection
+        #[arg(long, value_name = "PROFILE_FILE")]
+        profile: Option<String>,
+
+        /// Number of entries to follow below the rooted tip (default 1000)
+        #[arg(long, value_name = "ENTRIES")]
+        chain_depth: Option<u64>,
+
+        /// Stop migrating entries above the temporal tip after this many empty heights (default 5)
+        #[arg(long, value_name = "HEIGHTS")]
+        max_empty_heights: Option<u64>,
+
+        /// Entry migration phases to run: temporal_to_rooted, chain_to_genesis, above_temporal
+        #[arg(long, value_delimiter = ',', value_name = "PHASES")]
+        phases: Option<Vec<String>>,
+
+        /// Column families to copy in full (default contractstate,sysconf)
+        #[arg(long, value_delimiter = ',', value_name = "CFS")]
+        full_cfs: Option<Vec<String>>,
+
+        /// Selective migrations to run: entry, muts_rev, muts, attestation, consensus, tx
+        #[arg(long, value_delimiter = ',', value_name = "NAMES")]
+        selective: Option<Vec<String>>,
+
+        #[command(flatten)]
+        bulk_load: BulkLoadArg,
+    },
+    /// Only contractstate and sysconf
+    Weak {
+        #[arg(value_name = "TARGET_DB_PATH")]
+        target_db_path: String,
+
+        /// Copy only contractstate keys whose decoded form starts with (or, with `*`, matches) these patterns
+        #[arg(long, value_delimiter = ',', value_name = "PATTERNS", conflicts_with = "bulk_load")]
+        include_keys: Vec<String>,
+
+        /// Skip contractstate keys matching these decoded-key patterns
+        #[arg(long, value_delimiter = ',', value_name = "PATTERNS", conflicts_with = "bulk_load")]
+        exclude_keys: Vec<String>,
+
+        /// Copy contractstate keys containing these Base58 public keys
+        #[arg(long, value_delimiter = ',', value_name = "BASE58_PKS", conflicts_with = "bulk_load")]
+        accounts: Vec<String>,
+
+        /// Copy about one in N of the selected contractstate keys
+        #[arg(long, value_name = "N", conflicts_with = "bulk_load")]
+        sample: Option<u64>,
+
+        #[command(flatten)]
+        bulk_load: BulkLoadArg,
+    },
+}
 
-    /// Show raw binary data (don't parse ETF)
-    #[arg(short, long)]
-    raw: bool,
+#[derive(Args)]
+struct CfArg {
+    /// Column family to read, including the legacy ones of older databases
+    #[arg(long, value_name = "CF", default_value = "contractstate")]
+    cf: String,
+}
 
-    /// Test entry hash verification and output results to JSON file
-    #[arg(long, value_name = "OUTPUT_FILE")]
-    test: Option<String>,
+#[derive(Args)]
+struct DryRunArg {
+    /// Only report what would change, don't write to the database
+    #[arg(long)]
+    dry_run: bool,
+}
 
-    /// Show temporal and rooted tips from sysconf
+#[derive(Args)]
+struct BulkLoadArg {
+    /// Copy full column families by writing SST files and ingesting them instead of WriteBatch puts
     #[arg(long)]
-    tips: bool,
+    bulk_load: bool,
+
+    /// Also time 100,000 kvs of each bulk-loaded CF through WriteBatch and print the speedup
+    #[arg(long, requires = "bulk_load")]
+    bulk_load_benchmark: bool,
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
-    if let Some(target_db_path) = cli.migrate {
-        perform_migration(&cli.db_path, &target_db_path)?;
-    } else if let Some(target_db_path) = cli.weakmigrate {
-        perform_weak_migration(&cli.db_path, &target_db_path)?;
-    } else {
-        // Open the database with contractstate column family (read-only for inspection)
-        let db = open_source_database_readonly(&cli.db_path)?;
-
-        let contractstate_cf = db
-            .cf_handle("contractstate")
-            .ok_or_else(|| anyhow!("contractstate column family not found"))?;
-
-        if cli.tips {
+    match cli.command {
+        Command::Migrate { command: MigrateCommand::Full {
+            target_db_path,
+            profile,
+            chain_depth,
+            max_empty_heights,
+            phases,
+            full_cfs,
+            selective,
+            bulk_load,
+        } } => {
+            let profile = profile::MigrationProfile::resolve(
+                profile.as_deref(),
+                profile::ProfileOverrides {
+                    chain_depth,
+                    max_empty_heights,
+                    phases,
+                    full_cfs,
+                    selective,
+                    bulk_load: bulk_load.bulk_load,
+                    bulk_load_benchmark: bulk_load.bulk_load_benchmark,
+                },
+            )?;
+            perform_migration(&cli.db_path, &target_db_path, &profile)?;
+        }
+        Command::Migrate { command: MigrateCommand::Weak {
+            target_db_path,
+            include_keys,
+            exclude_keys,
+            accounts,
+            sample,
+            bulk_load,
+        } } => {
+            let filter = contractstate_filter::ContractstateFilter::new(include_keys, exclude_keys, accounts, sample)?;
+            perform_weak_migration(&cli.db_path, &target_db_path, &filter, bulk_load.bulk_load, bulk_load.bulk_load_benchmark)?;
+        }
+        Command::Verify { target_db_path, expect_differ } => {
+            digest::verify_databases(&cli.db_path, &target_db_path, &expect_differ)?;
+        }
+        Command::Extract { target_db_path, from_height, to_height, with_contractstate } => {
+            extract::extract_height_range(&cli.db_path, &target_db_path, from_height, to_height, with_contractstate)?;
+        }
+        Command::Reindex { dry_run } => {
+            entry_index::reindex_entries(&cli.db_path, dry_run.dry_run)?;
+        }
+        Command::Prune { below_height, archive, dry_run } => {
+            prune::prune_below_height(&cli.db_path, below_height, archive.as_deref(), dry_run.dry_run)?;
+        }
+        Command::Reencode { convert_header, accept_invalid_signatures, dry_run } => {
+            reencode::reencode_entries(&cli.db_path, convert_header, accept_invalid_signatures, dry_run.dry_run)?;
+        }
+        Command::Rewind { to_height, dry_run } => {
+            rewind::rewind_to_height(&cli.db_path, to_height, dry_run.dry_run)?;
+        }
+        Command::ExportSqlite { output_file } => {
+            sqlite_export::export_sqlite(&cli.db_path, &output_file)?;
+        }
+        Command::Import { input_file, cf, on_conflict, value_encoding, dry_run } => {
+            import::import_file(&cli.db_path, &input_file, cf.as_deref(), value_encoding, on_conflict, dry_run.dry_run)?;
+        }
+        Command::Entry { hash, height, slot, muts, muts_rev, consensus, raw } => {
+            let selector = match (hash, height, slot) {
+                (Some(hash), _, _) => entry_view::EntrySelector::Hash(entry_view::parse_hash_arg(&hash)?),
+                (None, Some(height), _) => entry_view::EntrySelector::Height(height),
+                (None, None, Some(slot)) => entry_view::EntrySelector::Slot(slot),
+                (None, None, None) => unreachable!("clap requires one selector"),
+            };
+            let db = open_source_database_readonly(&cli.db_path)?;
+            entry_view::show_entry(&db, &selector, &entry_view::EntrySections { muts, muts_rev, consensus, raw })?;
+        }
+        Command::Walk { from, to_height, issues_only } => {
+            let start = from.as_deref().map(entry_view::parse_hash_arg).transpose()?;
+            let db = open_source_database_readonly(&cli.db_path)?;
+            walk::walk_chain(&db, start, to_height, issues_only)?;
+        }
+        Command::Forks { delete_orphans, dry_run } => {
+            forks::report_forks(&cli.db_path, delete_orphans, dry_run.dry_run)?;
+        }
+        Command::Epoch { epoch, diff } => {
+            let db = open_source_database_readonly(&cli.db_path)?;
+            epoch::show_epoch(&db, epoch, diff)?;
+        }
+        Command::Tx { hash, account, received, limit } => {
+            let db = open_source_database_readonly(&cli.db_path)?;
+            match (hash, account) {
+                (Some(hash), _) => tx_lookup::show_tx(&db, &entry_view::parse_hash_arg(&hash)?)?,
+                (None, Some(account)) => tx_lookup::list_account_txs(&db, &account, received, limit)?,
+                (None, None) => unreachable!("clap requires a hash or an account"),
+            }
+        }
+        Command::Account { account, tx_limit, all_keys, json } => {
+            let db = open_source_database_readonly(&cli.db_path)?;
+            account::show_account(&db, &account, tx_limit, all_keys, json)?;
+        }
+        Command::Info => {
+            let db = open_source_database_readonly(&cli.db_path)?;
+            manifest::show_info(&db)?;
+        }
+        Command::Tips => {
+            let db = open_source_database_readonly(&cli.db_path)?;
             show_tips(&db)?;
-        } else if cli.list_keys {
-            list_keys(&db, &contractstate_cf)?;
-        } else if let Some(key_hex) = cli.key {
-            get_value(&db, &contractstate_cf, &key_hex, cli.raw)?;
-        } else if let Some(output_file) = cli.export {
-            export_all_data(&db, &contractstate_cf, &output_file, cli.raw)?;
-        } else if let Some(test_output_file) = cli.test {
-            test_entry_hash_verification(&db, &test_output_file)?;
-        } else {
-            println!("Use --help to see available options");
+        }
+        Command::Test { output_file } => {
+            let db = open_source_database_readonly(&cli.db_path)?;
+            test_entry_hash_verification(&db, &output_file)?;
+        }
+        Command::Inspect { command } => {
+            // Read-only for inspection
+            let db = open_source_database_readonly(&cli.db_path)?;
+            match command {
+                InspectCommand::Get { cf, key, raw } => {
+                    let key = scan::parse_key_arg(&cf.cf, &key)?;
+                    get_value(&db, &cf.cf, column_family(&db, &cf.cf)?, &key, raw)?;
+                }
+                InspectCommand::Scan { cf, prefix, start, end, reverse, limit, cursor, values } => {
+                    let key_arg = |text: Option<String>| text.map(|text| scan::parse_key_arg(&cf.cf, &text)).transpose();
+                    let options = scan::ScanOptions {
+                        prefix: key_arg(prefix)?,
+                        start: key_arg(start)?,
+                        end: key_arg(end)?,
+                        reverse,
+                        limit,
+                        cursor: cursor.map(|cursor| hex::decode(&cursor).map_err(|e| anyhow!("Invalid --cursor {}: {}", cursor, e))).transpose()?,
+                        with_values: values,
+                    };
+                    scan::scan(&db, &cf.cf, column_family(&db, &cf.cf)?, &options)?;
+                }
+                InspectCommand::Export { cf, output_file, raw, zstd } => {
+                    let compress = zstd || output_file.ends_with(".zst");
+                    export::export_ndjson(&db, &cf.cf, column_family(&db, &cf.cf)?, &output_file, raw, compress)?;
+                }
+            }
         }
     }
 
     Ok(())
 }
 
+/// Handle of a column family chosen with `--cf`, listing the available ones when it doesn't exist
+fn column_family<'a>(db: &'a DB, cf_name: &str) -> Result<&'a rocksdb::ColumnFamily> {
+    db.cf_handle(cf_name).ok_or_else(|| {
+        let available = DB::list_cf(&Options::default(), db.path()).unwrap_or_default();
+        anyhow!("Column family '{}' not found, available: {}", cf_name, available.join(", "))
+    })
+}
+
 fn open_source_database_readonly(db_path: &str) -> Result<DB> {
     if !Path::new(db_path).exists() {
         return Err(anyhow!("Source database path does not exist: {}", db_path));
@@ -110,7 +589,7 @@ fn open_source_database_readonly(db_path: &str) -> Result<DB> {
                 "entry_by_height|height:entryhash".to_string(),
                 "entry_by_slot|slot:entryhash".to_string(),
                 "my_seen_time_entry|entryhash".to_string(),
-                "my_attestation_for_entry|entryhash".to_string(),
+                attestation::LEGACY_MY_ATTESTATION_CF.to_string(),
                 "consensus".to_string(),
                 "consensus_by_entryhash|Map<mutationshash,consensus>".to_string(),
                 "muts".to_string(),
@@ -161,7 +640,7 @@ fn open_target_database_readwrite(db_path: &str) -> Result<DB> {
                 "entry_by_height|height:entryhash".to_string(),
                 "entry_by_slot|slot:entryhash".to_string(),
                 "my_seen_time_entry|entryhash".to_string(),
-                "my_attestation_for_entry|entryhash".to_string(),
+                attestation::LEGACY_MY_ATTESTATION_CF.to_string(),
                 "consensus".to_string(),
                 "consensus_by_entryhash|Map<mutationshash,consensus>".to_string(),
                 "muts".to_string(),
@@ -182,8 +661,9 @@ fn open_target_database_readwrite(db_path: &str) -> Result<DB> {
     Ok(db)
 }
 
-fn perform_migration(source_db_path: &str, target_db_path: &str) -> Result<()> {
+fn perform_migration(source_db_path: &str, target_db_path: &str, profile: &profile::MigrationProfile) -> Result<()> {
     println!("🔄 Starting comprehensive fabric migration from {} to {}", source_db_path, target_db_path);
+    profile.print();
 
     // Validate source database exists
     if !Path::new(source_db_path).exists() {
@@ -198,33 +678,78 @@ fn perform_migration(source_db_path: &str, target_db_path: &str) -> Result<()> {
     let source_db = open_source_database_readonly(source_db_path)?;
     println!("🎯 Opening target database...");
     let target_db = open_target_database_readwrite(target_db_path)?;
+    let mut manifest = manifest::MigrationManifest::start(
+        "migrate full",
+        &source_db,
+        source_db_path,
+        target_db_path,
+        serde_json::to_value(profile)?,
+    )?;
 
     // Step 1: Extract temporal and rooted heights from consensus
     let (temporal_height, rooted_height) = extract_heights(&source_db)?;
     println!("📊 Heights - Temporal: {}, Rooted: {}", temporal_height, rooted_height);
 
-    // Step 2: Migrate contractstate (full)
-    migrate_contractstate_full(&source_db, &target_db)?;
+    // Step 2: Migrate full column families (contractstate and sysconf by default)
+    for cf_name in &profile.full_cfs {
+        let cf_digest = migrate_named_cf_full(&source_db, &target_db, cf_name, profile.bulk_load, profile.bulk_load_benchmark)?;
+        // sysconf gets the heights below, so the manifest digests it again at the end
+        if let Some(cf_digest) = cf_digest.filter(|_| cf_name != "sysconf") {
+            manifest.record_digest(&cf_digest);
+        }
+    }
 
-    // Step 3: Migrate sysconf (full) + add temporal_height and rooted_height
-    migrate_sysconf_full(&source_db, &target_db)?;
+    // Step 3: Add temporal_height and rooted_height to sysconf
     write_height_to_sysconf(&target_db, "temporal_height", temporal_height)?;
     write_height_to_sysconf(&target_db, "rooted_height", rooted_height)?;
 
-    // Step 4: Migrate default CF (selective: temporal to rooted + chain to genesis)
-    let migrated_entry_hashes = migrate_default_selective(&source_db, &target_db, temporal_height, rooted_height)?;
+    // Step 4: Migrate entry CF (selective: temporal to rooted + chain to genesis)
+    let migrated_entries = if profile.runs_selective("entry") {
+        migrate_default_selective(&source_db, &target_db, temporal_height, rooted_height, profile)?
+    } else {
+        println!("⏭️  Skipping entry migration (disabled by profile)");
+        MigratedEntries::default()
+    };
+    let migrated_entry_hashes = &migrated_entries.temporal;
 
     // Step 5: Migrate muts_rev, muts, my_attestations, consensus (temporal entries only)
-    migrate_muts_rev_selective(&source_db, &target_db, &migrated_entry_hashes)?;
-    migrate_muts_selective(&source_db, &target_db, &migrated_entry_hashes)?;
-    migrate_my_attestations_selective(&source_db, &target_db, &migrated_entry_hashes)?;
-    migrate_consensus_selective(&source_db, &target_db, &migrated_entry_hashes)?;
+    if profile.runs_selective("muts_rev") {
+        migrate_muts_rev_selective(&source_db, &target_db, migrated_entry_hashes)?;
+    }
+    if profile.runs_selective("muts") {
+        migrate_muts_selective(&source_db, &target_db, migrated_entry_hashes)?;
+    }
+    if profile.runs_selective("attestation") {
+        migrate_my_attestations_selective(&source_db, &target_db, migrated_entry_hashes)?;
+    }
+    if profile.runs_selective("consensus") {
+        migrate_consensus_selective(&source_db, &target_db, migrated_entry_hashes)?;
+    }
+
+    // Step 6: Migrate transaction pointers and nonce indexes (all migrated entries)
+    if profile.runs_selective("tx") {
+        let all_entry_hashes: Vec<Vec<u8>> = migrated_entries
+            .temporal
+            .iter()
+            .chain(migrated_entries.chain.iter())
+            .cloned()
+            .collect();
+        migrate_tx_selective(&source_db, &target_db, &all_entry_hashes)?;
+    }
+
+    manifest.finish(&target_db)?;
 
     println!("✅ Comprehensive migration completed successfully!");
     Ok(())
 }
 
-fn perform_weak_migration(source_db_path: &str, target_db_path: &str) -> Result<()> {
+fn perform_weak_migration(
+    source_db_path: &str,
+    target_db_path: &str,
+    contractstate_filter: &contractstate_filter::ContractstateFilter,
+    bulk_load: bool,
+    bulk_load_benchmark: bool,
+) -> Result<()> {
     println!("🔄 Starting weak migration (contractstate + sysconf only) from {} to {}", source_db_path, target_db_path);
 
     // Validate source database exists
@@ -240,17 +765,47 @@ fn perform_weak_migration(source_db_path: &str, target_db_path: &str) -> Result<
     let source_db = open_source_database_readonly(source_db_path)?;
     println!("🎯 Opening target database...");
     let target_db = open_target_database_readwrite(target_db_path)?;
-
-    // Migrate contractstate (full)
-    migrate_contractstate_full(&source_db, &target_db)?;
+    let mut manifest = manifest::MigrationManifest::start(
+        "migrate weak",
+        &source_db,
+        source_db_path,
+        target_db_path,
+        json!({ "contractstate_filter": contractstate_filter.to_json(), "bulk_load": bulk_load, "bulk_load_benchmark": bulk_load_benchmark }),
+    )?;
+
+    // Migrate contractstate (full, or only the keys selected by the filter)
+    if contractstate_filter.is_empty() {
+        if let Some(cf_digest) = migrate_contractstate_full(&source_db, &target_db, bulk_load, bulk_load_benchmark)? {
+            manifest.record_digest(&cf_digest);
+        }
+    } else {
+        contractstate_filter::migrate_contractstate_filtered(&source_db, &target_db, contractstate_filter)?;
+    }
 
     // Migrate sysconf (full)
-    migrate_sysconf_full(&source_db, &target_db)?;
+    if let Some(cf_digest) = migrate_sysconf_full(&source_db, &target_db, bulk_load, bulk_load_benchmark)? {
+        manifest.record_digest(&cf_digest);
+    }
+
+    manifest.finish(&target_db)?;
 
     println!("✅ Weak migration completed successfully!");
     Ok(())
 }
 
+/// Column families of a database created by `create_target_database`
+const TARGET_COLUMN_FAMILIES: [&str; 9] = [
+    "default",
+    "sysconf",
+    "entry",
+    "entry_meta",
+    "attestation",
+    "tx",
+    "tx_account_nonce",
+    "tx_receiver_nonce",
+    "contractstate",
+];
+
 fn create_target_database(db_path: &str) -> Result<()> {
     let path = Path::new(db_path);
 
@@ -272,35 +827,18 @@ fn create_target_database(db_path: &str) -> Result<()> {
     // Limit open files to prevent "Too many open files" error
     opts.set_max_open_files(1000);
     
-    let cf_names = vec![
-        "default",
-        "sysconf",
-        "entry",
-        "entry_meta",
-        "attestation",
-        "tx",
-        "tx_account_nonce",
-        "tx_receiver_nonce",
-        "contractstate",
-    ];
-    
-    let cf_descriptors: Vec<_> = cf_names
+    let cf_descriptors: Vec<_> = TARGET_COLUMN_FAMILIES
         .iter()
         .map(|name| ColumnFamilyDescriptor::new(*name, Options::default()))
         .collect();
     
     let _db = DB::open_cf_descriptors(&opts, db_path, cf_descriptors)?;
-    println!("✅ Created new database with {} column families", cf_names.len());
+    println!("✅ Created new database with {} column families", TARGET_COLUMN_FAMILIES.len());
     
     Ok(())
 }
 
 fn extract_heights(source_db: &DB) -> Result<(u64, u64)> {
-    let sysconf_cf = source_db
-        .cf_handle("sysconf")
-        .ok_or_else(|| anyhow!("sysconf column family not found"))?;
-
-
     let mut temporal_height = 0u64;
     let mut rooted_height = 0u64;
 
@@ -310,8 +848,7 @@ fn extract_heights(source_db: &DB) -> Result<(u64, u64)> {
 
     // Get temporal_tip and derive height from entry_meta
     println!("🔍 Looking for temporal_tip...");
-    let temporal_tip_hash = source_db
-        .get_cf(&sysconf_cf, "temporal_tip".as_bytes())?
+    let temporal_tip_hash = sysconf::read_hash(source_db, sysconf::TEMPORAL_TIP)?
         .ok_or_else(|| anyhow!("temporal_tip not found in sysconf"))?;
     println!("  Found temporal_tip hash: {}", hex::encode(&temporal_tip_hash));
 
@@ -347,8 +884,7 @@ fn extract_heights(source_db: &DB) -> Result<(u64, u64)> {
 
     // Get rooted_tip and derive height from entry_meta
     println!("🔍 Looking for rooted_tip...");
-    let rooted_tip_hash = source_db
-        .get_cf(&sysconf_cf, "rooted_tip".as_bytes())?
+    let rooted_tip_hash = sysconf::read_hash(source_db, sysconf::ROOTED_TIP)?
         .ok_or_else(|| anyhow!("rooted_tip not found in sysconf"))?;
     println!("  Found rooted_tip hash: {}", hex::encode(&rooted_tip_hash));
 
@@ -383,30 +919,41 @@ fn extract_heights(source_db: &DB) -> Result<(u64, u64)> {
 }
 
 
-fn migrate_contractstate_full(source_db: &DB, target_db: &DB) -> Result<()> {
-    println!("🔄 Migrating contractstate (full)...");
-
-    let source_cf = source_db
-        .cf_handle("contractstate")
-        .ok_or_else(|| anyhow!("contractstate CF not found in source"))?;
-    let target_cf = target_db
-        .cf_handle("contractstate")
-        .ok_or_else(|| anyhow!("contractstate CF not found in target"))?;
+fn migrate_contractstate_full(
+    source_db: &DB,
+    target_db: &DB,
+    bulk_load: bool,
+    bulk_load_benchmark: bool,
+) -> Result<Option<digest::CfDigest>> {
+    migrate_named_cf_full(source_db, target_db, "contractstate", bulk_load, bulk_load_benchmark)
+}
 
-    migrate_column_family_full(source_db, &source_cf, target_db, &target_cf, "contractstate")
+fn migrate_sysconf_full(
+    source_db: &DB,
+    target_db: &DB,
+    bulk_load: bool,
+    bulk_load_benchmark: bool,
+) -> Result<Option<digest::CfDigest>> {
+    migrate_named_cf_full(source_db, target_db, "sysconf", bulk_load, bulk_load_benchmark)
 }
 
-fn migrate_sysconf_full(source_db: &DB, target_db: &DB) -> Result<()> {
-    println!("🔄 Migrating sysconf (full)...");
+fn migrate_named_cf_full(
+    source_db: &DB,
+    target_db: &DB,
+    cf_name: &str,
+    bulk_load: bool,
+    bulk_load_benchmark: bool,
+) -> Result<Option<digest::CfDigest>> {
+    println!("🔄 Migrating {} (full)...", cf_name);
 
     let source_cf = source_db
-        .cf_handle("sysconf")
-        .ok_or_else(|| anyhow!("sysconf CF not found in source"))?;
+        .cf_handle(cf_name)
+        .ok_or_else(|| anyhow!("{} CF not found in source", cf_name))?;
     let target_cf = target_db
-        .cf_handle("sysconf")
-        .ok_or_else(|| anyhow!("sysconf CF not found in target"))?;
+        .cf_handle(cf_name)
+        .ok_or_else(|| anyhow!("{} CF not found in target", cf_name))?;
 
-    migrate_column_family_full(source_db, &source_cf, target_db, &target_cf, "sysconf")
+    migrate_column_family_full(source_db, &source_cf, target_db, &target_cf, cf_name, bulk_load, bulk_load_benchmark)
 }
 
 fn migrate_column_family_full(
@@ -415,7 +962,9 @@ fn migrate_column_family_full(
     target_db: &DB,
     target_cf: &impl rocksdb::AsColumnFamilyRef,
     cf_name: &str,
-) -> Result<()> {
+    bulk_load: bool,
+    bulk_load_benchmark: bool,
+) -> Result<Option<digest::CfDigest>> {
     println!("🔄 Migrating {} column family data...", cf_name);
 
     // First, check if the target database already has data
@@ -426,102 +975,165 @@ fn migrate_column_family_full(
         std::io::stdin().read_line(&mut input)?;
         if input.trim().to_lowercase() != "y" && input.trim().to_lowercase() != "yes" {
             println!("❌ Migration cancelled by user");
-            return Ok(());
+            return Ok(None);
         }
         println!("🔄 Continuing with migration (data will be merged)...");
     }
     
     let iter = source_db.iterator_cf(source_cf, rocksdb::IteratorMode::Start);
-    let mut count = 0;
+    let mut count = 0u64;
     let mut batch_size = 0;
     let mut errors = 0;
     let max_batch_size = 1000; // Process in batches for better performance
     let max_errors = 10; // Stop after 10 consecutive errors
     
     let mut write_batch = rocksdb::WriteBatch::default();
+    // Digest the source while copying so verification needs only one extra scan of the target
+    let mut source_digest = digest::DigestBuilder::new(cf_name, digest::DEFAULT_RANGE_SIZE);
     
-    for item in iter {
-        match item {
-            Ok((key, value)) => {
-                // Validate key and value sizes (RocksDB limits)
-                if key.len() > 1024 * 1024 { // 1MB key limit
-                    println!("⚠️  Skipping key with size {} bytes (too large)", key.len());
-                    continue;
-                }
-                if value.len() > 256 * 1024 * 1024 { // 256MB value limit  
-                    println!("⚠️  Skipping value with size {} bytes (too large)", value.len());
-                    continue;
-                }
+    let copy_started = std::time::Instant::now();
+    let mut copied_bytes = 0u64;
+
+    if bulk_load {
+        let (sst, baseline) = bulk_load::copy_column_family_sst(
+            source_db,
+            source_cf,
+            target_db,
+            target_cf,
+            cf_name,
+            &mut source_digest,
+            bulk_load_benchmark,
+        )?;
+        count = sst.count;
+        bulk_load::print_throughput(cf_name, "SST ingestion", &sst);
+        if let Some(baseline) = baseline {
+            bulk_load::print_speedup(cf_name, &sst, &baseline);
+        }
+    } else {
+        for item in iter {
+            match item {
+                Ok((key, value)) => {
+                    source_digest.update(&key, &value);
+
+                    // Validate key and value sizes (RocksDB limits)
+                    if key.len() > 1024 * 1024 { // 1MB key limit
+                        println!("⚠️  Skipping key with size {} bytes (too large)", key.len());
+                        continue;
+                    }
+                    if value.len() > 256 * 1024 * 1024 { // 256MB value limit  
+                        println!("⚠️  Skipping value with size {} bytes (too large)", value.len());
+                        continue;
+                    }
                 
-                // Add to batch
-                write_batch.put_cf(target_cf, &key, &value);
-                batch_size += 1;
-                count += 1;
-                errors = 0; // Reset error counter on success
+                    // Add to batch
+                    write_batch.put_cf(target_cf, &key, &value);
+                    batch_size += 1;
+                    count += 1;
+                    copied_bytes += (key.len() + value.len()) as u64;
+                    errors = 0; // Reset error counter on success
                 
-                // Write batch when it reaches max size
-                if batch_size >= max_batch_size {
-                    let batch_to_write = std::mem::replace(&mut write_batch, rocksdb::WriteBatch::default());
-                    match target_db.write(batch_to_write) {
-                        Ok(_) => {
-                            batch_size = 0;
-                            println!("📦 Migrated {} kvs so far...", count);
-                        }
-                        Err(e) => {
-                            println!("❌ Failed to write batch: {}", e);
-                            errors += 1;
-                            if errors >= max_errors {
-                                return Err(anyhow!("Too many consecutive errors during migration"));
+                    // Write batch when it reaches max size
+                    if batch_size >= max_batch_size {
+                        let batch_to_write = std::mem::replace(&mut write_batch, rocksdb::WriteBatch::default());
+                        match target_db.write(batch_to_write) {
+                            Ok(_) => {
+                                batch_size = 0;
+                                println!("📦 Migrated {} kvs so far...", count);
+                            }
+                            Err(e) => {
+                                println!("❌ Failed to write batch: {}", e);
+                                errors += 1;
+                                if errors >= max_errors {
+                                    return Err(anyhow!("Too many consecutive errors during migration"));
+                                }
                             }
                         }
                     }
                 }
-            }
-            Err(e) => {
-                println!("⚠️  Error reading entry: {}", e);
-                errors += 1;
-                if errors >= max_errors {
-                    return Err(anyhow!("Too many consecutive read errors during migration"));
+                Err(e) => {
+                    println!("⚠️  Error reading entry: {}", e);
+                    errors += 1;
+                    if errors >= max_errors {
+                        return Err(anyhow!("Too many consecutive read errors during migration"));
+                    }
+                    continue;
                 }
-                continue;
             }
         }
-    }
     
-    // Write remaining entries in the batch
-    if batch_size > 0 {
-        match target_db.write(write_batch) {
-            Ok(_) => println!("📦 Wrote final batch of {} kvs", batch_size),
-            Err(e) => return Err(anyhow!("Failed to write final batch: {}", e)),
+        // Write remaining entries in the batch
+        if batch_size > 0 {
+            match target_db.write(write_batch) {
+                Ok(_) => println!("📦 Wrote final batch of {} kvs", batch_size),
+                Err(e) => return Err(anyhow!("Failed to write final batch: {}", e)),
+            }
         }
+        bulk_load::print_throughput(
+            cf_name,
+            "WriteBatch",
+            &bulk_load::Throughput { count, bytes: copied_bytes, elapsed: copy_started.elapsed() },
+        );
     }
 
-    // Verify migration by comparing counts
+    // Verify migration by comparing content digests
     println!("🔍 Verifying migration...");
-    let source_count = count_kvs(source_db, source_cf)?;
-    let final_target_count = count_kvs(target_db, target_cf)?;
-    let migrated_count = final_target_count - initial_target_count;
-
-    if source_count == migrated_count {
-        println!("✅ Migration verification successful: {} kvs migrated", migrated_count);
-        println!("📊 Summary:");
-        println!("   - Source kvs: {}", source_count);
-        println!("   - Target kvs (before): {}", initial_target_count);
-        println!("   - Target kvs (after): {}", final_target_count);
-        println!("   - Migrated kvs: {}", migrated_count);
-    } else {
-        return Err(anyhow!(
-            "Migration verification failed: source={}, migrated={}, target_total={}",
-            source_count,
-            migrated_count,
-            final_target_count
-        ));
+    let source_digest = source_digest.finish();
+    let target_digest = digest::digest_column_family_like(target_db, target_cf, &source_digest)?;
+
+    println!("📊 Summary:");
+    println!("   - Source kvs: {} (digest {})", source_digest.key_count, source_digest.hex());
+    println!("   - Target kvs (before): {}", initial_target_count);
+    println!("   - Target kvs (after): {} (digest {})", target_digest.key_count, target_digest.hex());
+    println!("   - Migrated kvs: {}", count);
+
+    if source_digest.digest == target_digest.digest {
+        println!("✅ Migration verification successful: content digests match");
+        return Ok(Some(target_digest));
     }
 
-    Ok(())
+    let summary = digest::diff_column_families(
+        source_db, source_cf, &source_digest,
+        target_db, target_cf, &target_digest,
+        None,
+    )?;
+    digest::print_diff_summary(&summary);
+
+    // Keys that only exist in the target are expected when merging into a non-empty database
+    if summary.value_mismatches == 0 && summary.missing_in_target == 0 && initial_target_count > 0 {
+        println!(
+            "⚠️  Migration verification passed with {} pre-existing target kvs not present in source",
+            summary.extra_in_target
+        );
+        return Ok(Some(target_digest));
+    }
+
+    match summary.first {
+        Some(first) => Err(anyhow!(
+            "Migration verification failed for {}: first differing key {} ({})",
+            cf_name,
+            hex::encode(first.key()),
+            first.describe()
+        )),
+        None => Err(anyhow!("Migration verification failed for {}: digests differ", cf_name)),
+    }
 }
 
-fn migrate_default_selective(source_db: &DB, target_db: &DB, temporal_height: u64, rooted_height: u64) -> Result<Vec<Vec<u8>>> {
+/// Entry hashes copied by `migrate_default_selective`
+#[derive(Default)]
+struct MigratedEntries {
+    /// Phase 1 and Phase 3 entries (temporal); their muts/consensus/attestations are migrated
+    temporal: Vec<Vec<u8>>,
+    /// Phase 2 entries (chain below the rooted tip)
+    chain: Vec<Vec<u8>>,
+}
+
+fn migrate_default_selective(
+    source_db: &DB,
+    target_db: &DB,
+    temporal_height: u64,
+    rooted_height: u64,
+    profile: &profile::MigrationProfile,
+) -> Result<MigratedEntries> {
     println!("🔄 Migrating entry CF (selective: temporal to rooted + chain to genesis)...");
 
     let source_entry_cf = source_db
@@ -548,26 +1160,18 @@ fn migrate_default_selective(source_db: &DB, target_db: &DB, temporal_height: u6
         .ok_or_else(|| anyhow!("entry_meta CF not found in source"))?;
 
     // Use index-based lookup instead of full table scan
-    for height in rooted_height..=temporal_height {
-        let height_padded = format!("{:012}", height);
-        let height_prefix = format!("by_height:{}:", height_padded);
-        println!("  Looking for entries at height {} (prefix: '{}')", height, height_prefix);
-
-        let iter = source_db.iterator_cf(&source_entry_meta_cf, rocksdb::IteratorMode::From(height_prefix.as_bytes(), rocksdb::Direction::Forward));
-        for item in iter {
-            let (index_key, entry_hash) = item?;
-
-            // Check if we're still in the right height range
-            if !index_key.starts_with(height_prefix.as_bytes()) {
-                break; // Moved past this height
-            }
-
-            if let Some(entry_data) = source_db.get_cf(&source_entry_cf, &entry_hash)? {
-                write_batch.put_cf(&target_entry_cf, &entry_hash, &entry_data);
-                write_batch.put_cf(&target_entry_meta_cf, &index_key, &entry_hash);
-
+    if profile.phases.temporal_to_rooted {
+        for height in rooted_height..=temporal_height {
+            println!("  Looking for entries at height {} (prefix: 'by_height:{:012}:')", height, height);
+
+            let entry_hashes = copy_entries_at_height(
+                source_db, &source_entry_meta_cf, &source_entry_cf,
+                &mut write_batch, &target_entry_cf, &target_entry_meta_cf,
+                height,
+            )?;
+            for entry_hash in entry_hashes {
                 // Collect temporal entry hash for muts_rev migration
-                temporal_entry_hashes.push(entry_hash.to_vec());
+                temporal_entry_hashes.push(entry_hash);
 
                 migrated_entries += 1;
 
@@ -578,6 +1182,8 @@ fn migrate_default_selective(source_db: &DB, target_db: &DB, temporal_height: u6
                 }
             }
         }
+    } else {
+        println!("⏭️  Phase 1 skipped (disabled by profile)");
     }
 
     // Phase 3: Migrate entries above temporal_height (going upwards until no entries found)
@@ -586,31 +1192,22 @@ fn migrate_default_selective(source_db: &DB, target_db: &DB, temporal_height: u6
     let mut current_height = temporal_height + 1;
     let mut above_temporal_entries = 0;
     let mut consecutive_empty_heights = 0;
-    let max_consecutive_empty = 5; // Stop after 5 consecutive empty heights
-
-    loop {
-        let height_padded = format!("{:012}", current_height);
-        let height_prefix = format!("by_height:{}:", height_padded);
-        let mut found_entries_at_height = false;
-
-        let iter = source_db.iterator_cf(&source_entry_meta_cf, rocksdb::IteratorMode::From(height_prefix.as_bytes(), rocksdb::Direction::Forward));
-        for item in iter {
-            let (index_key, entry_hash) = item?;
-
-            // Check if we're still in the right height range
-            if !index_key.starts_with(height_prefix.as_bytes()) {
-                break; // Moved past this height
-            }
-
-            if let Some(entry_data) = source_db.get_cf(&source_entry_cf, &entry_hash)? {
-                write_batch.put_cf(&target_entry_cf, &entry_hash, &entry_data);
-                write_batch.put_cf(&target_entry_meta_cf, &index_key, &entry_hash);
-
+    let max_consecutive_empty = profile.max_empty_heights; // Stop after this many consecutive empty heights
+
+    if profile.phases.above_temporal {
+        loop {
+            let entry_hashes = copy_entries_at_height(
+                source_db, &source_entry_meta_cf, &source_entry_cf,
+                &mut write_batch, &target_entry_cf, &target_entry_meta_cf,
+                current_height,
+            )?;
+            let found_entries_at_height = !entry_hashes.is_empty();
+
+            for entry_hash in entry_hashes {
                 // Collect entry hash for muts_rev migration (entries above temporal are also temporal-like)
-                temporal_entry_hashes.push(entry_hash.to_vec());
+                temporal_entry_hashes.push(entry_hash);
 
                 above_temporal_entries += 1;
-                found_entries_at_height = true;
 
                 if above_temporal_entries % batch_size == 0 {
                     let batch_to_write = std::mem::replace(&mut write_batch, rocksdb::WriteBatch::default());
@@ -618,87 +1215,93 @@ fn migrate_default_selective(source_db: &DB, target_db: &DB, temporal_height: u6
                     println!("📦 Migrated {} entries above temporal (Phase 3)...", above_temporal_entries);
                 }
             }
-        }
 
-        if found_entries_at_height {
-            consecutive_empty_heights = 0;
-            current_height += 1;
-        } else {
-            consecutive_empty_heights += 1;
-            if consecutive_empty_heights >= max_consecutive_empty {
-                println!("📍 No entries found for {} consecutive heights, stopping at height {}", max_consecutive_empty, current_height);
-                break;
+            if found_entries_at_height {
+                consecutive_empty_heights = 0;
+                current_height += 1;
+            } else {
+                consecutive_empty_heights += 1;
+                if consecutive_empty_heights >= max_consecutive_empty {
+                    println!("📍 No entries found for {} consecutive heights, stopping at height {}", max_consecutive_empty, current_height);
+                    break;
+                }
+                current_height += 1;
             }
-            current_height += 1;
         }
+    } else {
+        println!("⏭️  Phase 3 skipped (disabled by profile)");
     }
 
     println!("✅ Phase 3 complete: {} entries above temporal height migrated", above_temporal_entries);
 
     // Phase 2: Migrate chain from rooted_height down to genesis (follow prev_hash chain)
-    println!("📦 Phase 2: Migrating chain from rooted height {} down to genesis (max 1000 entries)", rooted_height);
+    println!("📦 Phase 2: Migrating chain from rooted height {} down to genesis (max {} entries)", rooted_height, profile.chain_depth);
 
     let mut current_height = rooted_height;
     let mut chain_entries = 0;
-    let max_chain_entries = 1000;
+    let max_chain_entries = profile.chain_depth;
 
-    loop {
-        if chain_entries >= max_chain_entries {
-            println!("📍 Reached limit of {} chain entries", max_chain_entries);
-            break;
-        }
-        // Find entry at current_height using index (much faster!)
-        if let Some(entry_at_height) = find_entry_at_height_indexed(source_db, &source_entry_meta_cf, &source_entry_cf, current_height)? {
-            let (entry_hash, entry_data) = entry_at_height;
+    if profile.phases.chain_to_genesis {
+        loop {
+            if chain_entries >= max_chain_entries {
+                println!("📍 Reached limit of {} chain entries", max_chain_entries);
+                break;
+            }
+            // Find entry at current_height using index (much faster!)
+            if let Some(entry_at_height) = find_entry_at_height_indexed(source_db, &source_entry_meta_cf, &source_entry_cf, current_height)? {
+                let (entry_hash, entry_data) = entry_at_height;
 
-            write_batch.put_cf(&target_entry_cf, &entry_hash, &entry_data);
+                write_batch.put_cf(&target_entry_cf, &entry_hash, &entry_data);
 
-            let height_padded = format!("{:012}", current_height);
-            let height_key = format!("by_height:{}:{}", height_padded, hex::encode(&entry_hash));
-            write_batch.put_cf(&target_entry_meta_cf, height_key.as_bytes(), &entry_hash);
+                // Same binary key format as the node (and Phase 1): by_height:{012}:<binary hash>
+                let height_key = entry_index::height_index_key(current_height, &entry_hash);
+                write_batch.put_cf(&target_entry_meta_cf, &height_key, &entry_hash);
 
-            // Collect rooted entry hash (for reference, not muts_rev)
-            rooted_entry_hashes.push(entry_hash.to_vec());
+                // Collect rooted entry hash (for reference, not muts_rev)
+                rooted_entry_hashes.push(entry_hash.to_vec());
 
-            chain_entries += 1;
+                chain_entries += 1;
 
-            if chain_entries % batch_size == 0 {
-                let batch_to_write = std::mem::replace(&mut write_batch, rocksdb::WriteBatch::default());
-                target_db.write(batch_to_write)?;
-                println!("📦 Migrated {} chain entries (Phase 2)...", chain_entries);
-            }
+                if chain_entries % batch_size == 0 {
+                    let batch_to_write = std::mem::replace(&mut write_batch, rocksdb::WriteBatch::default());
+                    target_db.write(batch_to_write)?;
+                    println!("📦 Migrated {} chain entries (Phase 2)...", chain_entries);
+                }
 
-            // Try to get prev_height to continue chain (may fail with vecpak entries)
-            match get_prev_height_from_entry(&entry_data, source_db) {
-                Ok(Some(prev_height)) => {
-                    if prev_height == 0 {
-                        println!("📍 Reached genesis at height 0");
+                // Try to get prev_height to continue chain (may fail with vecpak entries)
+                match get_prev_height_from_entry(&entry_data, source_db) {
+                    Ok(Some(prev_height)) => {
+                        if prev_height == 0 {
+                            println!("📍 Reached genesis at height 0");
+                            break;
+                        }
+                        current_height = prev_height;
+                    }
+                    Ok(None) => {
+                        println!("⚠️  Could not get prev_height from entry at height {}, stopping chain", current_height);
                         break;
                     }
-                    current_height = prev_height;
-                }
-                Ok(None) => {
-                    println!("⚠️  Could not get prev_height from entry at height {}, stopping chain", current_height);
-                    break;
-                }
-                Err(e) => {
-                    if e.to_string().contains("unsupported") || e.to_string().contains("format version") {
-                        println!("⚠️  Entries are vecpak encoded, cannot parse prev_hash. Phase 2 skipped.");
-                        println!("ℹ️  Migrated {} entries at rooted height before encountering parse error", chain_entries);
-                    } else {
-                        println!("⚠️  Failed to parse entry: {}", e);
+                    Err(e) => {
+                        if e.to_string().contains("unsupported") || e.to_string().contains("format version") {
+                            println!("⚠️  Entries are vecpak encoded, cannot parse prev_hash. Phase 2 skipped.");
+                            println!("ℹ️  Migrated {} entries at rooted height before encountering parse error", chain_entries);
+                        } else {
+                            println!("⚠️  Failed to parse entry: {}", e);
+                        }
+                        break;
                     }
-                    break;
                 }
+            } else {
+                println!("❌ Missing entry at height {}, gap detected", current_height);
+                break;
             }
-        } else {
-            println!("❌ Missing entry at height {}, gap detected", current_height);
-            break;
-        }
 
-        if current_height == 0 {
-            break;
+            if current_height == 0 {
+                break;
+            }
         }
+    } else {
+        println!("⏭️  Phase 2 skipped (disabled by profile)");
     }
 
     // Write final batch
@@ -713,7 +1316,10 @@ fn migrate_default_selective(source_db: &DB, target_db: &DB, temporal_height: u6
     println!("   - Total entries migrated: {}", migrated_entries + above_temporal_entries + chain_entries);
     println!("📊 Collected {} temporal entry hashes for muts_rev migration", temporal_entry_hashes.len());
     println!("📊 Collected {} rooted entry hashes (for reference)", rooted_entry_hashes.len());
-    Ok(temporal_entry_hashes)
+    Ok(MigratedEntries {
+        temporal: temporal_entry_hashes,
+        chain: rooted_entry_hashes,
+    })
 }
 
 fn migrate_muts_rev_selective(source_db: &DB, target_db: &DB, temporal_entry_hashes: &[Vec<u8>]) -> Result<()> {
@@ -766,15 +1372,9 @@ fn migrate_muts_rev_selective(source_db: &DB, target_db: &DB, temporal_entry_has
 fn write_height_to_sysconf(target_db: &DB, key: &str, height: u64) -> Result<()> {
     println!("🔄 Writing {} to sysconf...", key);
 
-    let target_cf = target_db
-        .cf_handle("sysconf")
-        .ok_or_else(|| anyhow!("sysconf CF not found in target"))?;
-
-    // Write height as string (e.g., "1234")
-    let height_string = height.to_string();
-
-    target_db.put_cf(&target_cf, key.as_bytes(), height_string.as_bytes())?;
-    println!("✅ {} ({}) written to sysconf as string \"{}\"", key, height, height_string);
+    // Written through the sysconf declaration (ETF integer, as the node stores it)
+    sysconf::write(target_db, key, &sysconf::SysconfValue::Height(height))?;
+    println!("✅ {} ({}) written to sysconf as an ETF integer", key, height);
     Ok(())
 }
 
@@ -820,11 +1420,134 @@ fn migrate_muts_selective(source_db: &DB, target_db: &DB, temporal_entry_hashes:
     Ok(())
 }
 
-fn migrate_my_attestations_selective(_source_db: &DB, _target_db: &DB, _temporal_entry_hashes: &[Vec<u8>]) -> Result<()> {
+fn migrate_my_attestations_selective(source_db: &DB, target_db: &DB, temporal_entry_hashes: &[Vec<u8>]) -> Result<()> {
     println!("🔄 Migrating attestations (for temporal entries only)...");
 
-    // Attestation keys require height+signer+mutations_hash, cannot migrate by entry hash alone
-    println!("⚠️  Attestation migration skipped - attestations will be regenerated by node");
+    let source_entry_cf = source_db
+        .cf_handle("entry")
+        .ok_or_else(|| anyhow!("entry CF not found in source"))?;
+    let source_attestation_cf = source_db
+        .cf_handle("attestation")
+        .ok_or_else(|| anyhow!("attestation CF not found in source"))?;
+    let target_attestation_cf = target_db
+        .cf_handle("attestation")
+        .ok_or_else(|| anyhow!("attestation CF not found in target"))?;
+    // Older databases keep the node's own attestations in a CF keyed by entry hash
+    let legacy_my_attestation_cf = source_db.cf_handle(attestation::LEGACY_MY_ATTESTATION_CF);
+    if legacy_my_attestation_cf.is_some() {
+        println!("ℹ️  Source has {}, its records are copied as my_attestation keys", attestation::LEGACY_MY_ATTESTATION_CF);
+    }
+    attestation::check_layouts(source_db, &source_attestation_cf)?;
+
+    // Attestation keys start with height, so group the migrated entries by their header height
+    let mut hashes_by_height: std::collections::BTreeMap<u64, Vec<Vec<u8>>> = std::collections::BTreeMap::new();
+    let mut unparsed = 0;
+    for entry_hash in temporal_entry_hashes {
+        let height = match source_db.get_cf(&source_entry_cf, entry_hash)? {
+            Some(entry_data) => parse_entry_metadata(&entry_data).ok().map(|(height, _slot, _hash)| height),
+            None => None,
+        };
+        match height {
+            Some(height) => hashes_by_height.entry(height).or_default().push(entry_hash.clone()),
+            None => unparsed += 1,
+        }
+    }
+
+    let mut count = 0;
+    let mut my_attestations = 0;
+    let mut legacy_my_attestations = 0;
+    let mut write_batch = rocksdb::WriteBatch::default();
+    let batch_size = 1000;
+    let mut per_entry: Vec<(u64, Vec<u8>, usize, bool)> = Vec::new();
+
+    for (height, entry_hashes) in &hashes_by_height {
+        let mut counts: std::collections::HashMap<&[u8], usize> = std::collections::HashMap::new();
+        let mut height_only = 0;
+
+        let prefix = attestation::attestation_height_prefix(*height);
+        let iter = source_db.iterator_cf(&source_attestation_cf, rocksdb::IteratorMode::From(&prefix, rocksdb::Direction::Forward));
+        for item in iter {
+            let (key, value) = item?;
+
+            if !key.starts_with(&prefix) {
+                break;
+            }
+
+            let decoded = attestation::decode_attestation_key(&key).ok_or_else(|| {
+                anyhow!("Attestation key {} at height {} doesn't fit the attestation layout", hex::encode(&key), height)
+            })?;
+            // Only copy attestations for entries that were migrated (skip forks at the same height);
+            // keys without an entry hash belong to the height
+            match decoded.entry_hash() {
+                Some(hash) => {
+                    let Some(entry_hash) = entry_hashes.iter().find(|h| h.as_slice() == hash) else {
+                        continue;
+                    };
+                    *counts.entry(entry_hash.as_slice()).or_default() += 1;
+                }
+                None => height_only += 1,
+            }
+
+            write_batch.put_cf(&target_attestation_cf, &key, &value);
+            count += 1;
+
+            if count % batch_size == 0 {
+                let batch_to_write = std::mem::take(&mut write_batch);
+                target_db.write(batch_to_write)?;
+                println!("📦 Migrated {} attestation kvs...", count);
+            }
+        }
+
+        for entry_hash in entry_hashes {
+            let my_key = attestation::my_attestation_key(entry_hash);
+            let legacy_value = match &legacy_my_attestation_cf {
+                Some(legacy_cf) => source_db.get_cf(legacy_cf, entry_hash)?,
+                None => None,
+            };
+            let has_my_attestation = match (source_db.get_cf(&source_attestation_cf, &my_key)?, legacy_value) {
+                (Some(value), _) => {
+                    write_batch.put_cf(&target_attestation_cf, &my_key, &value);
+                    my_attestations += 1;
+                    true
+                }
+                (None, Some(value)) => {
+                    write_batch.put_cf(&target_attestation_cf, &my_key, &value);
+                    legacy_my_attestations += 1;
+                    true
+                }
+                (None, None) => false,
+            };
+            let attestation_count = counts.get(entry_hash.as_slice()).copied().unwrap_or(0) + height_only;
+            per_entry.push((*height, entry_hash.clone(), attestation_count, has_my_attestation));
+        }
+    }
+
+    if !write_batch.is_empty() {
+        target_db.write(write_batch)?;
+    }
+
+    println!("📊 Attestations per entry:");
+    for (height, entry_hash, attestation_count, has_my_attestation) in &per_entry {
+        println!(
+            "   {:012} {} attestations={} my_attestation={}",
+            height,
+            hex::encode(entry_hash),
+            attestation_count,
+            if *has_my_attestation { "yes" } else { "no" }
+        );
+    }
+
+    println!(
+        "✅ attestation migration complete: {} attestations and {} my_attestation records ({} from {}) for {} entries",
+        count,
+        my_attestations + legacy_my_attestations,
+        legacy_my_attestations,
+        attestation::LEGACY_MY_ATTESTATION_CF,
+        per_entry.len()
+    );
+    if unparsed > 0 {
+        println!("⚠️  {} entries could not be decoded to find their height, their attestations were not migrated", unparsed);
+    }
     Ok(())
 }
 
@@ -875,6 +1598,125 @@ fn migrate_consensus_selective(source_db: &DB, target_db: &DB, temporal_entry_ha
     Ok(())
 }
 
+fn migrate_tx_selective(source_db: &DB, target_db: &DB, entry_hashes: &[Vec<u8>]) -> Result<()> {
+    println!("🔄 Migrating tx, tx_account_nonce and tx_receiver_nonce (for migrated entries)...");
+
+    let source_entry_cf = source_db
+        .cf_handle("entry")
+        .ok_or_else(|| anyhow!("entry CF not found in source"))?;
+
+    let mut cfs = Vec::new();
+    for cf_name in ["tx", "tx_account_nonce", "tx_receiver_nonce"] {
+        match (source_db.cf_handle(cf_name), target_db.cf_handle(cf_name)) {
+            (Some(source_cf), Some(target_cf)) => cfs.push((source_cf, target_cf)),
+            _ => {
+                println!("⚠️  {} CF not found in source or target, transaction migration skipped", cf_name);
+                return Ok(());
+            }
+        }
+    }
+    let (source_tx_cf, target_tx_cf) = &cfs[0];
+    let (source_account_cf, target_account_cf) = &cfs[1];
+    let (source_receiver_cf, target_receiver_cf) = &cfs[2];
+
+    let mut write_batch = rocksdb::WriteBatch::default();
+    let batch_size = 1000;
+    let mut written = 0;
+    let mut txs_seen = 0;
+    let mut undecodable_entries = 0;
+    let mut undecodable_txs = 0;
+    let mut hash_mismatches = 0;
+    let (mut tx_found, mut tx_missing) = (0, 0);
+    let (mut account_found, mut account_missing) = (0, 0);
+    let (mut receiver_found, mut receiver_missing) = (0, 0);
+
+    let mut copy = |write_batch: &mut rocksdb::WriteBatch,
+                    source_cf: &&rocksdb::ColumnFamily,
+                    target_cf: &&rocksdb::ColumnFamily,
+                    key: &[u8]|
+     -> Result<bool> {
+        match source_db.get_cf(source_cf, key)? {
+            Some(value) => {
+                write_batch.put_cf(target_cf, key, &value);
+                written += 1;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    };
+
+    for entry_hash in entry_hashes {
+        let Some(entry_data) = source_db.get_cf(&source_entry_cf, entry_hash)? else {
+            continue;
+        };
+        let packed_txs = match tx::entry_txs(&entry_data) {
+            Ok(packed_txs) => packed_txs,
+            Err(e) => {
+                println!("⚠️  Could not read txs of entry {}: {}", hex::encode(entry_hash), e);
+                undecodable_entries += 1;
+                continue;
+            }
+        };
+
+        for packed in packed_txs {
+            txs_seen += 1;
+            let decoded = match tx::decode_tx(&packed) {
+                Ok(decoded) => decoded,
+                Err(_) => {
+                    undecodable_txs += 1;
+                    continue;
+                }
+            };
+            if !decoded.hash_matches() {
+                hash_mismatches += 1;
+            }
+
+            if copy(&mut write_batch, source_tx_cf, target_tx_cf, &decoded.hash)? {
+                tx_found += 1;
+            } else {
+                tx_missing += 1;
+            }
+
+            let account_key = tx::nonce_index_key(&decoded.signer, decoded.nonce);
+            if copy(&mut write_batch, source_account_cf, target_account_cf, &account_key)? {
+                account_found += 1;
+            } else {
+                account_missing += 1;
+            }
+
+            for receiver in decoded.receivers() {
+                let receiver_key = tx::nonce_index_key(&receiver, decoded.nonce);
+                if copy(&mut write_batch, source_receiver_cf, target_receiver_cf, &receiver_key)? {
+                    receiver_found += 1;
+                } else {
+                    receiver_missing += 1;
+                }
+            }
+        }
+
+        if write_batch.len() >= batch_size {
+            target_db.write(std::mem::take(&mut write_batch))?;
+            println!("📦 Migrated transaction records for {} txs...", txs_seen);
+        }
+    }
+
+    if !write_batch.is_empty() {
+        target_db.write(write_batch)?;
+    }
+
+    println!("✅ transaction migration complete: {} kvs migrated for {} txs", written, txs_seen);
+    println!("   - tx pointers: {} migrated, {} missing in source", tx_found, tx_missing);
+    println!("   - tx_account_nonce: {} migrated, {} missing in source", account_found, account_missing);
+    println!("   - tx_receiver_nonce: {} migrated, {} missing in source", receiver_found, receiver_missing);
+    if hash_mismatches > 0 {
+        println!("⚠️  {} txs have a stored hash that differs from blake3 of their body", hash_mismatches);
+    }
+    if undecodable_entries > 0 || undecodable_txs > 0 {
+        println!("⚠️  {} entries and {} txs could not be decoded", undecodable_entries, undecodable_txs);
+    }
+    Ok(())
+}
+
 // Helper functions for entry parsing and chain following
 
 fn parse_entry_metadata(entry_data: &[u8]) -> Result<(u64, u64, Vec<u8>)> {
@@ -979,6 +1821,10 @@ fn parse_vecpak_entry_metadata(entry_term: &amadeus_fabric_doctor::vecpak::Term)
                                         }
                                     }
                                 }
+                            } else if let Ok((h, s, _prev_hash)) = reencode::etf_header_fields(header_bytes) {
+                                // Entry re-encoded with its original ETF header kept
+                                height = h;
+                                slot = s;
                             }
                         }
                         VTerm::PropList(header_props) => {
@@ -1020,6 +1866,29 @@ fn parse_vecpak_entry_metadata(entry_term: &amadeus_fabric_doctor::vecpak::Term)
     }
 }
 
+/// A binary field of an entry's header (`prev_hash`, `signer`, ...), for vecpak entries with a
+/// vecpak or kept ETF header and for ETF entries
+fn entry_header_binary(entry_data: &[u8], field: &str) -> Option<Vec<u8>> {
+    use amadeus_fabric_doctor::vecpak::Term as VTerm;
+
+    let header = match decode_term_from_slice(entry_data) {
+        Ok(entry) => tx::vecpak_get(&entry, b"header")?.clone(),
+        Err(_) => {
+            let entry = Term::decode(entry_data).ok()?;
+            let header_bin = tx::etf_binary(&entry, "header")?;
+            return tx::etf_binary(&Term::decode(&header_bin[..]).ok()?, field);
+        }
+    };
+    match header {
+        VTerm::PropList(_) => tx::vecpak_binary(&header, field.as_bytes()),
+        VTerm::Binary(header_bin) => match decode_term_from_slice(&header_bin) {
+            Ok(header) => tx::vecpak_binary(&header, field.as_bytes()),
+            Err(_) => tx::etf_binary(&Term::decode(&header_bin[..]).ok()?, field),
+        },
+        _ => None,
+    }
+}
+
 fn find_entry_at_height_indexed(
     source_db: &DB,
     source_entry_meta_cf: &impl rocksdb::AsColumnFamilyRef,
@@ -1045,10 +1914,64 @@ fn find_entry_at_height_indexed(
     Ok(None)
 }
 
+/// Queue every indexed entry at `height` (and its by_height key) into `write_batch`.
+/// Returns the hashes of the entries found, forks included.
+fn copy_entries_at_height(
+    source_db: &DB,
+    source_entry_meta_cf: &impl rocksdb::AsColumnFamilyRef,
+    source_entry_cf: &impl rocksdb::AsColumnFamilyRef,
+    write_batch: &mut rocksdb::WriteBatch,
+    target_entry_cf: &impl rocksdb::AsColumnFamilyRef,
+    target_entry_meta_cf: &impl rocksdb::AsColumnFamilyRef,
+    height: u64,
+) -> Result<Vec<Vec<u8>>> {
+    let height_padded = format!("{:012}", height);
+    let height_prefix = format!("by_height:{}:", height_padded);
+    let mut entry_hashes = Vec::new();
+
+    let iter = source_db.iterator_cf(source_entry_meta_cf, rocksdb::IteratorMode::From(height_prefix.as_bytes(), rocksdb::Direction::Forward));
+    for item in iter {
+        let (index_key, entry_hash) = item?;
+
+        // Check if we're still in the right height range
+        if !index_key.starts_with(height_prefix.as_bytes()) {
+            break; // Moved past this height
+        }
+
+        if let Some(entry_data) = source_db.get_cf(source_entry_cf, &entry_hash)? {
+            write_batch.put_cf(target_entry_cf, &entry_hash, &entry_data);
+            write_batch.put_cf(target_entry_meta_cf, &index_key, &entry_hash);
+            entry_hashes.push(entry_hash.to_vec());
+        }
+    }
+
+    Ok(entry_hashes)
+}
+
 fn get_prev_height_from_entry(entry_data: &[u8], source_db: &DB) -> Result<Option<u64>> {
     // Try vecpak decoding first
     if let Ok(vecpak_term) = decode_term_from_slice(entry_data) {
-        return get_prev_height_from_vecpak_entry(&vecpak_term, source_db);
+        let prev_height = get_prev_height_from_vecpak_entry(&vecpak_term, source_db)?;
+        if prev_height.is_some() {
+            return Ok(prev_height);
+        }
+
+        // Entry re-encoded with its original ETF header kept
+        if let Some(amadeus_fabric_doctor::vecpak::Term::Binary(header_bin)) = tx::vecpak_get(&vecpak_term, b"header") {
+            if let Ok((_height, _slot, Some(prev_hash))) = reencode::etf_header_fields(header_bin) {
+                if prev_hash == [0u8; 32] {
+                    return Ok(Some(0));
+                }
+                let entry_cf = source_db.cf_handle("entry")
+                    .ok_or_else(|| anyhow!("entry CF not found"))?;
+                if let Some(prev_entry_data) = source_db.get_cf(&entry_cf, &prev_hash)? {
+                    if let Ok((prev_height, _slot, _hash)) = parse_entry_metadata(&prev_entry_data) {
+                        return Ok(Some(prev_height));
+                    }
+                }
+            }
+        }
+        return Ok(None);
     }
 
     // Fallback to ETF
@@ -1102,101 +2025,39 @@ fn show_tips(db: &DB) -> Result<()> {
     println!("📊 Blockchain Tips from sysconf");
     println!("{}", "=".repeat(80));
 
-    let sysconf_cf = db
-        .cf_handle("sysconf")
-        .ok_or_else(|| anyhow!("sysconf column family not found"))?;
-
-    // Get temporal_height
-    if let Some(value) = db.get_cf(&sysconf_cf, "temporal_height".as_bytes())? {
-        if let Ok(term) = Term::decode(&value[..]) {
-            let height = match term {
-                Term::BigInteger(big_int) => big_int.value.clone().try_into().unwrap_or(0),
-                Term::FixInteger(fix_int) => fix_int.value as u64,
-                _ => 0,
-            };
-            println!("Temporal Height: {}", height);
-        }
-    } else {
-        println!("Temporal Height: (not found)");
-    }
+    // Every declared key, decoded with the encoding the node expects
+    sysconf::print_report(db)?;
 
-    // Check for temporal_tip (might not exist)
-    if let Some(value) = db.get_cf(&sysconf_cf, "temporal_tip".as_bytes())? {
-        println!("Temporal Tip:    {}", hex::encode(&value));
-    }
-
-    // Get rooted_tip
-    if let Some(rooted_tip_hash) = db.get_cf(&sysconf_cf, "rooted_tip".as_bytes())? {
-        println!("Rooted Tip:      {}", hex::encode(&rooted_tip_hash));
+    // Height and slot of the rooted tip entry itself
+    if let Some(rooted_tip_hash) = sysconf::read_hash(db, sysconf::ROOTED_TIP)? {
+        let entry_cf = db
+            .cf_handle("entry")
+            .ok_or_else(|| anyhow!("entry CF not found"))?;
 
-        // Try to get the height of the rooted tip
-        let default_cf = db
-            .cf_handle("default")
-            .ok_or_else(|| anyhow!("default CF not found"))?;
-
-        if let Some(entry_data) = db.get_cf(&default_cf, &rooted_tip_hash)? {
+        if let Some(entry_data) = db.get_cf(&entry_cf, &rooted_tip_hash)? {
             if let Ok((height, slot, _hash)) = parse_entry_metadata(&entry_data) {
-                println!("Rooted Height:   {}", height);
-                println!("Rooted Slot:     {}", slot);
+                println!("{:<34} {}", "Rooted Entry Height:", height);
+                println!("{:<34} {}", "Rooted Entry Slot:", slot);
             }
         }
-    } else {
-        println!("Rooted Tip:      (not found)");
     }
 
     println!("{}", "=".repeat(80));
     Ok(())
 }
 
-fn list_keys(db: &DB, cf_handle: &impl rocksdb::AsColumnFamilyRef) -> Result<()> {
-    let iter = db.iterator_cf(cf_handle, rocksdb::IteratorMode::Start);
-    let mut count = 0;
-
-    println!("Keys in contractstate column family:");
-    println!("{:<4} {:<50} {}", "No.", "Decoded Key", "Raw Hex");
-    println!("{:-<80}", "");
-    
-    for item in iter {
-        let (key, _) = item?;
-        let decoded_key = decode_contractstate_key(&key);
-        let hex_key = hex::encode(&key);
-        
-        // Truncate very long keys for display
-        let display_decoded = if decoded_key.len() > 45 {
-            format!("{}...", &decoded_key[..42])
-        } else {
-            decoded_key
-        };
-        
-        let display_hex = if hex_key.len() > 20 {
-            format!("{}...", &hex_key[..17])
-        } else {
-            hex_key
-        };
-        
-        println!("{:<4} {:<50} {}", count, display_decoded, display_hex);
-        count += 1;
-
-        if count >= 100 {
-            println!("... (showing first 100 keys, use --export to see all)");
-            break;
-        }
-    }
-
-    println!("\nTotal keys shown: {}", count);
-    Ok(())
-}
-
 fn get_value(
     db: &DB,
+    cf_name: &str,
     cf_handle: &impl rocksdb::AsColumnFamilyRef,
-    key_hex: &str,
+    key: &[u8],
     raw: bool,
 ) -> Result<()> {
-    let key = hex::decode(key_hex)?;
-    
-    if let Some(value) = db.get_cf(cf_handle, &key)? {
-        println!("Key: {}", key_hex);
+    let key_hex = hex::encode(key);
+
+    if let Some(value) = db.get_cf(cf_handle, key)? {
+        println!("Key: {} ({})", key_hex, describe_key(cf_name, key));
+        println!("Column family: {}", cf_name);
         println!("Raw value size: {} bytes", value.len());
         
         if raw {
@@ -1213,78 +2074,47 @@ fn get_value(
             }
         }
     } else {
-        println!("Key not found: {}", key_hex);
+        println!("Key not found in {}: {} ({})", cf_name, describe_key(cf_name, key), key_hex);
     }
 
     Ok(())
 }
 
-fn export_all_data(
-    db: &DB,
-    cf_handle: &impl rocksdb::AsColumnFamilyRef,
-    output_file: &str,
-    raw: bool,
-) -> Result<()> {
-    let iter = db.iterator_cf(cf_handle, rocksdb::IteratorMode::Start);
-    let mut data = serde_json::Map::new();
-    let mut count = 0;
-    let mut failed_parse_count = 0;
-
-    println!("Exporting contractstate data to {}...", output_file);
-
-    for item in iter {
-        let (key, value) = item?;
-        
-        // Use decoded key as the JSON key, with hex fallback
-        let json_key = decode_contractstate_key(&key);
-
-        if raw {
-            data.insert(json_key, json!(hex::encode(&value)));
-        } else {
-            match parse_etf_to_json(&value) {
-                Ok(parsed_value) => {
-                    data.insert(json_key, parsed_value);
-                }
-                Err(e) => {
-                    failed_parse_count += 1;
-                    // Store with error details
-                    data.insert(
-                        json_key.clone(),
-                        json!({
-                            "parse_error": format!("Failed to parse: {}", e),
-                            "raw_hex": hex::encode(&value),
-                            "as_string": std::str::from_utf8(&value).unwrap_or("<invalid UTF-8>"),
-                            "size_bytes": value.len()
-                        }),
-                    );
-                }
-            }
-        }
-
-        count += 1;
-        if count % 1000 == 0 {
-            println!("Processed {} entries...", count);
-        }
-    }
-
-    let final_json = json!({
-        "metadata": {
-            "total_entries": count,
-            "failed_parses": failed_parse_count,
-            "export_time": chrono::Utc::now().to_rfc3339(),
-            "raw_mode": raw,
-            "key_decoding": "enabled"
+/// Printable form of a key of any column family: decoded contractstate and entry_meta index keys,
+/// UTF-8 when the key is text, hex otherwise
+fn describe_key(cf_name: &str, key: &[u8]) -> String {
+    match cf_name {
+        "contractstate" => decode_contractstate_key(key),
+        "entry_meta" if entry_index::decode_index_key(key).is_some() => entry_index::describe_index_key(key),
+        _ => match std::str::from_utf8(key) {
+            Ok(text) if !text.chars().any(char::is_control) => text.to_string(),
+            _ => format!("hex:{}", hex::encode(key)),
         },
-        "data": data
-    });
+    }
+}
 
-    std::fs::write(output_file, serde_json::to_string_pretty(&final_json)?)?;
-    println!(
-        "Export complete: {} entries, {} failed parses",
-        count, failed_parse_count
-    );
+/// Common prefixes in contractstate, followed by binary public keys in many keys
+const CONTRACTSTATE_KEY_PREFIXES: &[&str] = &[
+    "bic:coin:balance:",
+    "bic:epoch:trainers:",
+    "bic:epoch:pop:",
+    "bic:base:nonce:",
+    "bic:epoch:emission_address:",
+    "bic:epoch:segment_vr_hash",
+    "bic:epoch:solutions_count:",
+    "bic:contract:account:",
+    "bic:coin:",
+    "bic:epoch:",
+];
+
+/// Bytes that can appear in Base58 text
+fn is_base58_char(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() && !matches!(byte, b'0' | b'O' | b'I' | b'l')
+}
 
-    Ok(())
+/// 48 bytes that are shown as a Base58 public key (not all zeros or all 0xFF)
+fn looks_like_public_key(bytes: &[u8]) -> bool {
+    bytes.len() >= 48 && !bytes[..48].iter().all(|&b| b == 0) && !bytes[..48].iter().all(|&b| b == 0xFF)
 }
 
 fn decode_contractstate_key(key: &[u8]) -> String {
@@ -1299,22 +2129,8 @@ fn decode_contractstate_key(key: &[u8]) -> String {
     let mut result = String::new();
     let mut pos = 0;
     
-    // Common prefixes in contractstate
-    let prefixes = [
-        "bic:coin:balance:",
-        "bic:epoch:trainers:",
-        "bic:epoch:pop:",
-        "bic:base:nonce:",
-        "bic:epoch:emission_address:",
-        "bic:epoch:segment_vr_hash",
-        "bic:epoch:solutions_count:",
-        "bic:contract:account:",
-        "bic:coin:",
-        "bic:epoch:",
-    ];
-    
     // Try to find a matching prefix
-    for prefix in &prefixes {
+    for prefix in CONTRACTSTATE_KEY_PREFIXES {
         if key.starts_with(prefix.as_bytes()) {
             result.push_str(prefix);
             pos = prefix.len();
@@ -1332,31 +2148,46 @@ fn decode_contractstate_key(key: &[u8]) -> String {
     }
     
     // Parse the rest of the key
+    let mut after_public_key = false;
     while pos < key.len() {
         // Try to detect patterns in the remaining data
         let remaining = &key[pos..];
+
+        // Separators are kept as text, the next segment is decoded on its own
+        if remaining[0] == b':' {
+            result.push(':');
+            pos += 1;
+            after_public_key = false;
+            continue;
+        }
+
+        // Base58 right after a public key would make its end ambiguous, show the rest in hex
+        if after_public_key && (looks_like_public_key(remaining) || is_base58_char(remaining[0])) {
+            result.push_str(&format!(":hex:{}", hex::encode(remaining)));
+            break;
+        }
+        after_public_key = false;
         
         // Check for 48-byte public key (Base58 encoded in display)
-        if remaining.len() >= 48 {
-            let maybe_pk = &remaining[0..48];
-            // Check if this looks like a public key (not all zeros or all 0xFF)
-            if !maybe_pk.iter().all(|&b| b == 0) && !maybe_pk.iter().all(|&b| b == 0xFF) {
-                // This looks like a public key, encode it in Base58
-                let base58_pk = bs58::encode(maybe_pk).into_string();
-                result.push_str(&base58_pk);
-                pos += 48;
-                
-                // Check if there's more data after the public key
-                if pos < key.len() {
-                    let remainder = &key[pos..];
-                    // Try to parse remainder as string (like ":AMA" suffix)
-                    if let Ok(suffix) = std::str::from_utf8(remainder) {
+        if looks_like_public_key(remaining) {
+            // This looks like a public key, encode it in Base58
+            let base58_pk = bs58::encode(&remaining[..48]).into_string();
+            result.push_str(&base58_pk);
+            pos += 48;
+
+            // Check if there's more data after the public key
+            if pos < key.len() {
+                let remainder = &key[pos..];
+                // Try to parse remainder as string (like ":AMA" suffix)
+                if let Ok(suffix) = std::str::from_utf8(remainder) {
+                    if !is_base58_char(remainder[0]) {
                         result.push_str(suffix);
                         break;
                     }
                 }
-                continue;
             }
+            after_public_key = true;
+            continue;
         }
         
         // Check for 12-digit height padding (like "000000319557")
@@ -1397,6 +2228,121 @@ fn decode_contractstate_key(key: &[u8]) -> String {
     result
 }
 
+/// Prefixes of the contractstate keys the node writes as `<prefix><public key>[suffix]`, e.g.
+/// `bic:coin:balance:<pk>:AMA`, `bic:base:nonce:<pk>` or `bic:contract:account:<pk>:<field>`
+const PUBLIC_KEY_KEY_SCHEMAS: &[&str] = &[
+    "bic:coin:balance:",
+    "bic:base:nonce:",
+    "bic:epoch:pop:",
+    "bic:epoch:emission_address:",
+    "bic:epoch:solutions_count:",
+    "bic:contract:account:",
+];
+
+/// Segments `encode_contractstate_key_rest` descends through before giving up on a key
+const MAX_KEY_SEGMENTS: usize = 16;
+
+/// Inverse of `decode_contractstate_key`: the raw key bytes of a decoded key such as
+/// `bic:coin:balance:<Base58 pk>:AMA`. Keys of the known schemas are built directly; other
+/// prefixed keys are searched for (Base58 public keys, padded heights and nonces, `:hex:` tails).
+/// Every candidate is checked by decoding it again, and text that doesn't decode from anything
+/// else is taken as a UTF-8 key.
+fn encode_contractstate_key(decoded: &str) -> Vec<u8> {
+    if let Some(key) = encode_known_contractstate_key(decoded) {
+        return key;
+    }
+    if let Some(prefix) = CONTRACTSTATE_KEY_PREFIXES.iter().find(|prefix| decoded.starts_with(*prefix)) {
+        if let Some(key) = encode_contractstate_key_rest(&decoded[prefix.len()..], prefix.as_bytes().to_vec(), decoded, 0) {
+            return key;
+        }
+    }
+    if let Some(hex_text) = decoded.strip_prefix("hex:") {
+        if let Ok(key) = hex::decode(hex_text) {
+            if decode_contractstate_key(&key) == decoded {
+                return key;
+            }
+        }
+    }
+    decoded.as_bytes().to_vec()
+}
+
+/// `<prefix><Base58 pk><suffix>` of a known schema: the Base58 run is the public key and the
+/// suffix is a padded number or one `:<name>` segment too short to hold another public key, kept
+/// as text. Anything longer (`:hex:` tails, more segments) is left to the search.
+fn encode_known_contractstate_key(decoded: &str) -> Option<Vec<u8>> {
+    let prefix = PUBLIC_KEY_KEY_SCHEMAS.iter().find(|prefix| decoded.starts_with(*prefix))?;
+    let rest = &decoded[prefix.len()..];
+    let base58_len = rest.bytes().take_while(|b| is_base58_char(*b)).count();
+    let (base58, suffix) = rest.split_at(base58_len);
+    let plain_suffix = suffix.bytes().all(|b| b.is_ascii_digit())
+        || suffix.strip_prefix(':').is_some_and(|name| name.len() < 48 && !name.contains(':'));
+    if !plain_suffix {
+        return None;
+    }
+    let public_key = bs58::decode(base58).into_vec().ok().filter(|public_key| public_key.len() == 48)?;
+    let key = [prefix.as_bytes(), &public_key, suffix.as_bytes()].concat();
+    (decode_contractstate_key(&key) == decoded).then_some(key)
+}
+
+/// Extend `key` with the encoding of `rest`, trying the same patterns as the decoder in order.
+/// Fallback for keys outside the known schemas, bounded to MAX_KEY_SEGMENTS levels.
+fn encode_contractstate_key_rest(rest: &str, key: Vec<u8>, decoded: &str, depth: usize) -> Option<Vec<u8>> {
+    let with = |bytes: &[u8]| {
+        let mut extended = key.clone();
+        extended.extend_from_slice(bytes);
+        extended
+    };
+    let matches = |candidate: &[u8]| decode_contractstate_key(candidate) == decoded;
+
+    if rest.is_empty() {
+        return matches(&key).then_some(key);
+    }
+    if depth >= MAX_KEY_SEGMENTS {
+        return None;
+    }
+
+    // Base58 public key: the encoded length varies, so try every length that yields 48 bytes
+    let base58_run = rest.bytes().take_while(|b| is_base58_char(*b)).count();
+    for len in (1..=base58_run.min(70)).rev() {
+        if let Ok(public_key) = bs58::decode(&rest[..len]).into_vec() {
+            if public_key.len() == 48 {
+                if let Some(encoded) = encode_contractstate_key_rest(&rest[len..], with(&public_key), decoded, depth + 1) {
+                    return Some(encoded);
+                }
+            }
+        }
+    }
+
+    // Zero-padded heights and nonces are plain digits in the key
+    for digits in [20, 12] {
+        if rest.len() >= digits && rest.as_bytes()[..digits].iter().all(|b| b.is_ascii_digit()) {
+            if let Some(encoded) = encode_contractstate_key_rest(&rest[digits..], with(&rest.as_bytes()[..digits]), decoded, depth + 1) {
+                return Some(encoded);
+            }
+        }
+    }
+
+    // Binary tail the decoder couldn't interpret
+    if let Some(hex_text) = rest.strip_prefix(":hex:") {
+        if let Ok(bytes) = hex::decode(hex_text) {
+            let candidate = with(&bytes);
+            if matches(&candidate) {
+                return Some(candidate);
+            }
+        }
+    }
+
+    // Separator between segments, after the hex tail since a literal ":hex:" reads the same
+    if let Some(after_separator) = rest.strip_prefix(':') {
+        if let Some(encoded) = encode_contractstate_key_rest(after_separator, with(b":"), decoded, depth + 1) {
+            return Some(encoded);
+        }
+    }
+
+    let candidate = with(rest.as_bytes());
+    matches(&candidate).then_some(candidate)
+}
+
 fn parse_etf_to_json(data: &[u8]) -> Result<serde_json::Value> {
     // First, try to parse as a string (many values in contractstate are plain strings)
     if let Ok(string_val) = std::str::from_utf8(data) {
@@ -1691,3 +2637,142 @@ fn extract_header_binary_from_entry(entry_packed: &[u8]) -> Result<Vec<u8>> {
     Err(anyhow!("Entry is not an ETF map"))
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli_rejects_invalid_combinations() {
+        Cli::command().debug_assert();
+
+        let cli = Cli::try_parse_from(["fd", "-d", "/db", "inspect", "get", "--cf", "entry_meta", "00ff"]).unwrap();
+        assert!(matches!(cli.command, Command::Inspect { command: InspectCommand::Get { ref cf, .. } } if cf.cf == "entry_meta"));
+        let cli = Cli::try_parse_from(["fd", "-d", "/db", "inspect", "scan"]).unwrap();
+        assert!(matches!(cli.command, Command::Inspect { command: InspectCommand::Scan { ref cf, limit: 100, .. } } if cf.cf == "contractstate"));
+
+        assert!(Cli::try_parse_from(["fd", "-d", "/db", "inspect", "get", "00", "--dry-run"]).is_err());
+        assert!(Cli::try_parse_from(["fd", "-d", "/db", "migrate", "weak", "/t", "--profile", "p.toml"]).is_err());
+        assert!(Cli::try_parse_from(["fd", "-d", "/db", "migrate", "full", "/t", "--include-keys", "bic:"]).is_err());
+        assert!(Cli::try_parse_from(["fd", "-d", "/db", "migrate", "weak", "/t", "--accounts", "x", "--bulk-load"]).is_err());
+        assert!(Cli::try_parse_from(["fd", "-d", "/db", "migrate", "weak", "/t", "--bulk-load-benchmark"]).is_err());
+        assert!(Cli::try_parse_from(["fd", "-d", "/db", "extract", "/t", "--from-height", "1"]).is_err());
+        assert!(Cli::try_parse_from(["fd", "-d", "/db", "tips", "--archive", "a.ndjson"]).is_err());
+        assert!(Cli::try_parse_from(["fd", "-d", "/db"]).is_err());
+        assert!(Cli::try_parse_from(["fd", "-d", "/db", "entry"]).is_err());
+        assert!(Cli::try_parse_from(["fd", "-d", "/db", "entry", "--height", "1", "--slot", "2"]).is_err());
+    }
+
+    #[test]
+    fn test_entry_header_binary() {
+        use amadeus_fabric_doctor::vecpak::{encode_term, Term as VTerm};
+
+        let header = VTerm::PropList(vec![
+            (VTerm::Binary(b"height".to_vec()), VTerm::VarInt(5)),
+            (VTerm::Binary(b"prev_hash".to_vec()), VTerm::Binary(vec![3u8; 32])),
+            (VTerm::Binary(b"signer".to_vec()), VTerm::Binary(vec![4u8; 48])),
+        ]);
+        let mut header_bin = Vec::new();
+        encode_term(&mut header_bin, header);
+        let entry = VTerm::PropList(vec![(VTerm::Binary(b"header".to_vec()), VTerm::Binary(header_bin))]);
+        let mut entry_data = Vec::new();
+        encode_term(&mut entry_data, entry);
+
+        assert_eq!(entry_header_binary(&entry_data, "prev_hash"), Some(vec![3u8; 32]));
+        assert_eq!(entry_header_binary(&entry_data, "signer"), Some(vec![4u8; 48]));
+        assert_eq!(entry_header_binary(&entry_data, "txs_hash"), None);
+        assert_eq!(entry_header_binary(b"garbage", "prev_hash"), None);
+    }
+
+    #[test]
+    fn test_contractstate_key_round_trip() {
+        let public_key: Vec<u8> = (0..48u8).map(|i| 0xA0 ^ i.wrapping_mul(37)).collect();
+        let other_key: Vec<u8> = (0..48u8).map(|i| 0x81 + i).collect();
+        let key = |parts: &[&[u8]]| parts.concat();
+
+        let keys = vec![
+            key(&[b"bic:coin:balance:", &public_key, b":AMA"]),
+            key(&[b"bic:base:nonce:", &public_key]),
+            key(&[b"bic:epoch:trainers:height:000000319557"]),
+            key(&[b"bic:epoch:pop:", &public_key]),
+            key(&[b"bic:epoch:solutions_count:", &public_key, b"00000000000000000042"]),
+            key(&[b"bic:coin:allowance:", &public_key, &other_key]),
+            key(&[b"bic:epoch:emission_address:", &public_key, &[0xFF, 0x00, 0xFE]]),
+            key(&[b"bic:contract:account:", &other_key, b":000000000007:", &public_key]),
+            key(&[b"bic:epoch:segment_vr_hash"]),
+            key(&[&[0xC3, 0x28, 0x01], b"unprefixed"]),
+            key(&[b"bic:coin:", &[0x80, 0x81]]),
+            key(&[b"plain text key"]),
+        ];
+        for raw in &keys {
+            let decoded = decode_contractstate_key(raw);
+            assert_eq!(&encode_contractstate_key(&decoded), raw, "round trip of {}", decoded);
+        }
+
+        let decoded = decode_contractstate_key(&keys[0]);
+        assert!(decoded.starts_with("bic:coin:balance:") && decoded.ends_with(":AMA") && !decoded.contains("hex:"));
+
+        // Known schemas are built without searching, the rest falls back to the bounded search
+        for raw in &keys[..2] {
+            assert_eq!(encode_known_contractstate_key(&decode_contractstate_key(raw)).as_ref(), Some(raw));
+        }
+        assert_eq!(encode_known_contractstate_key(&decode_contractstate_key(&keys[5])), None);
+        let many_segments = format!("bic:coin:{}", vec![bs58::encode(&public_key).into_string(); 64].join(":"));
+        assert_eq!(decode_contractstate_key(&encode_contractstate_key(&many_segments)), many_segments);
+    }
+
+    #[test]
+    fn test_contractstate_key_round_trip_from_db() {
+        let path = std::env::temp_dir().join(format!("fabric_doctor_key_round_trip_{}", std::process::id()));
+        let path = path.to_string_lossy().to_string();
+        let _ = std::fs::remove_dir_all(&path);
+        create_target_database(&path).unwrap();
+        let db = open_target_database_readwrite(&path).unwrap();
+        let contractstate_cf = db.cf_handle("contractstate").unwrap();
+
+        // Deterministic pseudo-random bytes: blake3 of a counter
+        let mut counter = 0u64;
+        let mut random = |len: usize| -> Vec<u8> {
+            counter += 1;
+            let mut output = vec![0u8; len];
+            blake3::Hasher::new().update(&counter.to_le_bytes()).finalize_xof().fill(&mut output);
+            output
+        };
+
+        let mut inserted = std::collections::BTreeSet::new();
+        for i in 0..2000u64 {
+            let public_key = random(48);
+            let other_key = random(48);
+            let seed = random(16);
+            let number = u64::from_le_bytes(seed[..8].try_into().unwrap());
+            let tail = random(seed[8] as usize % 9);
+            let key = match i % 10 {
+                0 => [b"bic:coin:balance:", &public_key[..], b":AMA"].concat(),
+                1 => [b"bic:base:nonce:", &public_key[..]].concat(),
+                2 => format!("bic:epoch:trainers:height:{:012}", number % 1_000_000_000_000).into_bytes(),
+                3 => [b"bic:epoch:solutions_count:", &public_key[..], format!("{:020}", number).as_bytes()].concat(),
+                4 => [b"bic:coin:allowance:", &public_key[..], &other_key[..]].concat(),
+                5 => [b"bic:contract:account:", &other_key[..], format!(":{:012}:", number % 1_000_000).as_bytes(), &public_key[..]].concat(),
+                6 => [b"bic:epoch:emission_address:", &public_key[..], &tail[..]].concat(),
+                7 => [b"bic:coin:", &tail[..]].concat(),
+                8 => [&tail[..], b":", &public_key[..]].concat(),
+                _ => [&seed[..], &tail[..]].concat(),
+            };
+            db.put_cf(&contractstate_cf, &key, b"").unwrap();
+            inserted.insert(key);
+        }
+
+        let mut checked = 0;
+        for item in db.iterator_cf(&contractstate_cf, rocksdb::IteratorMode::Start) {
+            let (key, _) = item.unwrap();
+            let decoded = decode_contractstate_key(&key);
+            assert_eq!(encode_contractstate_key(&decoded), key.to_vec(), "round trip of {}", decoded);
+            checked += 1;
+        }
+        assert_eq!(checked, inserted.len());
+
+        drop(db);
+        let _ = std::fs::remove_dir_all(&path);
+    }
+}
@@ -0,0 +1,247 @@
+use anyhow::{anyhow, Result};
+use rocksdb::DB;
+use serde::{Deserialize, Serialize};
+
+use crate::digest;
+use crate::sysconf::{self, SysconfValue};
+
+/// Copy of the manifest written next to the RocksDB files
+pub const MANIFEST_FILE: &str = "fabric_doctor_manifest.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SourceInfo {
+    pub path: String,
+    pub temporal_tip: Option<String>,
+    pub rooted_tip: Option<String>,
+    pub temporal_height: Option<u64>,
+    pub rooted_height: Option<u64>,
+}
+
+impl SourceInfo {
+    /// Tips from sysconf, heights from the tip entries' headers
+    fn read(db: &DB, path: &str) -> Result<Self> {
+        let entry_cf = db.cf_handle("entry");
+
+        let mut tips = Vec::new();
+        for tip_key in [sysconf::TEMPORAL_TIP, sysconf::ROOTED_TIP] {
+            let tip = sysconf::read_hash(db, tip_key)?;
+            let height = match (&tip, &entry_cf) {
+                (Some(hash), Some(entry_cf)) => db
+                    .get_cf(entry_cf, hash)?
+                    .and_then(|entry_data| crate::parse_entry_metadata(&entry_data).ok())
+                    .map(|(height, _slot, _hash)| height),
+                _ => None,
+            };
+            tips.push((tip.map(hex::encode), height));
+        }
+        let (rooted_tip, rooted_height) = tips.pop().unwrap_or_default();
+        let (temporal_tip, temporal_height) = tips.pop().unwrap_or_default();
+
+        Ok(SourceInfo { path: path.to_string(), temporal_tip, rooted_tip, temporal_height, rooted_height })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CfManifest {
+    pub name: String,
+    pub key_count: u64,
+    pub byte_count: u64,
+    pub digest: String,
+}
+
+/// Where a database came from: written into sysconf and as a JSON file at the end of a
+/// migration, extraction or weak migration. Digests are taken before the manifest itself is
+/// added to sysconf; column families copied in full reuse the digest of the copy's verification.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MigrationManifest {
+    pub tool_version: String,
+    pub command: String,
+    pub source: SourceInfo,
+    pub target_path: String,
+    /// Profile, filter or range the command ran with
+    pub parameters: serde_json::Value,
+    pub column_families: Vec<CfManifest>,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+}
+
+impl MigrationManifest {
+    pub fn start(command: &str, source_db: &DB, source_path: &str, target_path: &str, parameters: serde_json::Value) -> Result<Self> {
+        Ok(MigrationManifest {
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            command: command.to_string(),
+            source: SourceInfo::read(source_db, source_path)?,
+            target_path: target_path.to_string(),
+            parameters,
+            column_families: Vec::new(),
+            started_at: chrono::Utc::now().to_rfc3339(),
+            finished_at: None,
+        })
+    }
+
+    /// Column families the recorded command copied in full, which should still match the source.
+    /// `None` for commands that don't record it.
+    pub fn fully_copied_cfs(&self) -> Option<Vec<String>> {
+        match self.command.as_str() {
+            "migrate full" => serde_json::from_value(self.parameters["full_cfs"].clone()).ok(),
+            "migrate weak" => {
+                let filter = &self.parameters["contractstate_filter"];
+                let filtered = ["include", "exclude", "accounts"]
+                    .iter()
+                    .any(|field| filter[*field].as_array().is_some_and(|values| !values.is_empty()))
+                    || !filter["sample_one_in"].is_null();
+                let mut cfs = vec!["sysconf".to_string()];
+                if !filtered {
+                    cfs.push("contractstate".to_string());
+                }
+                Some(cfs)
+            }
+            "extract" if self.parameters["with_contractstate"] == true => Some(vec!["contractstate".to_string()]),
+            "extract" => Some(Vec::new()),
+            _ => None,
+        }
+    }
+
+    /// Record the digest of a column family that is not written again before `finish`
+    pub fn record_digest(&mut self, cf_digest: &digest::CfDigest) {
+        self.column_families.retain(|cf| cf.name != cf_digest.cf_name);
+        self.column_families.push(CfManifest {
+            name: cf_digest.cf_name.clone(),
+            key_count: cf_digest.key_count,
+            byte_count: cf_digest.byte_count,
+            digest: cf_digest.hex(),
+        });
+    }
+
+    /// Digest the column families without a recorded digest and record the manifest in the target
+    pub fn finish(mut self, target_db: &DB) -> Result<()> {
+        println!("📜 Writing migration manifest...");
+        let cf_names = DB::list_cf(&rocksdb::Options::default(), &self.target_path)?;
+        for cf_name in &cf_names {
+            if self.column_families.iter().any(|cf| &cf.name == cf_name) {
+                continue;
+            }
+            let Some(cf) = target_db.cf_handle(cf_name) else {
+                continue;
+            };
+            let cf_digest = digest::digest_column_family(target_db, &cf, cf_name, digest::DEFAULT_RANGE_SIZE)?;
+            self.record_digest(&cf_digest);
+        }
+        self.column_families.sort_by(|a, b| a.name.cmp(&b.name));
+        self.finished_at = Some(chrono::Utc::now().to_rfc3339());
+
+        let json = serde_json::to_vec_pretty(&self)?;
+        sysconf::write(target_db, sysconf::MIGRATION_MANIFEST, &SysconfValue::Json(serde_json::to_value(&self)?))?;
+
+        let file_path = std::path::Path::new(&self.target_path).join(MANIFEST_FILE);
+        std::fs::write(&file_path, &json)
+            .map_err(|e| anyhow!("Failed to write manifest file {}: {}", file_path.display(), e))?;
+        println!("✅ Manifest stored in sysconf ({}) and {}", sysconf::MIGRATION_MANIFEST, file_path.display());
+        Ok(())
+    }
+}
+
+pub fn read_manifest(db: &DB) -> Result<Option<MigrationManifest>> {
+    match sysconf::read(db, sysconf::MIGRATION_MANIFEST)? {
+        Some(sysconf::Decoded { value: SysconfValue::Json(json), .. }) => Ok(Some(
+            serde_json::from_value(json).map_err(|e| anyhow!("Invalid migration manifest in sysconf: {}", e))?,
+        )),
+        Some(_) => Err(anyhow!("Migration manifest in sysconf is not JSON")),
+        None => Ok(None),
+    }
+}
+
+/// Print the manifest of a database (the `info` command)
+pub fn show_info(db: &DB) -> Result<()> {
+    let Some(manifest) = read_manifest(db)? else {
+        println!("ℹ️  No migration manifest in sysconf: this database was not produced by a migration");
+        return Ok(());
+    };
+
+    let tip = |hash: &Option<String>, height: Option<u64>| match (hash, height) {
+        (Some(hash), Some(height)) => format!("{} (height {})", hash, height),
+        (Some(hash), None) => hash.clone(),
+        (None, _) => "not set".to_string(),
+    };
+
+    println!("📜 Migration manifest");
+    println!("   - Command: {} (amadeus-fabric-doctor {})", manifest.command, manifest.tool_version);
+    println!("   - Started: {}", manifest.started_at);
+    println!("   - Finished: {}", manifest.finished_at.as_deref().unwrap_or("not finished"));
+    println!("   - Source: {}", manifest.source.path);
+    println!("   - Source temporal tip: {}", tip(&manifest.source.temporal_tip, manifest.source.temporal_height));
+    println!("   - Source rooted tip: {}", tip(&manifest.source.rooted_tip, manifest.source.rooted_height));
+    println!("   - Target: {}", manifest.target_path);
+    println!("   - Parameters:");
+    for line in serde_json::to_string_pretty(&manifest.parameters)?.lines() {
+        println!("     {}", line);
+    }
+    println!("   - Column families at the end of the run:");
+    for cf in &manifest.column_families {
+        println!("     - {}: {} kvs, {} bytes, digest {}", cf.name, cf.key_count, cf.byte_count, cf.digest);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finish_round_trip() {
+        let dir = std::env::temp_dir().join(format!("fabric_doctor_manifest_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let source_path = dir.join("source").to_string_lossy().to_string();
+        let target_path = dir.join("target").to_string_lossy().to_string();
+        crate::create_target_database(&source_path).unwrap();
+        crate::create_target_database(&target_path).unwrap();
+        let source_db = crate::open_target_database_readwrite(&source_path).unwrap();
+        let target_db = crate::open_target_database_readwrite(&target_path).unwrap();
+
+        let entry_hash = [7u8; 32];
+        sysconf::write(&source_db, sysconf::TEMPORAL_TIP, &SysconfValue::Hash(entry_hash.to_vec())).unwrap();
+        let contractstate = target_db.cf_handle("contractstate").unwrap();
+        target_db.put_cf(&contractstate, b"bic:base:nonce:a", b"1").unwrap();
+        target_db.put_cf(&target_db.cf_handle("entry").unwrap(), entry_hash, b"entry").unwrap();
+
+        let mut manifest = MigrationManifest::start(
+            "migrate weak",
+            &source_db,
+            &source_path,
+            &target_path,
+            serde_json::json!({ "bulk_load": false }),
+        )
+        .unwrap();
+        // A recorded digest is kept as is, the other column families are digested by finish
+        let recorded = digest::digest_column_family(&target_db, &contractstate, "contractstate", digest::DEFAULT_RANGE_SIZE).unwrap();
+        manifest.record_digest(&recorded);
+        target_db.put_cf(&contractstate, b"bic:base:nonce:b", b"2").unwrap();
+        manifest.finish(&target_db).unwrap();
+
+        let stored = read_manifest(&target_db).unwrap().unwrap();
+        let file: MigrationManifest =
+            serde_json::from_slice(&std::fs::read(std::path::Path::new(&target_path).join(MANIFEST_FILE)).unwrap()).unwrap();
+        assert_eq!(serde_json::to_value(&stored).unwrap(), serde_json::to_value(&file).unwrap());
+
+        assert_eq!(stored.command, "migrate weak");
+        assert_eq!(stored.source.path, source_path);
+        assert_eq!(stored.source.temporal_tip, Some(hex::encode(entry_hash)));
+        assert_eq!(stored.source.rooted_tip, None);
+        assert_eq!(stored.target_path, target_path);
+        assert_eq!(stored.parameters, serde_json::json!({ "bulk_load": false }));
+        assert!(stored.finished_at.is_some());
+
+        let names: Vec<&str> = stored.column_families.iter().map(|cf| cf.name.as_str()).collect();
+        let mut expected = crate::TARGET_COLUMN_FAMILIES.to_vec();
+        expected.sort();
+        assert_eq!(names, expected);
+        let cf = |name: &str| stored.column_families.iter().find(|cf| cf.name == name).unwrap();
+        assert_eq!((cf("contractstate").key_count, cf("contractstate").digest.clone()), (1, recorded.hex()));
+        assert_eq!(cf("entry").key_count, 1);
+        assert_eq!(cf("tx").key_count, 0);
+        show_info(&target_db).unwrap();
+
+        drop((source_db, target_db));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,146 @@
+//! Contractstate mutation lists stored in `entry:<hash>:muts` and `entry:<hash>:muts_rev`.
+//!
+//! Each mutation is a map (ETF, atom keys) or proplist (vecpak, binary keys) with an `op`
+//! (`put`, `delete`, `set_bit`, `clear_bit`), a `key`, and for `put` the `value`, for the bit
+//! operations the bit index in `value` and the bitfield size in bits in `bloomsize`.
+
+use anyhow::{anyhow, Result};
+use amadeus_fabric_doctor::vecpak::{self, Term as VTerm};
+use eetf::Term;
+
+use crate::tx::{etf_binary, etf_get, etf_u64, vecpak_binary, vecpak_get};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mutation {
+    Put { key: Vec<u8>, value: Vec<u8> },
+    Delete { key: Vec<u8> },
+    SetBit { key: Vec<u8>, bit: u64, bloom_size: u64 },
+    ClearBit { key: Vec<u8>, bit: u64 },
+}
+
+impl Mutation {
+    pub fn key(&self) -> &[u8] {
+        match self {
+            Mutation::Put { key, .. }
+            | Mutation::Delete { key }
+            | Mutation::SetBit { key, .. }
+            | Mutation::ClearBit { key, .. } => key,
+        }
+    }
+
+    /// New value of the key given its current value (`None` = absent)
+    pub fn apply(&self, current: Option<&[u8]>) -> Option<Vec<u8>> {
+        match self {
+            Mutation::Put { value, .. } => Some(value.clone()),
+            Mutation::Delete { .. } => None,
+            Mutation::SetBit { bit, bloom_size, .. } => {
+                let mut bits = current.map(|c| c.to_vec()).unwrap_or_else(|| vec![0u8; bloom_size.div_ceil(8) as usize]);
+                set_bit(&mut bits, *bit, true);
+                Some(bits)
+            }
+            Mutation::ClearBit { bit, .. } => current.map(|c| {
+                let mut bits = c.to_vec();
+                set_bit(&mut bits, *bit, false);
+                bits
+            }),
+        }
+    }
+}
+
+/// Bits are numbered from the most significant bit of the first byte, like Erlang bitstrings
+fn set_bit(bits: &mut Vec<u8>, bit: u64, on: bool) {
+    let byte = (bit / 8) as usize;
+    if bits.len() <= byte {
+        bits.resize(byte + 1, 0);
+    }
+    let mask = 0x80u8 >> (bit % 8);
+    if on {
+        bits[byte] |= mask;
+    } else {
+        bits[byte] &= !mask;
+    }
+}
+
+pub fn decode_mutations(data: &[u8]) -> Result<Vec<Mutation>> {
+    if let Ok(VTerm::List(items)) = vecpak::decode_term_from_slice(data) {
+        return items.iter().map(decode_vecpak_mutation).collect();
+    }
+    match Term::decode(data)? {
+        Term::List(list) => list.elements.iter().map(decode_etf_mutation).collect(),
+        Term::ByteList(bytes) if bytes.bytes.is_empty() => Ok(Vec::new()),
+        other => Err(anyhow!("mutation list is not a list: {}", other)),
+    }
+}
+
+fn build(op: &str, key: Vec<u8>, value: Option<Vec<u8>>, bit: Option<u64>, bloom_size: Option<u64>) -> Result<Mutation> {
+    match op {
+        "put" => Ok(Mutation::Put { key, value: value.ok_or_else(|| anyhow!("put mutation without value"))? }),
+        "delete" => Ok(Mutation::Delete { key }),
+        "set_bit" => Ok(Mutation::SetBit {
+            key,
+            bit: bit.ok_or_else(|| anyhow!("set_bit mutation without bit index"))?,
+            bloom_size: bloom_size.unwrap_or(0),
+        }),
+        "clear_bit" => Ok(Mutation::ClearBit { key, bit: bit.ok_or_else(|| anyhow!("clear_bit mutation without bit index"))? }),
+        other => Err(anyhow!("unknown mutation op '{}'", other)),
+    }
+}
+
+fn decode_vecpak_mutation(term: &VTerm) -> Result<Mutation> {
+    let op = vecpak_binary(term, b"op").ok_or_else(|| anyhow!("mutation without op"))?;
+    let key = vecpak_binary(term, b"key").ok_or_else(|| anyhow!("mutation without key"))?;
+    let int = |name: &[u8]| match vecpak_get(term, name) {
+        Some(VTerm::VarInt(n)) if *n >= 0 => Some(*n as u64),
+        _ => None,
+    };
+    build(&String::from_utf8_lossy(&op), key, vecpak_binary(term, b"value"), int(b"value"), int(b"bloomsize"))
+}
+
+fn decode_etf_mutation(term: &Term) -> Result<Mutation> {
+    let op = etf_binary(term, "op").ok_or_else(|| anyhow!("mutation without op"))?;
+    let key = etf_binary(term, "key").ok_or_else(|| anyhow!("mutation without key"))?;
+    let value = match etf_get(term, "value") {
+        Some(Term::Binary(binary)) => Some(binary.bytes.clone()),
+        _ => None,
+    };
+    build(
+        &String::from_utf8_lossy(&op),
+        key,
+        value,
+        etf_get(term, "value").and_then(etf_u64),
+        etf_get(term, "bloomsize").and_then(etf_u64),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prop(pairs: Vec<(&[u8], VTerm)>) -> VTerm {
+        VTerm::PropList(pairs.into_iter().map(|(k, v)| (VTerm::Binary(k.to_vec()), v)).collect())
+    }
+
+    #[test]
+    fn test_decode_vecpak_mutations_and_apply_bits() {
+        let list = VTerm::List(vec![
+            prop(vec![(b"op", VTerm::Binary(b"put".to_vec())), (b"key", VTerm::Binary(b"k".to_vec())), (b"value", VTerm::Binary(b"v".to_vec()))]),
+            prop(vec![(b"op", VTerm::Binary(b"delete".to_vec())), (b"key", VTerm::Binary(b"d".to_vec()))]),
+            prop(vec![
+                (b"op", VTerm::Binary(b"set_bit".to_vec())),
+                (b"key", VTerm::Binary(b"bits".to_vec())),
+                (b"value", VTerm::VarInt(9)),
+                (b"bloomsize", VTerm::VarInt(16)),
+            ]),
+        ]);
+        let mut data = Vec::new();
+        vecpak::encode_term(&mut data, list);
+
+        let muts = decode_mutations(&data).unwrap();
+        assert_eq!(muts[0], Mutation::Put { key: b"k".to_vec(), value: b"v".to_vec() });
+        assert_eq!(muts[1].apply(Some(b"x")), None);
+        assert_eq!(muts[2].apply(None), Some(vec![0x00, 0x40]));
+
+        let clear = Mutation::ClearBit { key: b"bits".to_vec(), bit: 9 };
+        assert_eq!(clear.apply(Some(&[0xFF, 0xFF])), Some(vec![0xFF, 0xBF]));
+    }
+}
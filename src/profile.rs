@@ -0,0 +1,264 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+/// Column families that can be migrated selectively (only records tied to migrated entries)
+pub const SELECTIVE_MIGRATIONS: [&str; 6] = ["entry", "muts_rev", "muts", "attestation", "consensus", "tx"];
+
+/// Column families a selective migration writes into
+pub fn selective_cfs(name: &str) -> &'static [&'static str] {
+    match name {
+        // Entries come with their by_height/by_slot keys
+        "entry" => &["entry", "entry_meta"],
+        "muts_rev" | "muts" => &["entry_meta"],
+        "attestation" | "consensus" => &["attestation"],
+        "tx" => &["tx", "tx_account_nonce", "tx_receiver_nonce"],
+        _ => &[],
+    }
+}
+
+/// Phases of the entry migration in `migrate_default_selective`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PhaseSelection {
+    /// Phase 1: entries between rooted height and temporal height
+    pub temporal_to_rooted: bool,
+    /// Phase 2: chain from rooted height down towards genesis
+    pub chain_to_genesis: bool,
+    /// Phase 3: entries above temporal height
+    pub above_temporal: bool,
+}
+
+impl Default for PhaseSelection {
+    fn default() -> Self {
+        PhaseSelection {
+            temporal_to_rooted: true,
+            chain_to_genesis: true,
+            above_temporal: true,
+        }
+    }
+}
+
+/// Resolved settings of a `migrate full` run, loaded from a TOML profile and CLI overrides
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MigrationProfile {
+    /// Maximum number of entries followed below the rooted tip in Phase 2
+    pub chain_depth: u64,
+    /// Phase 3 stops after this many consecutive heights without entries
+    pub max_empty_heights: u64,
+    /// Column families copied in full
+    pub full_cfs: Vec<String>,
+    /// Selective migrations to run (see `SELECTIVE_MIGRATIONS`)
+    pub selective: Vec<String>,
+    pub phases: PhaseSelection,
+    /// Copy full column families through SST file ingestion instead of WriteBatch
+    pub bulk_load: bool,
+    /// Also time a WriteBatch sample of each bulk-loaded CF
+    pub bulk_load_benchmark: bool,
+}
+
+impl Default for MigrationProfile {
+    fn default() -> Self {
+        MigrationProfile {
+            chain_depth: 1000,
+            max_empty_heights: 5,
+            full_cfs: vec!["contractstate".to_string(), "sysconf".to_string()],
+            selective: SELECTIVE_MIGRATIONS.iter().map(|s| s.to_string()).collect(),
+            phases: PhaseSelection::default(),
+            bulk_load: false,
+            bulk_load_benchmark: false,
+        }
+    }
+}
+
+/// Overrides given on the command line, applied on top of the profile file
+#[derive(Debug, Default)]
+pub struct ProfileOverrides {
+    pub chain_depth: Option<u64>,
+    pub max_empty_heights: Option<u64>,
+    pub phases: Option<Vec<String>>,
+    pub full_cfs: Option<Vec<String>>,
+    pub selective: Option<Vec<String>>,
+    /// `--bulk-load` can only switch SST ingestion on
+    pub bulk_load: bool,
+    pub bulk_load_benchmark: bool,
+}
+
+impl MigrationProfile {
+    pub fn from_toml_file(path: &str) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read migration profile {}: {}", path, e))?;
+        toml::from_str(&content).map_err(|e| anyhow!("Invalid migration profile {}: {}", path, e))
+    }
+
+    /// Load the profile file (or the defaults) and apply CLI overrides
+    pub fn resolve(profile_path: Option<&str>, overrides: ProfileOverrides) -> Result<Self> {
+        let mut profile = match profile_path {
+            Some(path) => Self::from_toml_file(path)?,
+            None => Self::default(),
+        };
+
+        if let Some(chain_depth) = overrides.chain_depth {
+            profile.chain_depth = chain_depth;
+        }
+        if let Some(max_empty_heights) = overrides.max_empty_heights {
+            profile.max_empty_heights = max_empty_heights;
+        }
+        if let Some(phases) = overrides.phases {
+            profile.phases = PhaseSelection {
+                temporal_to_rooted: false,
+                chain_to_genesis: false,
+                above_temporal: false,
+            };
+            for phase in &phases {
+                match phase.as_str() {
+                    "temporal_to_rooted" | "1" => profile.phases.temporal_to_rooted = true,
+                    "chain_to_genesis" | "2" => profile.phases.chain_to_genesis = true,
+                    "above_temporal" | "3" => profile.phases.above_temporal = true,
+                    other => return Err(anyhow!(
+                        "Unknown phase '{}', expected temporal_to_rooted, chain_to_genesis or above_temporal",
+                        other
+                    )),
+                }
+            }
+        }
+        if let Some(full_cfs) = overrides.full_cfs {
+            profile.full_cfs = full_cfs;
+        }
+        if let Some(selective) = overrides.selective {
+            profile.selective = selective;
+        }
+        if overrides.bulk_load {
+            profile.bulk_load = true;
+        }
+        if overrides.bulk_load_benchmark {
+            profile.bulk_load_benchmark = true;
+        }
+
+        profile.validate()?;
+        Ok(profile)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        for name in &self.selective {
+            if !SELECTIVE_MIGRATIONS.contains(&name.as_str()) {
+                return Err(anyhow!(
+                    "Unknown selective migration '{}', expected one of {:?}",
+                    name,
+                    SELECTIVE_MIGRATIONS
+                ));
+            }
+        }
+        for name in &self.full_cfs {
+            if !crate::TARGET_COLUMN_FAMILIES.contains(&name.as_str()) {
+                return Err(anyhow!(
+                    "Unknown column family '{}' in full_cfs, expected one of {:?}",
+                    name,
+                    crate::TARGET_COLUMN_FAMILIES
+                ));
+            }
+            if let Some(selective) = self.selective.iter().find(|selective| selective_cfs(selective).contains(&name.as_str())) {
+                return Err(anyhow!(
+                    "Column family '{}' cannot be migrated both fully and selectively (the '{}' migration writes it)",
+                    name,
+                    selective
+                ));
+            }
+        }
+        if self.bulk_load_benchmark && !self.bulk_load {
+            return Err(anyhow!("bulk_load_benchmark requires bulk_load"));
+        }
+        if self.max_empty_heights == 0 {
+            return Err(anyhow!("max_empty_heights must be at least 1"));
+        }
+        Ok(())
+    }
+
+    pub fn runs_selective(&self, name: &str) -> bool {
+        self.selective.iter().any(|s| s == name)
+    }
+
+    pub fn print(&self) {
+        println!("📋 Resolved migration profile:");
+        match toml::to_string_pretty(self) {
+            Ok(rendered) => {
+                for line in rendered.lines() {
+                    println!("   {}", line);
+                }
+            }
+            Err(_) => println!("   {:?}", self),
+        }
+        if !self.runs_selective("entry") {
+            println!("ℹ️  entry migration disabled: selective migrations tied to entries will find nothing to copy");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_file_with_partial_fields() {
+        let profile: MigrationProfile = toml::from_str(
+            "chain_depth = 50000\nselective = [\"entry\", \"muts\"]\n[phases]\nabove_temporal = false\n",
+        )
+        .unwrap();
+        assert_eq!(profile.chain_depth, 50000);
+        assert_eq!(profile.max_empty_heights, 5);
+        assert_eq!(profile.full_cfs, vec!["contractstate", "sysconf"]);
+        assert!(profile.phases.temporal_to_rooted);
+        assert!(!profile.phases.above_temporal);
+        assert!(!profile.runs_selective("consensus"));
+    }
+
+    #[test]
+    fn test_unknown_profile_field_rejected() {
+        assert!(toml::from_str::<MigrationProfile>("chain_dept = 10\n").is_err());
+    }
+
+    #[test]
+    fn test_cli_overrides_replace_phases() {
+        let profile = MigrationProfile::resolve(
+            None,
+            ProfileOverrides {
+                chain_depth: Some(7),
+                phases: Some(vec!["temporal_to_rooted".to_string()]),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(profile.chain_depth, 7);
+        assert!(profile.phases.temporal_to_rooted);
+        assert!(!profile.phases.chain_to_genesis);
+        assert!(!profile.phases.above_temporal);
+
+        let invalid = ProfileOverrides { selective: Some(vec!["bogus".to_string()]), ..Default::default() };
+        assert!(MigrationProfile::resolve(None, invalid).is_err());
+
+        // Typos and legacy column family names are rejected before any migration starts
+        for name in ["contractstat", "my_attestation_for_entry|entryhash"] {
+            let invalid = ProfileOverrides { full_cfs: Some(vec![name.to_string()]), ..Default::default() };
+            assert!(MigrationProfile::resolve(None, invalid).is_err());
+        }
+    }
+
+    #[test]
+    fn test_full_cf_written_by_selective_migration_rejected() {
+        for (full_cf, selective) in [("entry", "entry"), ("entry_meta", "muts"), ("tx_account_nonce", "tx"), ("attestation", "consensus")] {
+            let overrides = ProfileOverrides {
+                full_cfs: Some(vec![full_cf.to_string()]),
+                selective: Some(vec![selective.to_string()]),
+                ..Default::default()
+            };
+            assert!(MigrationProfile::resolve(None, overrides).is_err(), "{} with {}", full_cf, selective);
+        }
+
+        let overrides = ProfileOverrides {
+            full_cfs: Some(vec!["tx".to_string()]),
+            selective: Some(vec!["entry".to_string(), "muts".to_string()]),
+            ..Default::default()
+        };
+        assert!(MigrationProfile::resolve(None, overrides).is_ok());
+    }
+}
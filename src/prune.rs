@@ -0,0 +1,279 @@
+use anyhow::{anyhow, Result};
+use rocksdb::DB;
+use serde_json::json;
+use std::io::Write;
+
+use crate::attestation;
+use crate::entry_index;
+
+/// NDJSON archive of pruned records: one `{"cf", "key_hex", "value_hex"}` object per line
+struct Archive {
+    writer: std::io::BufWriter<std::fs::File>,
+    records: u64,
+}
+
+impl Archive {
+    fn create(path: &str) -> Result<Self> {
+        let file = std::fs::File::create(path)
+            .map_err(|e| anyhow!("Failed to create archive file {}: {}", path, e))?;
+        Ok(Archive { writer: std::io::BufWriter::new(file), records: 0 })
+    }
+
+    fn record(&mut self, cf_name: &str, key: &[u8], value: &[u8]) -> Result<()> {
+        let line = json!({
+            "cf": cf_name,
+            "key_hex": hex::encode(key),
+            "value_hex": hex::encode(value),
+        });
+        writeln!(self.writer, "{}", line)?;
+        self.records += 1;
+        Ok(())
+    }
+}
+
+/// Smallest key that sorts after every key starting with `prefix`, or None when there is none
+/// (an empty or all-0xFF prefix), meaning the range is unbounded above
+pub fn prefix_end(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut end = prefix.to_vec();
+    while let Some(last) = end.pop() {
+        if last < 0xFF {
+            end.push(last + 1);
+            return Some(end);
+        }
+    }
+    None
+}
+
+#[derive(Debug, Default)]
+struct PruneStats {
+    entries: u64,
+    height_index_keys: u64,
+    slot_index_keys: u64,
+    muts: u64,
+    muts_rev: u64,
+    consensus: u64,
+    attestations: u64,
+    my_attestations: u64,
+}
+
+/// Visit every key in `[start, end)` of a column family, up to the last key if `end` is None
+pub fn for_each_in_range(
+    db: &DB,
+    cf: &impl rocksdb::AsColumnFamilyRef,
+    start: &[u8],
+    end: Option<&[u8]>,
+    mut visit: impl FnMut(&[u8], &[u8]) -> Result<()>,
+) -> Result<()> {
+    let iter = db.iterator_cf(cf, rocksdb::IteratorMode::From(start, rocksdb::Direction::Forward));
+    for item in iter {
+        let (key, value) = item?;
+        if end.is_some_and(|end| key.as_ref() >= end) {
+            break;
+        }
+        visit(&key, &value)?;
+    }
+    Ok(())
+}
+
+/// Delete entries and everything hanging off them below `below_height`, in place.
+/// Heights at or above the rooted tip are never pruned.
+pub fn prune_below_height(db_path: &str, below_height: u64, archive_path: Option<&str>, dry_run: bool) -> Result<()> {
+    println!(
+        "✂️  Pruning chain data below height {} in {}{}",
+        below_height,
+        db_path,
+        if dry_run { " (dry run)" } else { "" }
+    );
+
+    let db = if dry_run {
+        crate::open_source_database_readonly(db_path)?
+    } else {
+        crate::open_target_database_readwrite(db_path)?
+    };
+    let (temporal_height, rooted_height) = crate::extract_heights(&db)?;
+    println!("📊 Heights - Temporal: {}, Rooted: {}", temporal_height, rooted_height);
+
+    if below_height > rooted_height {
+        return Err(anyhow!(
+            "Refusing to prune below height {}: it would remove the rooted tip at height {} or entries above it",
+            below_height,
+            rooted_height
+        ));
+    }
+
+    let entry_cf = db
+        .cf_handle("entry")
+        .ok_or_else(|| anyhow!("entry CF not found"))?;
+    let entry_meta_cf = db
+        .cf_handle("entry_meta")
+        .ok_or_else(|| anyhow!("entry_meta CF not found"))?;
+    let attestation_cf = db
+        .cf_handle("attestation")
+        .ok_or_else(|| anyhow!("attestation CF not found"))?;
+    attestation::check_layouts(&db, &attestation_cf)?;
+
+    let mut archive = match archive_path {
+        Some(path) => {
+            println!("🗄️  Archiving pruned records to {}", path);
+            Some(Archive::create(path)?)
+        }
+        None => None,
+    };
+    let mut stats = PruneStats::default();
+
+    // Entries below the height, found through the by_height index
+    let height_start = entry_index::height_index_key(0, b"");
+    let height_end = entry_index::height_index_key(below_height, b"");
+    let mut entry_hashes = Vec::new();
+    for_each_in_range(&db, &entry_meta_cf, &height_start, Some(&height_end), |key, value| {
+        stats.height_index_keys += 1;
+        if let Some(archive) = archive.as_mut() {
+            archive.record("entry_meta", key, value)?;
+        }
+        entry_hashes.push(value.to_vec());
+        Ok(())
+    })?;
+
+    // Point deletes for records keyed by entry hash
+    let mut point_deletes: Vec<(&str, Vec<u8>)> = Vec::new();
+    for entry_hash in &entry_hashes {
+        if let Some(entry_data) = db.get_cf(&entry_cf, entry_hash)? {
+            stats.entries += 1;
+            if let Some(archive) = archive.as_mut() {
+                archive.record("entry", entry_hash, &entry_data)?;
+            }
+            point_deletes.push(("entry", entry_hash.clone()));
+
+            if let Ok((_height, slot, _hash)) = crate::parse_entry_metadata(&entry_data) {
+                let slot_key = entry_index::slot_index_key(slot, entry_hash);
+                if let Some(value) = db.get_cf(&entry_meta_cf, &slot_key)? {
+                    stats.slot_index_keys += 1;
+                    if let Some(archive) = archive.as_mut() {
+                        archive.record("entry_meta", &slot_key, &value)?;
+                    }
+                    point_deletes.push(("entry_meta", slot_key));
+                }
+            }
+        }
+
+        for (suffix, counter) in [(&b":muts"[..], &mut stats.muts), (&b":muts_rev"[..], &mut stats.muts_rev)] {
+            let mut key = b"entry:".to_vec();
+            key.extend_from_slice(entry_hash);
+            key.extend_from_slice(suffix);
+            if let Some(value) = db.get_cf(&entry_meta_cf, &key)? {
+                *counter += 1;
+                if let Some(archive) = archive.as_mut() {
+                    archive.record("entry_meta", &key, &value)?;
+                }
+                point_deletes.push(("entry_meta", key));
+            }
+        }
+
+        let my_key = attestation::my_attestation_key(entry_hash);
+        if let Some(value) = db.get_cf(&attestation_cf, &my_key)? {
+            stats.my_attestations += 1;
+            if let Some(archive) = archive.as_mut() {
+                archive.record("attestation", &my_key, &value)?;
+            }
+            point_deletes.push(("attestation", my_key));
+        }
+    }
+
+    // Range deletes: consensus per entry hash, attestations by height
+    let mut range_deletes: Vec<(Vec<u8>, Vec<u8>)> = Vec::new();
+    for entry_hash in &entry_hashes {
+        let mut prefix = attestation::CONSENSUS_PREFIX.to_vec();
+        prefix.extend_from_slice(entry_hash);
+        prefix.push(b':');
+        let end = prefix_end(&prefix);
+        let mut keys = Vec::new();
+        for_each_in_range(&db, &attestation_cf, &prefix, end.as_deref(), |key, value| {
+            stats.consensus += 1;
+            keys.push(key.to_vec());
+            if let Some(archive) = archive.as_mut() {
+                archive.record("attestation", key, value)?;
+            }
+            Ok(())
+        })?;
+        match end {
+            Some(end) if !keys.is_empty() => range_deletes.push((prefix, end)),
+            // Nothing sorts after an all-0xFF prefix, so its keys are deleted one by one
+            _ => point_deletes.extend(keys.into_iter().map(|key| ("attestation", key))),
+        }
+    }
+
+    let attestation_start = attestation::attestation_height_prefix(0);
+    let attestation_end = attestation::attestation_height_prefix(below_height);
+    for_each_in_range(&db, &attestation_cf, &attestation_start, Some(&attestation_end), |key, value| {
+        stats.attestations += 1;
+        if let Some(archive) = archive.as_mut() {
+            archive.record("attestation", key, value)?;
+        }
+        Ok(())
+    })?;
+
+    if let Some(mut archive) = archive {
+        archive.writer.flush()?;
+        println!("🗄️  Archived {} records", archive.records);
+    }
+
+    println!("📊 Records below height {}:", below_height);
+    println!("   - Entries: {}", stats.entries);
+    println!("   - by_height keys: {}", stats.height_index_keys);
+    println!("   - by_slot keys: {}", stats.slot_index_keys);
+    println!("   - muts: {}", stats.muts);
+    println!("   - muts_rev: {}", stats.muts_rev);
+    println!("   - consensus: {}", stats.consensus);
+    println!("   - attestations: {}", stats.attestations);
+    println!("   - my_attestation: {}", stats.my_attestations);
+
+    if dry_run {
+        println!("ℹ️  Dry run, nothing deleted");
+        return Ok(());
+    }
+
+    let mut write_batch = rocksdb::WriteBatch::default();
+    write_batch.delete_range_cf(&entry_meta_cf, &height_start, &height_end);
+    write_batch.delete_range_cf(&attestation_cf, &attestation_start, &attestation_end);
+    for (start, end) in &range_deletes {
+        write_batch.delete_range_cf(&attestation_cf, start, end);
+        if write_batch.len() >= 1000 {
+            db.write(std::mem::take(&mut write_batch))?;
+        }
+    }
+    for (cf_name, key) in &point_deletes {
+        match *cf_name {
+            "entry" => write_batch.delete_cf(&entry_cf, key),
+            "entry_meta" => write_batch.delete_cf(&entry_meta_cf, key),
+            _ => write_batch.delete_cf(&attestation_cf, key),
+        }
+        if write_batch.len() >= 1000 {
+            db.write(std::mem::take(&mut write_batch))?;
+        }
+    }
+    if !write_batch.is_empty() {
+        db.write(write_batch)?;
+    }
+    println!("🗑️  Deleted {} keys and {} ranges", point_deletes.len(), range_deletes.len() + 2);
+
+    println!("🧹 Compacting entry, entry_meta and attestation...");
+    db.compact_range_cf(&entry_cf, None::<&[u8]>, None::<&[u8]>);
+    db.compact_range_cf(&entry_meta_cf, None::<&[u8]>, None::<&[u8]>);
+    db.compact_range_cf(&attestation_cf, None::<&[u8]>, None::<&[u8]>);
+
+    println!("✅ Pruning below height {} completed successfully!", below_height);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_end() {
+        assert_eq!(prefix_end(b"consensus:"), Some(b"consensus;".to_vec()));
+        assert_eq!(prefix_end(&[1, 0xFF]), Some(vec![2]));
+        assert_eq!(prefix_end(&[0xFF, 0xFF]), None);
+        assert_eq!(prefix_end(b""), None);
+    }
+}
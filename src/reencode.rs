@@ -0,0 +1,507 @@
+//! Conversion of ETF-era entries into the vecpak layout used by current nodes.
+//!
+//! The entry envelope (`header`, `txs`, `hash`, `signature`, ...) always becomes a vecpak
+//! proplist. By default the header is kept byte for byte: the entry hash is blake3 of those bytes
+//! and the signature covers that hash, so both stay valid and nothing keyed by the hash moves.
+//! Transactions are kept as stored for the same reason: a tx hash is blake3 of the encoded tx and
+//! its signature covers that hash, so converting them would change every tx hash, the tx and nonce
+//! index records keyed by it and the header's txs_hash. Entry and tx readers (`parse_entry_metadata`,
+//! `entry_header_binary`, `tx::decode_tx`) accept either encoding inside a vecpak entry.
+//!
+//! With `convert_header` the header becomes vecpak too, the hash is recomputed and every record
+//! keyed by it is moved. Entry signatures, attestations and consensus records were made over the
+//! old hashes and no longer verify, so that is only written when explicitly accepted.
+
+use anyhow::{anyhow, Result};
+use amadeus_fabric_doctor::vecpak::{self, Term as VTerm};
+use eetf::Term;
+use std::collections::HashMap;
+
+use crate::attestation;
+use crate::entry_index;
+use crate::sysconf;
+use crate::tx::{self, etf_get, etf_u64};
+
+/// Convert an ETF term into its vecpak equivalent (atoms and map keys become binaries)
+pub fn etf_to_vecpak(term: &Term) -> Result<VTerm> {
+    match term {
+        Term::Atom(atom) => Ok(match atom.name.as_str() {
+            "true" => VTerm::Bool(true),
+            "false" => VTerm::Bool(false),
+            "nil" => VTerm::Nil(),
+            name => VTerm::Binary(name.as_bytes().to_vec()),
+        }),
+        Term::FixInteger(int) => Ok(VTerm::VarInt(int.value as i128)),
+        Term::BigInteger(big) => i128::try_from(big.value.clone())
+            .map(VTerm::VarInt)
+            .map_err(|_| anyhow!("integer {} does not fit a vecpak varint", big.value)),
+        Term::Binary(binary) => Ok(VTerm::Binary(binary.bytes.clone())),
+        Term::ByteList(byte_list) => Ok(VTerm::List(
+            byte_list.bytes.iter().map(|b| VTerm::VarInt(*b as i128)).collect(),
+        )),
+        Term::List(list) => Ok(VTerm::List(
+            list.elements.iter().map(etf_to_vecpak).collect::<Result<_>>()?,
+        )),
+        Term::Tuple(tuple) => Ok(VTerm::List(
+            tuple.elements.iter().map(etf_to_vecpak).collect::<Result<_>>()?,
+        )),
+        Term::Map(map) => Ok(VTerm::PropList(
+            map.map
+                .iter()
+                .map(|(k, v)| Ok((etf_to_vecpak(k)?, etf_to_vecpak(v)?)))
+                .collect::<Result<_>>()?,
+        )),
+        other => Err(anyhow!("no vecpak equivalent for ETF term {:?}", other)),
+    }
+}
+
+/// Height, slot and prev_hash of an ETF-encoded header
+pub fn etf_header_fields(header_bin: &[u8]) -> Result<(u64, u64, Option<Vec<u8>>)> {
+    let header = Term::decode(header_bin)?;
+    let height = etf_get(&header, "height").and_then(etf_u64).ok_or_else(|| anyhow!("ETF header has no height"))?;
+    let slot = etf_get(&header, "slot").and_then(etf_u64).unwrap_or(0);
+    let prev_hash = match etf_get(&header, "prev_hash") {
+        Some(Term::Binary(binary)) => Some(binary.bytes.clone()),
+        _ => None,
+    };
+    Ok((height, slot, prev_hash))
+}
+
+pub struct ReencodedEntry {
+    pub data: Vec<u8>,
+    /// blake3 of the header bytes in `data`
+    pub hash: Vec<u8>,
+    /// `hash` field of the ETF entry, if any
+    pub stored_hash: Option<Vec<u8>>,
+    /// blake3 of the original ETF header
+    pub original_hash: Vec<u8>,
+    /// Whether the entry carries a signature, which covers `original_hash`
+    pub signed: bool,
+}
+
+/// Convert an ETF entry into a vecpak entry, keeping the header bytes unless `convert_header`.
+/// `rehashed` maps old entry hashes to new ones and is used to rewrite prev_hash when the header
+/// is converted.
+pub fn reencode_entry(entry_data: &[u8], convert_header: bool, rehashed: &HashMap<Vec<u8>, Vec<u8>>) -> Result<ReencodedEntry> {
+    let Term::Map(map) = Term::decode(entry_data)? else {
+        return Err(anyhow!("Entry is not an ETF map"));
+    };
+
+    let mut props = Vec::new();
+    let mut header_bin = None;
+    let mut stored_hash = None;
+    let mut signed = false;
+    for (key, value) in &map.map {
+        let name = match key {
+            Term::Atom(atom) => atom.name.clone(),
+            Term::Binary(binary) => String::from_utf8_lossy(&binary.bytes).to_string(),
+            other => return Err(anyhow!("unexpected entry field key {:?}", other)),
+        };
+        match (name.as_str(), value) {
+            ("header", Term::Binary(binary)) => header_bin = Some(binary.bytes.clone()),
+            ("hash", Term::Binary(binary)) => stored_hash = Some(binary.bytes.clone()),
+            ("signature", Term::Binary(binary)) => {
+                signed = true;
+                props.push((VTerm::Binary(b"signature".to_vec()), VTerm::Binary(binary.bytes.clone())));
+            }
+            ("txs", Term::List(list)) => {
+                let txs = list
+                    .elements
+                    .iter()
+                    .map(|tx| match tx {
+                        Term::Binary(binary) => Ok(VTerm::Binary(binary.bytes.clone())),
+                        other => Err(anyhow!("unexpected tx term in entry: {}", other)),
+                    })
+                    .collect::<Result<_>>()?;
+                props.push((VTerm::Binary(b"txs".to_vec()), VTerm::List(txs)));
+            }
+            _ => props.push((VTerm::Binary(name.into_bytes()), etf_to_vecpak(value)?)),
+        }
+    }
+
+    let header_bin = header_bin.ok_or_else(|| anyhow!("No 'header' binary found in entry map"))?;
+    let original_hash = blake3::hash(&header_bin).as_bytes().to_vec();
+
+    let new_header_bin = if convert_header {
+        let VTerm::PropList(mut header_props) = etf_to_vecpak(&Term::decode(&header_bin[..])?)? else {
+            return Err(anyhow!("ETF header is not a map"));
+        };
+        for (key, value) in header_props.iter_mut() {
+            if let (VTerm::Binary(key), VTerm::Binary(prev_hash)) = (key, value) {
+                if key == b"prev_hash" {
+                    if let Some(new_prev_hash) = rehashed.get(prev_hash) {
+                        *prev_hash = new_prev_hash.clone();
+                    }
+                }
+            }
+        }
+        let mut buf = Vec::new();
+        vecpak::encode_term(&mut buf, VTerm::PropList(header_props));
+        buf
+    } else {
+        header_bin
+    };
+
+    let hash = blake3::hash(&new_header_bin).as_bytes().to_vec();
+    props.push((VTerm::Binary(b"header".to_vec()), VTerm::Binary(new_header_bin)));
+    props.push((VTerm::Binary(b"hash".to_vec()), VTerm::Binary(hash.clone())));
+
+    let mut data = Vec::new();
+    vecpak::encode_term(&mut data, VTerm::PropList(props));
+    Ok(ReencodedEntry { data, hash, stored_hash, original_hash, signed })
+}
+
+#[derive(Debug, Default)]
+struct ReencodeStats {
+    entries_scanned: u64,
+    already_vecpak: u64,
+    undecodable: u64,
+    converted: u64,
+    stored_hash_mismatches: u64,
+    key_mismatches: u64,
+    rehashed: u64,
+    signatures_invalidated: u64,
+    records_moved: u64,
+    attestation_signatures_invalidated: u64,
+    tx_pointers_rewritten: u64,
+}
+
+/// Rewrite every ETF entry of the database as vecpak, in place. With `convert_header` every
+/// converted entry gets a new hash, so nothing is written unless `accept_invalid_signatures` is set.
+/// Each entry's changes (entry, index and attestation records, tx pointers) go into the same batch
+/// and the tips are written last, so an interrupted run never leaves an entry half moved.
+pub fn reencode_entries(db_path: &str, convert_header: bool, accept_invalid_signatures: bool, dry_run: bool) -> Result<()> {
+    println!(
+        "🔄 Re-encoding ETF entries as vecpak ({}){}",
+        if convert_header { "headers converted, hashes recomputed" } else { "headers kept, hashes unchanged" },
+        if dry_run { " (dry run)" } else { "" }
+    );
+
+    let db = if dry_run {
+        crate::open_source_database_readonly(db_path)?
+    } else {
+        crate::open_target_database_readwrite(db_path)?
+    };
+    let entry_cf = db
+        .cf_handle("entry")
+        .ok_or_else(|| anyhow!("entry CF not found"))?;
+    let entry_meta_cf = db
+        .cf_handle("entry_meta")
+        .ok_or_else(|| anyhow!("entry_meta CF not found"))?;
+    let attestation_cf = db.cf_handle("attestation");
+    if let Some(attestation_cf) = &attestation_cf {
+        attestation::check_layouts(&db, attestation_cf)?;
+    }
+    let tx_cf = db.cf_handle("tx");
+
+    let mut stats = ReencodeStats::default();
+
+    // Parents must be converted before their children so prev_hash can be remapped
+    println!("📦 Scanning entries...");
+    let mut etf_entries: Vec<(u64, Vec<u8>)> = Vec::new();
+    for item in db.iterator_cf(&entry_cf, rocksdb::IteratorMode::Start) {
+        let (key, value) = item?;
+        stats.entries_scanned += 1;
+        if vecpak::decode_term_from_slice(&value).is_ok() {
+            stats.already_vecpak += 1;
+            continue;
+        }
+        match crate::parse_entry_metadata(&value) {
+            Ok((height, _slot, _hash)) => etf_entries.push((height, key.to_vec())),
+            Err(e) => {
+                stats.undecodable += 1;
+                println!("⚠️  Could not decode entry {}: {}", hex::encode(&key), e);
+            }
+        }
+    }
+    etf_entries.sort();
+    println!("📊 {} ETF entries to convert, {} already vecpak", etf_entries.len(), stats.already_vecpak);
+    if convert_header && !etf_entries.is_empty() && !dry_run && !accept_invalid_signatures {
+        return Err(anyhow!(
+            "Converting the headers of {} entries changes their hashes, which invalidates their signatures, attestations and consensus records; \
+             run with --dry-run to list them, or pass --accept-invalid-signatures to convert anyway",
+            etf_entries.len()
+        ));
+    }
+
+    let with_slot_index = db
+        .iterator_cf(&entry_meta_cf, rocksdb::IteratorMode::From(entry_index::BY_SLOT_PREFIX, rocksdb::Direction::Forward))
+        .next()
+        .transpose()?
+        .is_some_and(|(key, _)| key.starts_with(entry_index::BY_SLOT_PREFIX));
+
+    let mut rehashed: HashMap<Vec<u8>, Vec<u8>> = HashMap::new();
+    let mut write_batch = rocksdb::WriteBatch::default();
+    let batch_size = 1000;
+
+    for (height, old_key) in &etf_entries {
+        let Some(entry_data) = db.get_cf(&entry_cf, old_key)? else {
+            continue;
+        };
+        let reencoded = match reencode_entry(&entry_data, convert_header, &rehashed) {
+            Ok(reencoded) => reencoded,
+            Err(e) => {
+                stats.undecodable += 1;
+                println!("⚠️  Could not re-encode entry {} at height {}: {}", hex::encode(old_key), height, e);
+                continue;
+            }
+        };
+        stats.converted += 1;
+
+        if let Some(stored_hash) = &reencoded.stored_hash {
+            if *stored_hash != reencoded.original_hash {
+                stats.stored_hash_mismatches += 1;
+                println!(
+                    "⚠️  Height {}: stored hash {} differs from blake3(header) {}",
+                    height,
+                    hex::encode(stored_hash),
+                    hex::encode(&reencoded.original_hash)
+                );
+            }
+        }
+        if reencoded.original_hash != *old_key {
+            stats.key_mismatches += 1;
+            println!(
+                "⚠️  Height {}: entry stored under {} but blake3(header) is {}",
+                height,
+                hex::encode(old_key),
+                hex::encode(&reencoded.original_hash)
+            );
+        }
+
+        if !convert_header || reencoded.hash == *old_key {
+            if !dry_run {
+                write_batch.put_cf(&entry_cf, old_key, &reencoded.data);
+            }
+        } else {
+            stats.rehashed += 1;
+            rehashed.insert(old_key.clone(), reencoded.hash.clone());
+
+            // A dry run collects the moves in a batch that is dropped, so they can still be counted
+            let mut dry_run_batch = rocksdb::WriteBatch::default();
+            let batch = if dry_run { &mut dry_run_batch } else { &mut write_batch };
+            batch.delete_cf(&entry_cf, old_key);
+            batch.put_cf(&entry_cf, &reencoded.hash, &reencoded.data);
+            let (moved, signed_records) = move_entry_records(
+                &db,
+                batch,
+                &entry_meta_cf,
+                attestation_cf.as_ref(),
+                with_slot_index,
+                &entry_data,
+                old_key,
+                &reencoded.hash,
+            )?;
+            if let Some(tx_cf) = tx_cf.as_ref() {
+                stats.tx_pointers_rewritten += move_tx_pointers(&db, batch, tx_cf, &entry_data, old_key, &reencoded.hash)?;
+            }
+            stats.records_moved += moved;
+            stats.attestation_signatures_invalidated += signed_records;
+            if reencoded.signed {
+                stats.signatures_invalidated += 1;
+            }
+            println!(
+                "🔁 Height {}: {} -> {} ({}{} attestation/consensus signatures invalidated)",
+                height,
+                hex::encode(old_key),
+                hex::encode(&reencoded.hash),
+                if reencoded.signed { "entry signature and " } else { "" },
+                signed_records
+            );
+        }
+
+        // Only flushed between entries, so every entry's changes land in one batch
+        if write_batch.len() >= batch_size {
+            db.write(std::mem::take(&mut write_batch))?;
+        }
+    }
+    if !write_batch.is_empty() {
+        db.write(write_batch)?;
+    }
+
+    // Tips last: until here they still name entries that exist under their old hash
+    if !rehashed.is_empty() && !dry_run {
+        let mut tips_batch = rocksdb::WriteBatch::default();
+        for tip_key in [sysconf::TEMPORAL_TIP, sysconf::ROOTED_TIP] {
+            if let Some(tip) = sysconf::read_hash(&db, tip_key)? {
+                if let Some(new_tip) = rehashed.get(&tip) {
+                    println!("🔁 sysconf {} -> {}", tip_key, hex::encode(new_tip));
+                    sysconf::write_in_batch(&db, &mut tips_batch, tip_key, &sysconf::SysconfValue::Hash(new_tip.clone()))?;
+                }
+            }
+        }
+        if !tips_batch.is_empty() {
+            db.write(tips_batch)?;
+        }
+    }
+
+    println!("✅ Re-encoding complete{}:", if dry_run { " (dry run, nothing written)" } else { "" });
+    println!("   - Entries scanned: {}", stats.entries_scanned);
+    println!("   - Already vecpak: {}", stats.already_vecpak);
+    println!("   - Converted: {}", stats.converted);
+    println!("   - Undecodable: {}", stats.undecodable);
+    println!("   - Stored hash != blake3(header): {}", stats.stored_hash_mismatches);
+    println!("   - Key != blake3(header): {}", stats.key_mismatches);
+    println!("   - Entries with a new hash: {}", stats.rehashed);
+    println!("   - Entry signatures invalidated: {}", stats.signatures_invalidated);
+    println!("   - Index/muts/attestation records moved: {}", stats.records_moved);
+    println!("   - Attestation/consensus signatures invalidated: {}", stats.attestation_signatures_invalidated);
+    println!("   - tx pointers rewritten: {}", stats.tx_pointers_rewritten);
+    if stats.signatures_invalidated + stats.attestation_signatures_invalidated > 0 {
+        println!("⚠️  Signatures cover the old hashes and will not verify against the new ones");
+    }
+    Ok(())
+}
+
+/// Point the tx records of the entry's transactions at `new_hash`; tx values start with the hash
+/// of the entry that includes the transaction. Returns the number rewritten.
+fn move_tx_pointers(
+    db: &rocksdb::DB,
+    write_batch: &mut rocksdb::WriteBatch,
+    tx_cf: &impl rocksdb::AsColumnFamilyRef,
+    entry_data: &[u8],
+    old_hash: &[u8],
+    new_hash: &[u8],
+) -> Result<u64> {
+    let mut rewritten = 0;
+    for packed in tx::entry_txs(entry_data)? {
+        let tx_hash = match tx::decode_tx(&packed) {
+            Ok(decoded) => decoded.hash,
+            Err(e) => {
+                println!("⚠️  Could not decode a tx of entry {}, its tx pointer is not moved: {}", hex::encode(old_hash), e);
+                continue;
+            }
+        };
+        let Some(pointer) = db.get_cf(tx_cf, &tx_hash)? else {
+            continue;
+        };
+        if pointer.starts_with(old_hash) {
+            let mut new_pointer = new_hash.to_vec();
+            new_pointer.extend_from_slice(&pointer[old_hash.len()..]);
+            write_batch.put_cf(tx_cf, &tx_hash, new_pointer);
+            rewritten += 1;
+        }
+    }
+    Ok(rewritten)
+}
+
+/// Move entry_meta and attestation records from `old_hash` to `new_hash`. Returns the number
+/// moved and how many of them are signed attestation CF records.
+#[allow(clippy::too_many_arguments)]
+fn move_entry_records(
+    db: &rocksdb::DB,
+    write_batch: &mut rocksdb::WriteBatch,
+    entry_meta_cf: &impl rocksdb::AsColumnFamilyRef,
+    attestation_cf: Option<&impl rocksdb::AsColumnFamilyRef>,
+    with_slot_index: bool,
+    entry_data: &[u8],
+    old_hash: &[u8],
+    new_hash: &[u8],
+) -> Result<(u64, u64)> {
+    let mut moved = 0;
+    let mut signed_records = 0;
+    let (height, slot, _) = crate::parse_entry_metadata(entry_data)?;
+
+    let mut index_keys = vec![(entry_index::height_index_key(height, old_hash), entry_index::height_index_key(height, new_hash))];
+    if with_slot_index {
+        index_keys.push((entry_index::slot_index_key(slot, old_hash), entry_index::slot_index_key(slot, new_hash)));
+    }
+    for (old_key, new_key) in index_keys {
+        if db.get_cf(entry_meta_cf, &old_key)?.is_some() {
+            write_batch.delete_cf(entry_meta_cf, &old_key);
+            write_batch.put_cf(entry_meta_cf, &new_key, new_hash);
+            moved += 1;
+        }
+    }
+
+    for suffix in [&b":muts"[..], &b":muts_rev"[..]] {
+        let meta_key = |hash: &[u8]| {
+            let mut key = b"entry:".to_vec();
+            key.extend_from_slice(hash);
+            key.extend_from_slice(suffix);
+            key
+        };
+        if let Some(value) = db.get_cf(entry_meta_cf, meta_key(old_hash))? {
+            write_batch.delete_cf(entry_meta_cf, meta_key(old_hash));
+            write_batch.put_cf(entry_meta_cf, meta_key(new_hash), value);
+            moved += 1;
+        }
+    }
+
+    let Some(attestation_cf) = attestation_cf else {
+        return Ok((moved, signed_records));
+    };
+    let mut consensus_prefix = attestation::CONSENSUS_PREFIX.to_vec();
+    consensus_prefix.extend_from_slice(old_hash);
+    let prefixes = [
+        attestation::attestation_height_prefix(height),
+        attestation::my_attestation_key(old_hash),
+        consensus_prefix,
+    ];
+    for prefix in prefixes {
+        for item in db.iterator_cf(attestation_cf, rocksdb::IteratorMode::From(&prefix, rocksdb::Direction::Forward)) {
+            let (key, value) = item?;
+            if !key.starts_with(&prefix) {
+                break;
+            }
+            if let Some(decoded) = attestation::decode_attestation_key(&key) {
+                if decoded.entry_hash() == Some(old_hash) {
+                    write_batch.delete_cf(attestation_cf, &key);
+                    write_batch.put_cf(attestation_cf, decoded.with_entry_hash(new_hash).encode(), value);
+                    moved += 1;
+                    signed_records += 1;
+                }
+            }
+        }
+    }
+    Ok((moved, signed_records))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn etf_entry(header: &Term) -> Vec<u8> {
+        let mut header_bin = Vec::new();
+        header.encode(&mut header_bin).unwrap();
+        let hash = blake3::hash(&header_bin).as_bytes().to_vec();
+        let entry = Term::from(eetf::Map::from([
+            (Term::from(eetf::Atom::from("header")), Term::from(eetf::Binary::from(header_bin))),
+            (Term::from(eetf::Atom::from("hash")), Term::from(eetf::Binary::from(hash))),
+            (Term::from(eetf::Atom::from("signature")), Term::from(eetf::Binary::from(vec![2u8; 96]))),
+            (Term::from(eetf::Atom::from("txs")), Term::from(eetf::List::from(vec![Term::from(eetf::Binary::from(vec![1u8, 2, 3]))]))),
+        ]));
+        let mut buf = Vec::new();
+        entry.encode(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn test_reencode_entry_keep_and_convert_header() {
+        let header = Term::from(eetf::Map::from([
+            (Term::from(eetf::Atom::from("height")), Term::from(eetf::FixInteger::from(7))),
+            (Term::from(eetf::Atom::from("slot")), Term::from(eetf::FixInteger::from(9))),
+            (Term::from(eetf::Atom::from("prev_hash")), Term::from(eetf::Binary::from(vec![5u8; 32]))),
+        ]));
+        let entry = etf_entry(&header);
+        let (height, slot, old_hash) = crate::parse_entry_metadata(&entry).unwrap();
+        assert_eq!((height, slot), (7, 9));
+
+        let kept = reencode_entry(&entry, false, &HashMap::new()).unwrap();
+        assert_eq!(kept.hash, old_hash);
+        assert_eq!(kept.stored_hash.as_deref(), Some(&old_hash[..]));
+        assert_eq!(crate::parse_entry_metadata(&kept.data).unwrap(), (7, 9, old_hash.clone()));
+        assert_eq!(crate::entry_header_binary(&kept.data, "prev_hash").unwrap(), vec![5u8; 32]);
+        assert_eq!(crate::tx::entry_txs(&kept.data).unwrap(), vec![vec![1u8, 2, 3]]);
+
+        let rehashed = HashMap::from([(vec![5u8; 32], vec![6u8; 32])]);
+        let converted = reencode_entry(&entry, true, &rehashed).unwrap();
+        assert_ne!(converted.hash, old_hash);
+        assert_eq!(converted.original_hash, old_hash);
+        assert_eq!(converted.stored_hash.as_deref(), Some(&old_hash[..]));
+        assert!(converted.signed);
+        assert_eq!(crate::parse_entry_metadata(&converted.data).unwrap(), (7, 9, converted.hash.clone()));
+        assert_eq!(crate::entry_header_binary(&converted.data, "prev_hash").unwrap(), vec![6u8; 32]);
+        assert_eq!(crate::tx::entry_txs(&converted.data).unwrap(), vec![vec![1u8, 2, 3]]);
+    }
+}
@@ -0,0 +1,231 @@
+use anyhow::{anyhow, Result};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::entry_index;
+use crate::mutation;
+use crate::sysconf::{self, SysconfValue};
+
+fn muts_key(entry_hash: &[u8], suffix: &[u8]) -> Vec<u8> {
+    let mut key = b"entry:".to_vec();
+    key.extend_from_slice(entry_hash);
+    key.extend_from_slice(suffix);
+    key
+}
+
+fn describe_value(value: Option<&[u8]>) -> String {
+    match value {
+        None => "(absent)".to_string(),
+        Some(value) if value.len() > 32 => format!("{}... ({} bytes)", hex::encode(&value[..32]), value.len()),
+        Some(value) => hex::encode(value),
+    }
+}
+
+/// Undo one entry's contractstate changes in `overlay`. muts_rev holds the reverse of each
+/// mutation in the order the mutations were executed, so it is applied from the last one back.
+/// `read` returns the stored value of a key not in the overlay yet.
+fn undo_mutations(
+    muts_rev: &[mutation::Mutation],
+    overlay: &mut HashMap<Vec<u8>, Option<Vec<u8>>>,
+    mut read: impl FnMut(&[u8]) -> Result<Option<Vec<u8>>>,
+) -> Result<()> {
+    for mutation in muts_rev.iter().rev() {
+        let current = match overlay.get(mutation.key()) {
+            Some(value) => value.clone(),
+            None => read(mutation.key())?,
+        };
+        overlay.insert(mutation.key().to_vec(), mutation.apply(current.as_deref()));
+    }
+    Ok(())
+}
+
+/// Undo the entries above `to_height` on the temporal chain: apply their `muts_rev` to
+/// contractstate, drop their indexes and muts, and point the temporal tip at the entry at
+/// `to_height`. Everything is written in a single atomic batch.
+pub fn rewind_to_height(db_path: &str, to_height: u64, dry_run: bool) -> Result<()> {
+    println!("⏪ Rewinding {} to height {}{}", db_path, to_height, if dry_run { " (dry run)" } else { "" });
+
+    let db = if dry_run {
+        crate::open_source_database_readonly(db_path)?
+    } else {
+        crate::open_target_database_readwrite(db_path)?
+    };
+    let (temporal_height, rooted_height) = crate::extract_heights(&db)?;
+    println!("📊 Heights - Temporal: {}, Rooted: {}", temporal_height, rooted_height);
+
+    if to_height >= temporal_height {
+        return Err(anyhow!("Nothing to rewind: temporal tip is at height {}", temporal_height));
+    }
+    if to_height < rooted_height {
+        return Err(anyhow!(
+            "Refusing to rewind to height {}: entries up to the rooted tip at height {} are final",
+            to_height,
+            rooted_height
+        ));
+    }
+
+    let entry_cf = db
+        .cf_handle("entry")
+        .ok_or_else(|| anyhow!("entry CF not found"))?;
+    let entry_meta_cf = db
+        .cf_handle("entry_meta")
+        .ok_or_else(|| anyhow!("entry_meta CF not found"))?;
+    let contractstate_cf = db
+        .cf_handle("contractstate")
+        .ok_or_else(|| anyhow!("contractstate CF not found"))?;
+
+    let mut tip = sysconf::read_hash(&db, sysconf::TEMPORAL_TIP)?
+        .ok_or_else(|| anyhow!("temporal_tip not found in sysconf"))?;
+
+    // Walk back along prev_hash, collecting what every undone entry leaves behind
+    let mut write_batch = rocksdb::WriteBatch::default();
+    let mut overlay: HashMap<Vec<u8>, Option<Vec<u8>>> = HashMap::new();
+    let mut original: BTreeMap<Vec<u8>, Option<Vec<u8>>> = BTreeMap::new();
+    let mut entries_undone = 0u64;
+    let mut mutations_applied = 0u64;
+    let mut index_keys_deleted = 0u64;
+
+    let new_tip = loop {
+        let entry_data = db
+            .get_cf(&entry_cf, &tip)?
+            .ok_or_else(|| anyhow!("Entry {} on the temporal chain is missing", hex::encode(&tip)))?;
+        let (height, slot, _hash) = crate::parse_entry_metadata(&entry_data)?;
+        if height == to_height {
+            break tip;
+        }
+        if height < to_height {
+            return Err(anyhow!("Temporal chain skips height {} (reached height {})", to_height, height));
+        }
+
+        let muts_rev_key = muts_key(&tip, b":muts_rev");
+        let muts_rev = db.get_cf(&entry_meta_cf, &muts_rev_key)?.ok_or_else(|| {
+            anyhow!("Entry {} at height {} has no muts_rev, it cannot be undone", hex::encode(&tip), height)
+        })?;
+        let mutations = mutation::decode_mutations(&muts_rev)
+            .map_err(|e| anyhow!("Could not decode muts_rev of entry at height {}: {}", height, e))?;
+        println!("↩️  Height {} ({}): {} reverse mutations", height, hex::encode(&tip), mutations.len());
+
+        undo_mutations(&mutations, &mut overlay, |key| {
+            let stored = db.get_cf(&contractstate_cf, key)?;
+            original.insert(key.to_vec(), stored.clone());
+            Ok(stored)
+        })?;
+        mutations_applied += mutations.len() as u64;
+
+        for key in [
+            entry_index::height_index_key(height, &tip),
+            entry_index::slot_index_key(slot, &tip),
+            muts_key(&tip, b":muts"),
+            muts_rev_key,
+        ] {
+            if db.get_cf(&entry_meta_cf, &key)?.is_some() {
+                write_batch.delete_cf(&entry_meta_cf, &key);
+                index_keys_deleted += 1;
+            }
+        }
+        entries_undone += 1;
+
+        tip = crate::entry_header_binary(&entry_data, "prev_hash")
+            .ok_or_else(|| anyhow!("Entry at height {} has no prev_hash", height))?;
+    };
+
+    println!("📊 Contractstate changes:");
+    let mut state_changes = 0u64;
+    for (key, before) in &original {
+        let after = &overlay[key];
+        if before == after {
+            continue;
+        }
+        state_changes += 1;
+        match after {
+            Some(value) => write_batch.put_cf(&contractstate_cf, key, value),
+            None => write_batch.delete_cf(&contractstate_cf, key),
+        }
+        if dry_run {
+            println!("   {}", crate::decode_contractstate_key(key));
+            println!("      {} -> {}", describe_value(before.as_deref()), describe_value(after.as_deref()));
+        }
+    }
+
+    sysconf::write_in_batch(&db, &mut write_batch, sysconf::TEMPORAL_TIP, &SysconfValue::Hash(new_tip.clone()))?;
+    if sysconf::read(&db, sysconf::TEMPORAL_HEIGHT)?.is_some() {
+        sysconf::write_in_batch(&db, &mut write_batch, sysconf::TEMPORAL_HEIGHT, &SysconfValue::Height(to_height))?;
+    }
+
+    if !dry_run {
+        db.write(write_batch)?;
+    }
+
+    println!("✅ Rewind {}:", if dry_run { "preview (dry run, nothing written)" } else { "complete" });
+    println!("   - Entries undone: {}", entries_undone);
+    println!("   - Reverse mutations applied: {}", mutations_applied);
+    println!("   - Contractstate keys changed: {}", state_changes);
+    println!("   - Index and muts keys deleted: {}", index_keys_deleted);
+    println!("   - New temporal tip: {} (height {})", hex::encode(&new_tip), to_height);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amadeus_fabric_doctor::vecpak::{self, Term as VTerm};
+    use mutation::Mutation;
+
+    fn encode(mutations: &[Mutation]) -> Vec<u8> {
+        let prop = |op: &[u8], key: &[u8], value: Option<VTerm>| {
+            let mut pairs = vec![
+                (VTerm::Binary(b"op".to_vec()), VTerm::Binary(op.to_vec())),
+                (VTerm::Binary(b"key".to_vec()), VTerm::Binary(key.to_vec())),
+            ];
+            pairs.extend(value.map(|value| (VTerm::Binary(b"value".to_vec()), value)));
+            VTerm::PropList(pairs)
+        };
+        let list = mutations
+            .iter()
+            .map(|mutation| match mutation {
+                Mutation::Put { key, value } => prop(b"put", key, Some(VTerm::Binary(value.clone()))),
+                Mutation::Delete { key } => prop(b"delete", key, None),
+                other => panic!("unused in this test: {:?}", other),
+            })
+            .collect();
+        let mut data = Vec::new();
+        vecpak::encode_term(&mut data, VTerm::List(list));
+        data
+    }
+
+    #[test]
+    fn test_muts_rev_restores_state_before_muts() {
+        let put = |key: &[u8], value: &[u8]| Mutation::Put { key: key.to_vec(), value: value.to_vec() };
+        let delete = |key: &[u8]| Mutation::Delete { key: key.to_vec() };
+        let original: BTreeMap<Vec<u8>, Vec<u8>> =
+            BTreeMap::from([(b"balance".to_vec(), b"10".to_vec()), (b"nonce".to_vec(), b"1".to_vec())]);
+
+        // "balance" is written twice, so undoing in the wrong order leaves the intermediate value
+        let muts = vec![put(b"balance", b"7"), put(b"created", b"x"), put(b"balance", b"4"), delete(b"nonce")];
+
+        // The node records, for every mutation as it executes, the one restoring the previous value
+        let mut state = original.clone();
+        let mut muts_rev = Vec::new();
+        for mutation in &muts {
+            muts_rev.push(match state.get(mutation.key()) {
+                Some(previous) => put(mutation.key(), previous),
+                None => delete(mutation.key()),
+            });
+            match mutation.apply(state.get(mutation.key()).map(Vec::as_slice)) {
+                Some(value) => state.insert(mutation.key().to_vec(), value),
+                None => state.remove(mutation.key()),
+            };
+        }
+        assert_ne!(state, original);
+
+        let muts_rev = mutation::decode_mutations(&encode(&muts_rev)).unwrap();
+        let mut overlay = HashMap::new();
+        undo_mutations(&muts_rev, &mut overlay, |key| Ok(state.get(key).cloned())).unwrap();
+        for (key, value) in overlay {
+            match value {
+                Some(value) => state.insert(key, value),
+                None => state.remove(&key),
+            };
+        }
+        assert_eq!(state, original);
+    }
+}
@@ -0,0 +1,157 @@
+use anyhow::{anyhow, Result};
+use rocksdb::DB;
+
+use crate::prune::prefix_end;
+
+/// Bounds and paging of an `inspect scan`, with every key already converted to raw bytes
+#[derive(Debug, Default)]
+pub struct ScanOptions {
+    pub prefix: Option<Vec<u8>>,
+    /// Inclusive lower bound
+    pub start: Option<Vec<u8>>,
+    /// Exclusive upper bound
+    pub end: Option<Vec<u8>>,
+    pub reverse: bool,
+    pub limit: usize,
+    /// Last key of the previous page; the scan continues right after it
+    pub cursor: Option<Vec<u8>>,
+    pub with_values: bool,
+}
+
+impl ScanOptions {
+    /// `[lower, upper)` from the prefix and the bounds, narrowed to the tightest of both
+    fn range(&self) -> (Option<Vec<u8>>, Option<Vec<u8>>) {
+        let mut lower = self.start.clone();
+        let mut upper = self.end.clone();
+        if let Some(prefix) = &self.prefix {
+            if lower.as_ref().is_none_or(|lower| lower < prefix) {
+                lower = Some(prefix.clone());
+            }
+            // An empty or all-0xFF prefix leaves the upper bound as it is
+            if let Some(prefix_upper) = prefix_end(prefix) {
+                if upper.as_ref().is_none_or(|upper| *upper > prefix_upper) {
+                    upper = Some(prefix_upper);
+                }
+            }
+        }
+        (lower, upper)
+    }
+}
+
+/// Raw bytes of a key given on the command line: `hex:`/`0x` followed by hex, or text. For
+/// contractstate, text is the decoded form printed by the key decoder and is encoded back.
+pub fn parse_key_arg(cf_name: &str, text: &str) -> Result<Vec<u8>> {
+    if let Some(hex_text) = text.strip_prefix("hex:").or_else(|| text.strip_prefix("0x")) {
+        return hex::decode(hex_text).map_err(|e| anyhow!("Invalid hex key '{}': {}", text, e));
+    }
+    if cf_name == "contractstate" {
+        return Ok(crate::encode_contractstate_key(text));
+    }
+    Ok(text.as_bytes().to_vec())
+}
+
+/// Print one page of keys (and optionally values) of a column family, untruncated
+pub fn scan(db: &DB, cf_name: &str, cf: &impl rocksdb::AsColumnFamilyRef, options: &ScanOptions) -> Result<()> {
+    let (mut lower, mut upper) = options.range();
+    let mut skip_cursor = None;
+    if let Some(cursor) = &options.cursor {
+        if options.reverse {
+            if upper.as_ref().is_none_or(|upper| cursor < upper) {
+                upper = Some(cursor.clone());
+            }
+        } else if lower.as_ref().is_none_or(|lower| cursor >= lower) {
+            lower = Some(cursor.clone());
+            skip_cursor = Some(cursor.clone());
+        }
+    }
+
+    let mode = match (options.reverse, &lower, &upper) {
+        (false, Some(lower), _) => rocksdb::IteratorMode::From(lower, rocksdb::Direction::Forward),
+        (false, None, _) => rocksdb::IteratorMode::Start,
+        (true, _, Some(upper)) => rocksdb::IteratorMode::From(upper, rocksdb::Direction::Reverse),
+        (true, _, None) => rocksdb::IteratorMode::End,
+    };
+
+    println!(
+        "Keys in {} column family{}{}:",
+        cf_name,
+        match (&lower, &upper) {
+            (None, None) => String::new(),
+            (lower, upper) => format!(
+                " from {} to {}",
+                lower.as_deref().map(|key| crate::describe_key(cf_name, key)).unwrap_or_else(|| "start".to_string()),
+                upper.as_deref().map(|key| crate::describe_key(cf_name, key)).unwrap_or_else(|| "end".to_string())
+            ),
+        },
+        if options.reverse { " (reverse)" } else { "" }
+    );
+
+    let mut shown = 0usize;
+    let mut last_key: Option<Vec<u8>> = None;
+    let mut more = false;
+    for item in db.iterator_cf(cf, mode) {
+        let (key, value) = item?;
+        if lower.as_deref().is_some_and(|lower| key.as_ref() < lower) {
+            if options.reverse {
+                break;
+            }
+            continue;
+        }
+        if upper.as_deref().is_some_and(|upper| key.as_ref() >= upper) {
+            if options.reverse {
+                continue;
+            }
+            break;
+        }
+        if skip_cursor.as_deref() == Some(key.as_ref()) {
+            continue;
+        }
+        if shown >= options.limit {
+            more = true;
+            break;
+        }
+
+        println!("{}", crate::describe_key(cf_name, &key));
+        println!("   hex: {}", hex::encode(&key));
+        if options.with_values {
+            match crate::parse_etf_to_json(&value) {
+                Ok(json) => println!("   value: {}", json),
+                Err(_) => println!("   value (hex): {}", hex::encode(&value)),
+            }
+        }
+        shown += 1;
+        last_key = Some(key.to_vec());
+    }
+
+    println!("\nKeys shown: {}", shown);
+    if let (true, Some(last_key)) = (more, last_key) {
+        println!("➡️  More keys follow, resume with --cursor {}", hex::encode(&last_key));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_key_arg_and_range() {
+        let public_key: Vec<u8> = (0..48).map(|i| 0x80 + i as u8).collect();
+        let base58 = bs58::encode(&public_key).into_string();
+        let mut balance = b"bic:coin:balance:".to_vec();
+        balance.extend_from_slice(&public_key);
+        balance.extend_from_slice(b":AMA");
+        assert_eq!(parse_key_arg("contractstate", &format!("bic:coin:balance:{}:AMA", base58)).unwrap(), balance);
+        assert_eq!(parse_key_arg("entry_meta", "by_height:").unwrap(), b"by_height:".to_vec());
+        assert_eq!(parse_key_arg("entry", "hex:00ff").unwrap(), vec![0x00, 0xFF]);
+        assert_eq!(parse_key_arg("entry", "0x0a").unwrap(), vec![0x0A]);
+        assert!(parse_key_arg("entry", "hex:zz").is_err());
+
+        let options = ScanOptions { prefix: Some(b"bic:coin:".to_vec()), end: Some(b"bic:coin:c".to_vec()), ..Default::default() };
+        assert_eq!(options.range(), (Some(b"bic:coin:".to_vec()), Some(b"bic:coin:c".to_vec())));
+        let options = ScanOptions { prefix: Some(b"bic:".to_vec()), start: Some(b"a".to_vec()), ..Default::default() };
+        assert_eq!(options.range(), (Some(b"bic:".to_vec()), Some(b"bic;".to_vec())));
+        let options = ScanOptions { prefix: Some(vec![0xFF, 0xFF]), ..Default::default() };
+        assert_eq!(options.range(), (Some(vec![0xFF, 0xFF]), None));
+    }
+}
@@ -0,0 +1,366 @@
+use anyhow::{anyhow, Result};
+use rocksdb::DB;
+use rusqlite::{params, Connection};
+use serde_json::json;
+
+use crate::mutation::{self, Mutation};
+use crate::sysconf;
+use crate::tx::{self, TxArg};
+
+const SCHEMA: &str = "
+CREATE TABLE contractstate (
+    key BLOB PRIMARY KEY,
+    key_decoded TEXT NOT NULL,
+    module TEXT,
+    contract TEXT,
+    field TEXT,
+    rest TEXT,
+    account TEXT,
+    value BLOB NOT NULL,
+    value_json TEXT
+);
+CREATE TABLE entries (
+    hash BLOB PRIMARY KEY,
+    height INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    prev_hash BLOB,
+    signer TEXT,
+    tx_count INTEGER,
+    size INTEGER NOT NULL
+);
+CREATE TABLE transactions (
+    hash BLOB NOT NULL,
+    entry_hash BLOB NOT NULL,
+    height INTEGER NOT NULL,
+    position INTEGER NOT NULL,
+    signer TEXT NOT NULL,
+    nonce INTEGER,
+    contract TEXT,
+    function TEXT,
+    args_json TEXT,
+    hash_valid INTEGER NOT NULL,
+    PRIMARY KEY (hash, entry_hash)
+);
+CREATE TABLE muts (
+    entry_hash BLOB NOT NULL,
+    kind TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    op TEXT NOT NULL,
+    key BLOB NOT NULL,
+    key_decoded TEXT NOT NULL,
+    value BLOB,
+    bit INTEGER,
+    PRIMARY KEY (entry_hash, kind, position)
+);
+CREATE TABLE sysconf (
+    key BLOB PRIMARY KEY,
+    name TEXT,
+    value BLOB NOT NULL,
+    decoded TEXT
+);
+";
+
+/// Created once the tables are filled, which is faster than maintaining them during inserts
+const INDEXES: &str = "
+CREATE INDEX contractstate_field ON contractstate (module, contract, field);
+CREATE INDEX contractstate_account ON contractstate (account);
+CREATE INDEX contractstate_key_decoded ON contractstate (key_decoded);
+CREATE INDEX entries_height ON entries (height);
+CREATE INDEX entries_slot ON entries (slot);
+CREATE INDEX entries_prev_hash ON entries (prev_hash);
+CREATE INDEX entries_signer ON entries (signer);
+CREATE INDEX transactions_signer_nonce ON transactions (signer, nonce);
+CREATE INDEX transactions_entry_hash ON transactions (entry_hash);
+CREATE INDEX transactions_height ON transactions (height);
+CREATE INDEX transactions_call ON transactions (contract, function);
+CREATE INDEX muts_key ON muts (key);
+CREATE INDEX muts_key_decoded ON muts (key_decoded);
+";
+
+/// Columns derived from a decoded contractstate key: the first three `:` segments, the rest,
+/// and the Base58 public key the key contains, if any
+struct KeyParts {
+    module: Option<String>,
+    contract: Option<String>,
+    field: Option<String>,
+    rest: Option<String>,
+    account: Option<String>,
+}
+
+fn key_parts(decoded: &str) -> KeyParts {
+    let mut segments = decoded.splitn(4, ':').map(str::to_string);
+    let account = decoded
+        .split(':')
+        .find(|segment| bs58::decode(segment).into_vec().is_ok_and(|bytes| bytes.len() == 48))
+        .map(str::to_string);
+    KeyParts {
+        module: segments.next(),
+        contract: segments.next(),
+        field: segments.next(),
+        rest: segments.next(),
+        account,
+    }
+}
+
+fn mutation_columns(mutation: &Mutation) -> (&'static str, Option<&[u8]>, Option<i64>) {
+    match mutation {
+        Mutation::Put { value, .. } => ("put", Some(value), None),
+        Mutation::Delete { .. } => ("delete", None, None),
+        Mutation::SetBit { bit, .. } => ("set_bit", None, i64::try_from(*bit).ok()),
+        Mutation::ClearBit { bit, .. } => ("clear_bit", None, i64::try_from(*bit).ok()),
+    }
+}
+
+#[derive(Debug, Default)]
+struct SqliteStats {
+    contractstate: u64,
+    entries: u64,
+    undecodable_entries: u64,
+    transactions: u64,
+    undecodable_txs: u64,
+    muts: u64,
+    undecodable_muts: u64,
+    sysconf: u64,
+}
+
+/// Write contractstate, entries, transactions, muts and sysconf of `db_path` into a new SQLite file
+pub fn export_sqlite(db_path: &str, output_file: &str) -> Result<()> {
+    if std::path::Path::new(output_file).exists() {
+        return Err(anyhow!("SQLite file already exists: {}", output_file));
+    }
+    println!("🗃️  Exporting {} to SQLite {}", db_path, output_file);
+
+    let db = crate::open_source_database_readonly(db_path)?;
+    let mut conn = Connection::open(output_file)?;
+    conn.execute_batch("PRAGMA journal_mode = OFF; PRAGMA synchronous = OFF;")?;
+    conn.execute_batch(SCHEMA)?;
+
+    let mut stats = SqliteStats::default();
+    export_contractstate(&db, &mut conn, &mut stats)?;
+    export_entries(&db, &mut conn, &mut stats)?;
+    export_muts(&db, &mut conn, &mut stats)?;
+    export_sysconf(&db, &mut conn, &mut stats)?;
+
+    println!("📇 Creating indexes...");
+    conn.execute_batch(INDEXES)?;
+
+    println!("✅ SQLite export complete:");
+    println!("   - contractstate: {}", stats.contractstate);
+    println!("   - entries: {} ({} undecodable)", stats.entries, stats.undecodable_entries);
+    println!("   - transactions: {} ({} undecodable)", stats.transactions, stats.undecodable_txs);
+    println!("   - muts: {} ({} undecodable lists)", stats.muts, stats.undecodable_muts);
+    println!("   - sysconf: {}", stats.sysconf);
+    Ok(())
+}
+
+fn export_contractstate(db: &DB, conn: &mut Connection, stats: &mut SqliteStats) -> Result<()> {
+    println!("🔄 Exporting contractstate...");
+    let cf = db
+        .cf_handle("contractstate")
+        .ok_or_else(|| anyhow!("contractstate CF not found"))?;
+
+    let transaction = conn.transaction()?;
+    {
+        let mut insert = transaction.prepare(
+            "INSERT INTO contractstate (key, key_decoded, module, contract, field, rest, account, value, value_json)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        )?;
+        for item in db.iterator_cf(cf, rocksdb::IteratorMode::Start) {
+            let (key, value) = item?;
+            let decoded = crate::decode_contractstate_key(&key);
+            let parts = key_parts(&decoded);
+            let value_json = crate::parse_etf_to_json(&value).ok().map(|json| json.to_string());
+            insert.execute(params![
+                key.as_ref(),
+                decoded,
+                parts.module,
+                parts.contract,
+                parts.field,
+                parts.rest,
+                parts.account,
+                value.as_ref(),
+                value_json
+            ])?;
+            stats.contractstate += 1;
+            if stats.contractstate.is_multiple_of(100_000) {
+                println!("📦 Exported {} contractstate keys so far...", stats.contractstate);
+            }
+        }
+    }
+    transaction.commit()?;
+    Ok(())
+}
+
+fn export_entries(db: &DB, conn: &mut Connection, stats: &mut SqliteStats) -> Result<()> {
+    println!("🔄 Exporting entries and transactions...");
+    let cf = db
+        .cf_handle("entry")
+        .ok_or_else(|| anyhow!("entry CF not found"))?;
+
+    let transaction = conn.transaction()?;
+    {
+        let mut insert_entry = transaction.prepare(
+            "INSERT INTO entries (hash, height, slot, prev_hash, signer, tx_count, size) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        )?;
+        let mut insert_tx = transaction.prepare(
+            "INSERT OR IGNORE INTO transactions
+             (hash, entry_hash, height, position, signer, nonce, contract, function, args_json, hash_valid)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        )?;
+
+        for item in db.iterator_cf(cf, rocksdb::IteratorMode::Start) {
+            let (entry_hash, entry_data) = item?;
+            let (height, slot, _hash) = match crate::parse_entry_metadata(&entry_data) {
+                Ok(metadata) => metadata,
+                Err(e) => {
+                    stats.undecodable_entries += 1;
+                    println!("⚠️  Could not decode entry {}: {}", hex::encode(&entry_hash), e);
+                    continue;
+                }
+            };
+            let signer = crate::entry_header_binary(&entry_data, "signer").map(|signer| bs58::encode(signer).into_string());
+            let txs = tx::entry_txs(&entry_data).ok();
+
+            insert_entry.execute(params![
+                entry_hash.as_ref(),
+                height as i64,
+                slot as i64,
+                crate::entry_header_binary(&entry_data, "prev_hash"),
+                signer,
+                txs.as_ref().map(|txs| txs.len() as i64),
+                entry_data.len() as i64
+            ])?;
+            stats.entries += 1;
+
+            for (position, packed) in txs.iter().flatten().enumerate() {
+                let decoded = match tx::decode_tx(packed) {
+                    Ok(decoded) => decoded,
+                    Err(_) => {
+                        stats.undecodable_txs += 1;
+                        continue;
+                    }
+                };
+                let action = decoded.actions.first();
+                let args = json!(decoded
+                    .actions
+                    .iter()
+                    .map(|action| action.args.iter().map(TxArg::to_json).collect::<Vec<_>>())
+                    .collect::<Vec<_>>());
+                // A transaction repeated within the entry is inserted once
+                stats.transactions += insert_tx.execute(params![
+                    decoded.hash,
+                    entry_hash.as_ref(),
+                    height as i64,
+                    position as i64,
+                    bs58::encode(&decoded.signer).into_string(),
+                    i64::try_from(decoded.nonce).ok(),
+                    action.map(|action| action.contract.clone()),
+                    action.map(|action| action.function.clone()),
+                    args.to_string(),
+                    decoded.hash_matches()
+                ])? as u64;
+            }
+
+            if stats.entries.is_multiple_of(10_000) {
+                println!("📦 Exported {} entries so far...", stats.entries);
+            }
+        }
+    }
+    transaction.commit()?;
+    Ok(())
+}
+
+fn export_muts(db: &DB, conn: &mut Connection, stats: &mut SqliteStats) -> Result<()> {
+    println!("🔄 Exporting muts and muts_rev...");
+    let cf = db
+        .cf_handle("entry_meta")
+        .ok_or_else(|| anyhow!("entry_meta CF not found"))?;
+
+    let transaction = conn.transaction()?;
+    {
+        let mut insert = transaction.prepare(
+            "INSERT INTO muts (entry_hash, kind, position, op, key, key_decoded, value, bit) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )?;
+        let prefix = b"entry:";
+        for item in db.iterator_cf(cf, rocksdb::IteratorMode::From(prefix, rocksdb::Direction::Forward)) {
+            let (key, value) = item?;
+            if !key.starts_with(prefix) {
+                break;
+            }
+            // entry:<hash 32>:muts or entry:<hash 32>:muts_rev
+            let Some(entry_hash) = key.get(prefix.len()..prefix.len() + 32) else {
+                continue;
+            };
+            let kind = match &key[prefix.len() + 32..] {
+                b":muts" => "muts",
+                b":muts_rev" => "muts_rev",
+                _ => continue,
+            };
+            let Ok(mutations) = mutation::decode_mutations(&value) else {
+                stats.undecodable_muts += 1;
+                continue;
+            };
+            for (position, mutation) in mutations.iter().enumerate() {
+                let (op, value, bit) = mutation_columns(mutation);
+                insert.execute(params![
+                    entry_hash,
+                    kind,
+                    position as i64,
+                    op,
+                    mutation.key(),
+                    crate::decode_contractstate_key(mutation.key()),
+                    value,
+                    bit
+                ])?;
+                stats.muts += 1;
+            }
+        }
+    }
+    transaction.commit()?;
+    Ok(())
+}
+
+fn export_sysconf(db: &DB, conn: &mut Connection, stats: &mut SqliteStats) -> Result<()> {
+    println!("🔄 Exporting sysconf...");
+    let cf = db
+        .cf_handle("sysconf")
+        .ok_or_else(|| anyhow!("sysconf CF not found"))?;
+
+    let transaction = conn.transaction()?;
+    {
+        let mut insert = transaction.prepare("INSERT INTO sysconf (key, name, value, decoded) VALUES (?1, ?2, ?3, ?4)")?;
+        for item in db.iterator_cf(cf, rocksdb::IteratorMode::Start) {
+            let (key, value) = item?;
+            // Keyed on the raw bytes, `name` is only set for UTF-8 keys
+            let name = std::str::from_utf8(&key).ok();
+            let decoded = match name.map(sysconf::declaration) {
+                Some(Ok(declaration)) => sysconf::decode(declaration, &value).ok().map(|decoded| decoded.value.to_string()),
+                _ => std::str::from_utf8(&value).ok().map(str::to_string),
+            };
+            insert.execute(params![key.as_ref(), name, value.as_ref(), decoded])?;
+            stats.sysconf += 1;
+        }
+    }
+    transaction.commit()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_parts() {
+        let account = bs58::encode([0x81u8; 48]).into_string();
+        let parts = key_parts(&format!("bic:coin:balance:{}:AMA", account));
+        assert_eq!(parts.module.as_deref(), Some("bic"));
+        assert_eq!(parts.contract.as_deref(), Some("coin"));
+        assert_eq!(parts.field.as_deref(), Some("balance"));
+        assert_eq!(parts.rest, Some(format!("{}:AMA", account)));
+        assert_eq!(parts.account, Some(account));
+
+        let parts = key_parts("bic:epoch:segment_vr_hash");
+        assert_eq!(parts.rest, None);
+        assert_eq!(parts.account, None);
+    }
+}
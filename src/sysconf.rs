@@ -0,0 +1,256 @@
+//! Typed access to the `sysconf` column family.
+//!
+//! Every key the tool reads or writes is declared once with the encoding the node uses, and all
+//! reads and writes go through that declaration. Values stored in another (legacy) encoding are
+//! still decoded where possible, but reported so they can be rewritten.
+
+use anyhow::{anyhow, Result};
+use eetf::Term;
+use rocksdb::DB;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SysconfEncoding {
+    /// Raw 32-byte entry hash
+    Hash32,
+    /// Non-negative ETF integer (`term_to_binary` of the height)
+    EtfInteger,
+    /// UTF-8 JSON document
+    Json,
+}
+
+impl SysconfEncoding {
+    fn describe(self) -> &'static str {
+        match self {
+            SysconfEncoding::Hash32 => "raw 32-byte hash",
+            SysconfEncoding::EtfInteger => "ETF integer",
+            SysconfEncoding::Json => "JSON",
+        }
+    }
+}
+
+pub struct SysconfKey {
+    pub name: &'static str,
+    pub encoding: SysconfEncoding,
+    pub description: &'static str,
+}
+
+pub const TEMPORAL_TIP: &str = "temporal_tip";
+pub const ROOTED_TIP: &str = "rooted_tip";
+pub const TEMPORAL_HEIGHT: &str = "temporal_height";
+pub const ROOTED_HEIGHT: &str = "rooted_height";
+pub const MIGRATION_MANIFEST: &str = "fabric_doctor:migration_manifest";
+
+pub const KNOWN_KEYS: &[SysconfKey] = &[
+    SysconfKey { name: TEMPORAL_TIP, encoding: SysconfEncoding::Hash32, description: "hash of the temporal tip entry" },
+    SysconfKey { name: ROOTED_TIP, encoding: SysconfEncoding::Hash32, description: "hash of the rooted tip entry" },
+    SysconfKey { name: TEMPORAL_HEIGHT, encoding: SysconfEncoding::EtfInteger, description: "height of the temporal tip" },
+    SysconfKey { name: ROOTED_HEIGHT, encoding: SysconfEncoding::EtfInteger, description: "height of the rooted tip" },
+    SysconfKey {
+        name: MIGRATION_MANIFEST,
+        encoding: SysconfEncoding::Json,
+        description: "manifest of the migration that produced this database",
+    },
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SysconfValue {
+    Hash(Vec<u8>),
+    Height(u64),
+    Json(serde_json::Value),
+}
+
+impl std::fmt::Display for SysconfValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SysconfValue::Hash(hash) => write!(f, "{}", hex::encode(hash)),
+            SysconfValue::Height(height) => write!(f, "{}", height),
+            SysconfValue::Json(json) => write!(f, "{}", json),
+        }
+    }
+}
+
+/// A stored value decoded through its declaration
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decoded {
+    pub value: SysconfValue,
+    /// Set when the value was stored in an encoding other than the declared one
+    pub legacy_encoding: Option<&'static str>,
+}
+
+pub fn declaration(name: &str) -> Result<&'static SysconfKey> {
+    KNOWN_KEYS
+        .iter()
+        .find(|key| key.name == name)
+        .ok_or_else(|| anyhow!("sysconf key '{}' is not declared", name))
+}
+
+pub fn encode(key: &SysconfKey, value: &SysconfValue) -> Result<Vec<u8>> {
+    match (key.encoding, value) {
+        (SysconfEncoding::Hash32, SysconfValue::Hash(hash)) if hash.len() == 32 => Ok(hash.clone()),
+        (SysconfEncoding::EtfInteger, SysconfValue::Height(height)) => {
+            let term = match i32::try_from(*height) {
+                Ok(small) => Term::from(eetf::FixInteger::from(small)),
+                Err(_) => Term::from(eetf::BigInteger::from(*height)),
+            };
+            let mut encoded = Vec::new();
+            term.encode(&mut encoded)?;
+            Ok(encoded)
+        }
+        (SysconfEncoding::Json, SysconfValue::Json(json)) => Ok(serde_json::to_vec_pretty(json)?),
+        _ => Err(anyhow!("{} cannot be stored as {} ({})", value, key.name, key.encoding.describe())),
+    }
+}
+
+pub fn decode(key: &SysconfKey, raw: &[u8]) -> Result<Decoded> {
+    let strict = match key.encoding {
+        SysconfEncoding::Hash32 => (raw.len() == 32).then(|| SysconfValue::Hash(raw.to_vec())),
+        SysconfEncoding::EtfInteger => match Term::decode(raw) {
+            Ok(Term::FixInteger(int)) if int.value >= 0 => Some(SysconfValue::Height(int.value as u64)),
+            Ok(Term::BigInteger(big)) => big.value.try_into().ok().map(SysconfValue::Height),
+            _ => None,
+        },
+        SysconfEncoding::Json => serde_json::from_slice(raw).ok().map(SysconfValue::Json),
+    };
+    if let Some(value) = strict {
+        return Ok(Decoded { value, legacy_encoding: None });
+    }
+
+    let legacy = match key.encoding {
+        SysconfEncoding::Hash32 => std::str::from_utf8(raw)
+            .ok()
+            .and_then(|text| hex::decode(text).ok())
+            .filter(|hash| hash.len() == 32)
+            .map(|hash| (SysconfValue::Hash(hash), "hex string")),
+        // Written by earlier versions of this tool
+        SysconfEncoding::EtfInteger => std::str::from_utf8(raw)
+            .ok()
+            .filter(|text| !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|text| text.parse().ok())
+            .map(|height| (SysconfValue::Height(height), "decimal string")),
+        SysconfEncoding::Json => None,
+    };
+    match legacy {
+        Some((value, encoding)) => Ok(Decoded { value, legacy_encoding: Some(encoding) }),
+        None => Err(anyhow!(
+            "sysconf {} is not a {} ({} bytes: {})",
+            key.name,
+            key.encoding.describe(),
+            raw.len(),
+            hex::encode(&raw[..raw.len().min(64)])
+        )),
+    }
+}
+
+pub fn read(db: &DB, name: &str) -> Result<Option<Decoded>> {
+    let key = declaration(name)?;
+    let sysconf_cf = db
+        .cf_handle("sysconf")
+        .ok_or_else(|| anyhow!("sysconf column family not found"))?;
+    match db.get_cf(&sysconf_cf, key.name)? {
+        Some(raw) => Ok(Some(decode(key, &raw)?)),
+        None => Ok(None),
+    }
+}
+
+pub fn read_hash(db: &DB, name: &str) -> Result<Option<Vec<u8>>> {
+    match read(db, name)? {
+        Some(Decoded { value: SysconfValue::Hash(hash), .. }) => Ok(Some(hash)),
+        Some(other) => Err(anyhow!("sysconf {} holds {} instead of a hash", name, other.value)),
+        None => Ok(None),
+    }
+}
+
+pub fn write(db: &DB, name: &str, value: &SysconfValue) -> Result<()> {
+    let key = declaration(name)?;
+    let sysconf_cf = db
+        .cf_handle("sysconf")
+        .ok_or_else(|| anyhow!("sysconf column family not found"))?;
+    db.put_cf(&sysconf_cf, key.name, encode(key, value)?)?;
+    Ok(())
+}
+
+pub fn write_in_batch(db: &DB, batch: &mut rocksdb::WriteBatch, name: &str, value: &SysconfValue) -> Result<()> {
+    let key = declaration(name)?;
+    let sysconf_cf = db
+        .cf_handle("sysconf")
+        .ok_or_else(|| anyhow!("sysconf column family not found"))?;
+    batch.put_cf(&sysconf_cf, key.name, encode(key, value)?);
+    Ok(())
+}
+
+/// Print every declared key with its decoded value, flagging legacy or invalid encodings,
+/// and count the keys sysconf holds that are not declared
+pub fn print_report(db: &DB) -> Result<()> {
+    let sysconf_cf = db
+        .cf_handle("sysconf")
+        .ok_or_else(|| anyhow!("sysconf column family not found"))?;
+
+    let mut problems = 0;
+    for key in KNOWN_KEYS {
+        let Some(raw) = db.get_cf(&sysconf_cf, key.name)? else {
+            println!("{:<34} (not found: {})", key.name, key.description);
+            continue;
+        };
+        match decode(key, &raw) {
+            Ok(Decoded { value: SysconfValue::Json(_), legacy_encoding: None }) => {
+                println!("{:<34} {} bytes of JSON", key.name, raw.len());
+            }
+            Ok(Decoded { value, legacy_encoding: None }) => println!("{:<34} {}", key.name, value),
+            Ok(Decoded { value, legacy_encoding: Some(encoding) }) => {
+                problems += 1;
+                println!("{:<34} {}", key.name, value);
+                println!("   ⚠️  stored as {}, the node expects a {}", encoding, key.encoding.describe());
+            }
+            Err(e) => {
+                problems += 1;
+                println!("{:<34} ❌ {}", key.name, e);
+            }
+        }
+    }
+
+    let undeclared = db
+        .iterator_cf(&sysconf_cf, rocksdb::IteratorMode::Start)
+        .filter_map(|item| item.ok())
+        .filter(|(key, _)| !KNOWN_KEYS.iter().any(|known| known.name.as_bytes() == key.as_ref()))
+        .count();
+    if undeclared > 0 {
+        println!("ℹ️  {} other sysconf keys without a declared encoding", undeclared);
+    }
+    if problems > 0 {
+        println!("⚠️  {} sysconf keys differ from the encoding the node expects", problems);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_height_round_trip_and_legacy_decimal() {
+        let key = declaration(TEMPORAL_HEIGHT).unwrap();
+        let encoded = encode(key, &SysconfValue::Height(39434469)).unwrap();
+        // term_to_binary(39434469): INTEGER_EXT
+        assert_eq!(encoded, [131, 98, 0x02, 0x59, 0xb8, 0xe5]);
+        assert_eq!(decode(key, &encoded).unwrap(), Decoded { value: SysconfValue::Height(39434469), legacy_encoding: None });
+
+        let big = encode(key, &SysconfValue::Height(u64::MAX)).unwrap();
+        assert_eq!(decode(key, &big).unwrap().value, SysconfValue::Height(u64::MAX));
+
+        let decoded = decode(key, b"1234").unwrap();
+        assert_eq!(decoded.value, SysconfValue::Height(1234));
+        assert_eq!(decoded.legacy_encoding, Some("decimal string"));
+
+        assert!(decode(key, b"12a").is_err());
+    }
+
+    #[test]
+    fn test_hash_encoding_checks() {
+        let key = declaration(ROOTED_TIP).unwrap();
+        assert!(encode(key, &SysconfValue::Hash(vec![1u8; 31])).is_err());
+        assert!(encode(key, &SysconfValue::Height(1)).is_err());
+        let hex_tip = hex::encode([7u8; 32]);
+        assert_eq!(decode(key, hex_tip.as_bytes()).unwrap().legacy_encoding, Some("hex string"));
+        assert!(declaration("bogus").is_err());
+    }
+}
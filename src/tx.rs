@@ -0,0 +1,377 @@
+use anyhow::{anyhow, Result};
+use amadeus_fabric_doctor::vecpak::{self, Term as VTerm};
+use eetf::Term;
+use serde_json::json;
+
+/// Argument of a contract call, kept as close to the stored form as possible
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxArg {
+    Binary(Vec<u8>),
+    Integer(i128),
+    Other(String),
+}
+
+impl TxArg {
+    /// Base58 for 48-byte public keys, text when printable, otherwise `hex:<hex>`
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            TxArg::Binary(bytes) if bytes.len() == 48 => json!(bs58::encode(bytes).into_string()),
+            TxArg::Binary(bytes) => match std::str::from_utf8(bytes) {
+                Ok(text) if !text.chars().any(char::is_control) => json!(text),
+                _ => json!(format!("hex:{}", hex::encode(bytes))),
+            },
+            TxArg::Integer(n) => i64::try_from(*n).map(|n| json!(n)).unwrap_or_else(|_| json!(n.to_string())),
+            TxArg::Other(text) => json!(text),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TxAction {
+    /// Contract name, or the Base58 public key of a deployed contract
+    pub contract: String,
+    pub function: String,
+    pub args: Vec<TxArg>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DecodedTx {
+    /// Hash stored in the packed transaction
+    pub hash: Vec<u8>,
+    /// blake3 of the encoded transaction body
+    pub computed_hash: Vec<u8>,
+    pub signer: Vec<u8>,
+    pub nonce: u64,
+    pub actions: Vec<TxAction>,
+}
+
+impl DecodedTx {
+    pub fn hash_matches(&self) -> bool {
+        self.hash == self.computed_hash
+    }
+
+    /// Public keys receiving something from this transaction (first argument of `transfer` calls)
+    pub fn receivers(&self) -> Vec<Vec<u8>> {
+        let mut receivers = Vec::new();
+        for action in &self.actions {
+            if action.function == "transfer" {
+                if let Some(TxArg::Binary(receiver)) = action.args.first() {
+                    if receiver.len() == 48 && !receivers.contains(receiver) {
+                        receivers.push(receiver.clone());
+                    }
+                }
+            }
+        }
+        receivers
+    }
+}
+
+/// `<public key 48>:<nonce:020>`, the key layout of tx_account_nonce and tx_receiver_nonce
+pub fn nonce_index_key(public_key: &[u8], nonce: u64) -> Vec<u8> {
+    let mut key = public_key.to_vec();
+    key.extend_from_slice(format!(":{:020}", nonce).as_bytes());
+    key
+}
+
+/// Nonce parsed from a nonce index key, and the tx hash it points at
+pub type NonceIndexEntry = (Option<u64>, Vec<u8>);
+
+/// Total number of entries of a nonce index (tx_account_nonce or tx_receiver_nonce) for
+/// `public_key`, and the newest `limit` of them as (nonce, tx hash)
+pub fn nonce_index_entries(
+    db: &rocksdb::DB,
+    cf_name: &str,
+    public_key: &[u8],
+    limit: usize,
+) -> Result<(usize, Vec<NonceIndexEntry>)> {
+    let cf = db.cf_handle(cf_name).ok_or_else(|| anyhow!("{} CF not found", cf_name))?;
+    let mut prefix = public_key.to_vec();
+    prefix.push(b':');
+    let upper = crate::prune::prefix_end(&prefix);
+    let mode = match &upper {
+        Some(upper) => rocksdb::IteratorMode::From(upper, rocksdb::Direction::Reverse),
+        None => rocksdb::IteratorMode::End,
+    };
+
+    let mut count = 0usize;
+    let mut entries = Vec::new();
+    for item in db.iterator_cf(&cf, mode) {
+        let (key, value) = item?;
+        if upper.as_deref().is_some_and(|upper| key.as_ref() >= upper) {
+            continue;
+        }
+        if !key.starts_with(&prefix) {
+            break;
+        }
+        count += 1;
+        if entries.len() < limit {
+            let nonce = std::str::from_utf8(&key[prefix.len()..]).ok().and_then(|text| text.parse().ok());
+            entries.push((nonce, value.to_vec()));
+        }
+    }
+    Ok((count, entries))
+}
+
+/// Packed transactions of an entry, in entry order (ETF or vecpak)
+pub fn entry_txs(entry_data: &[u8]) -> Result<Vec<Vec<u8>>> {
+    if let Ok(entry_term) = vecpak::decode_term_from_slice(entry_data) {
+        let txs = vecpak_get(&entry_term, b"txs").ok_or_else(|| anyhow!("vecpak entry has no txs field"))?;
+        let VTerm::List(items) = txs else {
+            return Err(anyhow!("vecpak entry txs is not a list"));
+        };
+        return Ok(items
+            .iter()
+            .map(|item| match item {
+                VTerm::Binary(bytes) => bytes.clone(),
+                other => {
+                    let mut buf = Vec::new();
+                    vecpak::encode_term(&mut buf, other.clone());
+                    buf
+                }
+            })
+            .collect());
+    }
+
+    let term = Term::decode(entry_data)?;
+    let txs = etf_get(&term, "txs").ok_or_else(|| anyhow!("ETF entry has no txs field"))?;
+    match txs {
+        Term::List(list) => list
+            .elements
+            .iter()
+            .map(|item| match item {
+                Term::Binary(binary) => Ok(binary.bytes.clone()),
+                other => Err(anyhow!("unexpected tx term in entry: {}", other)),
+            })
+            .collect(),
+        Term::ByteList(_) => Ok(Vec::new()),
+        other => Err(anyhow!("ETF entry txs is not a list: {}", other)),
+    }
+}
+
+/// Decode a packed transaction (ETF `%{tx_encoded, hash, signature}` or vecpak `{tx, hash, signature}`)
+pub fn decode_tx(packed: &[u8]) -> Result<DecodedTx> {
+    if let Ok(term) = vecpak::decode_term_from_slice(packed) {
+        return decode_vecpak_tx(&term);
+    }
+    decode_etf_tx(&Term::decode(packed)?)
+}
+
+fn decode_vecpak_tx(term: &VTerm) -> Result<DecodedTx> {
+    let hash = vecpak_binary(term, b"hash").unwrap_or_default();
+
+    let (body_bytes, body) = match vecpak_get(term, b"tx") {
+        Some(VTerm::Binary(bytes)) => {
+            let body = vecpak::decode_term_from_slice(bytes).map_err(|e| anyhow!("invalid vecpak tx body: {}", e))?;
+            (bytes.clone(), body)
+        }
+        Some(body @ VTerm::PropList(_)) => {
+            let mut buf = Vec::new();
+            vecpak::encode_term(&mut buf, body.clone());
+            (buf, body.clone())
+        }
+        _ => return Err(anyhow!("vecpak tx has no tx body")),
+    };
+
+    let signer = vecpak_binary(&body, b"signer").ok_or_else(|| anyhow!("vecpak tx has no signer"))?;
+    let nonce = match vecpak_get(&body, b"nonce") {
+        Some(VTerm::VarInt(n)) if *n >= 0 => *n as u64,
+        _ => return Err(anyhow!("vecpak tx has no valid nonce")),
+    };
+
+    let mut actions = Vec::new();
+    let action_terms: Vec<&VTerm> = match (vecpak_get(&body, b"actions"), vecpak_get(&body, b"action")) {
+        (Some(VTerm::List(list)), _) => list.iter().collect(),
+        (_, Some(action)) => vec![action],
+        _ => Vec::new(),
+    };
+    for action in action_terms {
+        let args = match vecpak_get(action, b"args") {
+            Some(VTerm::List(list)) => list
+                .iter()
+                .map(|arg| match arg {
+                    VTerm::Binary(bytes) => TxArg::Binary(bytes.clone()),
+                    VTerm::VarInt(n) => TxArg::Integer(*n),
+                    other => TxArg::Other(format!("{:?}", other)),
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        actions.push(TxAction {
+            contract: vecpak_binary(action, b"contract").map(|b| contract_name(&b)).unwrap_or_default(),
+            function: vecpak_string(action, b"function").unwrap_or_default(),
+            args,
+        });
+    }
+
+    Ok(DecodedTx {
+        hash,
+        computed_hash: blake3::hash(&body_bytes).as_bytes().to_vec(),
+        signer,
+        nonce,
+        actions,
+    })
+}
+
+fn decode_etf_tx(term: &Term) -> Result<DecodedTx> {
+    let hash = etf_binary(term, "hash").unwrap_or_default();
+    let body_bytes = etf_binary(term, "tx_encoded").ok_or_else(|| anyhow!("ETF tx has no tx_encoded"))?;
+    let body = Term::decode(&body_bytes[..])?;
+
+    let signer = etf_binary(&body, "signer").ok_or_else(|| anyhow!("ETF tx has no signer"))?;
+    let nonce = etf_get(&body, "nonce")
+        .and_then(etf_u64)
+        .ok_or_else(|| anyhow!("ETF tx has no valid nonce"))?;
+
+    let mut actions = Vec::new();
+    if let Some(Term::List(list)) = etf_get(&body, "actions") {
+        for action in &list.elements {
+            let args = match etf_get(action, "args") {
+                Some(Term::List(args)) => args
+                    .elements
+                    .iter()
+                    .map(|arg| match arg {
+                        Term::Binary(binary) => TxArg::Binary(binary.bytes.clone()),
+                        Term::FixInteger(int) => TxArg::Integer(int.value as i128),
+                        Term::BigInteger(big) => match i128::try_from(big.value.clone()) {
+                            Ok(value) => TxArg::Integer(value),
+                            Err(_) => TxArg::Other(big.value.to_string()),
+                        },
+                        other => TxArg::Other(other.to_string()),
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            };
+            actions.push(TxAction {
+                contract: etf_binary(action, "contract").map(|b| contract_name(&b)).unwrap_or_default(),
+                function: etf_binary(action, "function").map(|b| String::from_utf8_lossy(&b).to_string()).unwrap_or_default(),
+                args,
+            });
+        }
+    }
+
+    Ok(DecodedTx {
+        hash,
+        computed_hash: blake3::hash(&body_bytes).as_bytes().to_vec(),
+        signer,
+        nonce,
+        actions,
+    })
+}
+
+fn contract_name(contract: &[u8]) -> String {
+    if contract.len() == 48 {
+        bs58::encode(contract).into_string()
+    } else {
+        String::from_utf8_lossy(contract).to_string()
+    }
+}
+
+// Field access helpers for ETF maps with atom keys and vecpak proplists with binary keys
+
+pub fn etf_get<'a>(term: &'a Term, key: &str) -> Option<&'a Term> {
+    if let Term::Map(map) = term {
+        for (k, v) in &map.map {
+            match k {
+                Term::Atom(atom) if atom.name == key => return Some(v),
+                Term::Binary(binary) if binary.bytes == key.as_bytes() => return Some(v),
+                _ => {}
+            }
+        }
+    }
+    None
+}
+
+pub fn etf_binary(term: &Term, key: &str) -> Option<Vec<u8>> {
+    match etf_get(term, key)? {
+        Term::Binary(binary) => Some(binary.bytes.clone()),
+        Term::Atom(atom) => Some(atom.name.as_bytes().to_vec()),
+        _ => None,
+    }
+}
+
+pub fn etf_u64(term: &Term) -> Option<u64> {
+    match term {
+        Term::FixInteger(int) if int.value >= 0 => Some(int.value as u64),
+        Term::BigInteger(big) => big.value.clone().try_into().ok(),
+        _ => None,
+    }
+}
+
+pub fn vecpak_get<'a>(term: &'a VTerm, key: &[u8]) -> Option<&'a VTerm> {
+    if let VTerm::PropList(props) = term {
+        for (k, v) in props {
+            if let VTerm::Binary(k) = k {
+                if k == key {
+                    return Some(v);
+                }
+            }
+        }
+    }
+    None
+}
+
+pub fn vecpak_binary(term: &VTerm, key: &[u8]) -> Option<Vec<u8>> {
+    match vecpak_get(term, key)? {
+        VTerm::Binary(bytes) => Some(bytes.clone()),
+        _ => None,
+    }
+}
+
+fn vecpak_string(term: &VTerm, key: &[u8]) -> Option<String> {
+    vecpak_binary(term, key).map(|b| String::from_utf8_lossy(&b).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vecpak_bin(s: &[u8]) -> VTerm {
+        VTerm::Binary(s.to_vec())
+    }
+
+    #[test]
+    fn test_decode_vecpak_tx_and_receivers() {
+        let receiver = vec![3u8; 48];
+        let body = VTerm::PropList(vec![
+            (vecpak_bin(b"signer"), VTerm::Binary(vec![1u8; 48])),
+            (vecpak_bin(b"nonce"), VTerm::VarInt(1_700_000_000_000_000_000)),
+            (
+                vecpak_bin(b"action"),
+                VTerm::PropList(vec![
+                    (vecpak_bin(b"op"), vecpak_bin(b"call")),
+                    (vecpak_bin(b"contract"), vecpak_bin(b"Coin")),
+                    (vecpak_bin(b"function"), vecpak_bin(b"transfer")),
+                    (
+                        vecpak_bin(b"args"),
+                        VTerm::List(vec![VTerm::Binary(receiver.clone()), vecpak_bin(b"100"), vecpak_bin(b"AMA")]),
+                    ),
+                ]),
+            ),
+        ]);
+        let mut body_bytes = Vec::new();
+        vecpak::encode_term(&mut body_bytes, body);
+        let hash = blake3::hash(&body_bytes).as_bytes().to_vec();
+
+        let packed_term = VTerm::PropList(vec![
+            (vecpak_bin(b"hash"), VTerm::Binary(hash.clone())),
+            (vecpak_bin(b"signature"), VTerm::Binary(vec![9u8; 96])),
+            (vecpak_bin(b"tx"), VTerm::Binary(body_bytes)),
+        ]);
+        let mut packed = Vec::new();
+        vecpak::encode_term(&mut packed, packed_term);
+
+        let tx = decode_tx(&packed).unwrap();
+        assert!(tx.hash_matches());
+        assert_eq!(tx.nonce, 1_700_000_000_000_000_000);
+        assert_eq!(tx.actions[0].contract, "Coin");
+        assert_eq!(tx.actions[0].function, "transfer");
+        assert_eq!(tx.receivers(), vec![receiver]);
+    }
+
+    #[test]
+    fn test_nonce_index_key_layout() {
+        let key = nonce_index_key(&[1u8; 48], 42);
+        assert_eq!(key.len(), 48 + 1 + 20);
+        assert!(key.ends_with(b":00000000000000000042"));
+    }
+}
@@ -0,0 +1,199 @@
+use anyhow::{anyhow, Result};
+use amadeus_fabric_doctor::vecpak::{self, Term as VTerm};
+use rocksdb::DB;
+use serde_json::json;
+
+use crate::tx::{self, DecodedTx};
+
+/// A transaction found through its tx CF pointer
+struct ResolvedTx {
+    entry_hash: Vec<u8>,
+    entry_data: Vec<u8>,
+    position: usize,
+    packed: Vec<u8>,
+    decoded: DecodedTx,
+    /// `(start, size)` of the transaction inside the entry, when the pointer holds one that checks out
+    slice: Option<(usize, usize)>,
+}
+
+/// `(start, size)` stored after the entry hash: two big-endian u32, or a term with `index_start`
+/// and `index_size`
+fn pointer_location(rest: &[u8]) -> Option<(usize, usize)> {
+    if rest.len() == 8 {
+        let start = u32::from_be_bytes(rest[..4].try_into().ok()?) as usize;
+        let size = u32::from_be_bytes(rest[4..].try_into().ok()?) as usize;
+        return Some((start, size));
+    }
+    let fields = match vecpak::decode_term_from_slice(rest) {
+        Ok(term) => (
+            tx::vecpak_get(&term, b"index_start").cloned(),
+            tx::vecpak_get(&term, b"index_size").cloned(),
+        ),
+        Err(_) => {
+            let term = eetf::Term::decode(rest).ok()?;
+            let start = tx::etf_get(&term, "index_start").and_then(tx::etf_u64)?;
+            let size = tx::etf_get(&term, "index_size").and_then(tx::etf_u64)?;
+            return Some((start as usize, size as usize));
+        }
+    };
+    match fields {
+        (Some(VTerm::VarInt(start)), Some(VTerm::VarInt(size))) => Some((usize::try_from(start).ok()?, usize::try_from(size).ok()?)),
+        _ => None,
+    }
+}
+
+/// Follow the tx CF pointer to the entry and find the transaction in it: by the stored slice when
+/// it decodes to this hash, otherwise by matching the hashes of the entry's transactions
+fn resolve_tx(db: &DB, tx_hash: &[u8]) -> Result<ResolvedTx> {
+    let tx_cf = db.cf_handle("tx").ok_or_else(|| anyhow!("tx CF not found"))?;
+    let entry_cf = db
+        .cf_handle("entry")
+        .ok_or_else(|| anyhow!("entry CF not found"))?;
+
+    let pointer = db
+        .get_cf(&tx_cf, tx_hash)?
+        .ok_or_else(|| anyhow!("Transaction {} not found in the tx CF", hex::encode(tx_hash)))?;
+    if pointer.len() < 32 {
+        return Err(anyhow!("tx pointer of {} is only {} bytes", hex::encode(tx_hash), pointer.len()));
+    }
+    let entry_hash = pointer[..32].to_vec();
+    let entry_data = db
+        .get_cf(&entry_cf, &entry_hash)?
+        .ok_or_else(|| anyhow!("Entry {} holding transaction {} is missing", hex::encode(&entry_hash), hex::encode(tx_hash)))?;
+
+    let txs = tx::entry_txs(&entry_data)?;
+    let slice = pointer_location(&pointer[32..]).filter(|(start, size)| {
+        entry_data
+            .get(*start..start.saturating_add(*size))
+            .and_then(|bytes| tx::decode_tx(bytes).ok())
+            .is_some_and(|decoded| decoded.hash == tx_hash)
+    });
+
+    for (position, packed) in txs.into_iter().enumerate() {
+        let Ok(decoded) = tx::decode_tx(&packed) else {
+            continue;
+        };
+        if decoded.hash == tx_hash {
+            return Ok(ResolvedTx { entry_hash, entry_data, position, packed, decoded, slice });
+        }
+    }
+    Err(anyhow!("Entry {} does not contain transaction {}", hex::encode(&entry_hash), hex::encode(tx_hash)))
+}
+
+/// Show a transaction found through the tx CF: its entry, height and distance to the tips, the
+/// decoded calls, the hash check and the nonce index entries pointing at it
+pub fn show_tx(db: &DB, tx_hash: &[u8]) -> Result<()> {
+    let resolved = resolve_tx(db, tx_hash)?;
+    let decoded = &resolved.decoded;
+    let (height, slot, _) = crate::parse_entry_metadata(&resolved.entry_data)?;
+    let (temporal_height, rooted_height) = crate::extract_heights(db)?;
+
+    println!("💸 Transaction {}", hex::encode(tx_hash));
+    println!("{}", "=".repeat(80));
+    println!("{:<20} {}", "Entry:", hex::encode(&resolved.entry_hash));
+    println!("{:<20} {} (slot {})", "Height:", height, slot);
+    println!(
+        "{:<20} {}, {}",
+        "Tip distance:",
+        if height <= temporal_height {
+            format!("{} below the temporal tip", temporal_height - height)
+        } else {
+            format!("{} above the temporal tip", height - temporal_height)
+        },
+        if height <= rooted_height { "rooted".to_string() } else { format!("{} above the rooted tip", height - rooted_height) }
+    );
+    println!(
+        "{:<20} {}{}",
+        "Position:",
+        resolved.position,
+        match resolved.slice {
+            Some((start, size)) => format!(" (bytes {}..{} of the entry)", start, start + size),
+            None => " (found by hash, the pointer holds no usable slice)".to_string(),
+        }
+    );
+    println!("{:<20} {} bytes", "Size:", resolved.packed.len());
+    println!("{:<20} {}", "Signer:", bs58::encode(&decoded.signer).into_string());
+    println!("{:<20} {}", "Nonce:", decoded.nonce);
+    println!(
+        "{:<20} {} {}",
+        "Computed hash:",
+        hex::encode(&decoded.computed_hash),
+        if decoded.hash_matches() { "✅" } else { "❌ differs from the stored hash" }
+    );
+
+    println!("\n📞 Actions ({})", decoded.actions.len());
+    for action in &decoded.actions {
+        let args: Vec<_> = action.args.iter().map(tx::TxArg::to_json).collect();
+        println!("   {}.{}({})", action.contract, action.function, json!(args));
+    }
+
+    println!("\n🧾 Nonce indexes");
+    let account_key = tx::nonce_index_key(&decoded.signer, decoded.nonce);
+    let mut indexed = vec![("tx_account_nonce", bs58::encode(&decoded.signer).into_string(), account_key)];
+    for receiver in decoded.receivers() {
+        let key = tx::nonce_index_key(&receiver, decoded.nonce);
+        indexed.push(("tx_receiver_nonce", bs58::encode(&receiver).into_string(), key));
+    }
+    for (cf_name, account, key) in indexed {
+        let cf = db.cf_handle(cf_name).ok_or_else(|| anyhow!("{} CF not found", cf_name))?;
+        let status = match db.get_cf(&cf, &key)? {
+            Some(value) if value == tx_hash => "✅",
+            Some(_) => "⚠️  points at another transaction",
+            None => "❌ missing",
+        };
+        println!("   {:<18} {} nonce {} {}", cf_name, account, decoded.nonce, status);
+    }
+    println!("{}", "=".repeat(80));
+    Ok(())
+}
+
+/// List the newest transactions sent (tx_account_nonce) or received (tx_receiver_nonce) by an
+/// account, each resolved to its entry height and calls
+pub fn list_account_txs(db: &DB, account: &str, received: bool, limit: usize) -> Result<()> {
+    let public_key = crate::account::parse_public_key(account)?;
+    let cf_name = if received { "tx_receiver_nonce" } else { "tx_account_nonce" };
+    let (count, entries) = tx::nonce_index_entries(db, cf_name, &public_key, limit)?;
+
+    println!("💸 Transactions {} {} ({} in {}, newest {} shown)", if received { "received by" } else { "sent by" }, account, count, cf_name, entries.len());
+    for (nonce, tx_hash) in entries {
+        let nonce = nonce.map(|nonce| nonce.to_string()).unwrap_or_else(|| "?".to_string());
+        match resolve_tx(db, &tx_hash) {
+            Ok(resolved) => {
+                let height = crate::parse_entry_metadata(&resolved.entry_data).map(|(height, _, _)| height).unwrap_or(0);
+                let calls: Vec<String> = resolved
+                    .decoded
+                    .actions
+                    .iter()
+                    .map(|action| {
+                        let args: Vec<_> = action.args.iter().map(tx::TxArg::to_json).collect();
+                        format!("{}.{}({})", action.contract, action.function, json!(args))
+                    })
+                    .collect();
+                println!("   nonce {:>20}  height {:>12}  {}  {}", nonce, height, hex::encode(&tx_hash), calls.join(" "));
+            }
+            Err(e) => println!("   nonce {:>20}  {}  ❌ {}", nonce, hex::encode(&tx_hash), e),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pointer_location() {
+        assert_eq!(pointer_location(&[0, 0, 1, 0, 0, 0, 0, 64]), Some((256, 64)));
+        assert_eq!(pointer_location(&[]), None);
+
+        let mut term = Vec::new();
+        vecpak::encode_term(
+            &mut term,
+            VTerm::PropList(vec![
+                (VTerm::Binary(b"index_start".to_vec()), VTerm::VarInt(10)),
+                (VTerm::Binary(b"index_size".to_vec()), VTerm::VarInt(20)),
+            ]),
+        );
+        assert_eq!(pointer_location(&term), Some((10, 20)));
+    }
+}
@@ -0,0 +1,136 @@
+use anyhow::{anyhow, Result};
+use rocksdb::DB;
+
+use crate::entry_index;
+use crate::sysconf;
+
+/// Problem found between two consecutive entries of the walk
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum LinkIssue {
+    /// The previous entry is lower, but not by exactly one
+    HeightGap { missing: u64 },
+    /// The previous entry is not lower than the current one
+    NonMonotonic { prev_height: u64 },
+}
+
+/// Compare the header height of an entry with the height of its predecessor
+fn check_heights(height: u64, prev_height: u64) -> Option<LinkIssue> {
+    if prev_height >= height {
+        Some(LinkIssue::NonMonotonic { prev_height })
+    } else if prev_height + 1 < height {
+        Some(LinkIssue::HeightGap { missing: height - prev_height - 1 })
+    } else {
+        None
+    }
+}
+
+/// Whether the by_height index lists `hash` at `height`, under its binary or (older databases) hex-encoded hash
+fn indexed_at(db: &DB, entry_meta_cf: &impl rocksdb::AsColumnFamilyRef, height: u64, hash: &[u8]) -> Result<bool> {
+    Ok(db.get_cf(entry_meta_cf, entry_index::height_index_key(height, hash))?.is_some()
+        || db.get_cf(entry_meta_cf, entry_index::height_index_key(height, hex::encode(hash).as_bytes()))?.is_some())
+}
+
+#[derive(Debug, Default)]
+struct WalkStats {
+    steps: u64,
+    height_gaps: u64,
+    missing_heights: u64,
+    non_monotonic: u64,
+    index_mismatches: u64,
+    broken_links: u64,
+}
+
+/// Follow prev_hash from `start` (default: the temporal tip) down to `to_height` (default: genesis),
+/// printing every entry and reporting height gaps, broken links, non-decreasing heights and entries
+/// the by_height index doesn't list at their header height
+pub fn walk_chain(db: &DB, start: Option<Vec<u8>>, to_height: Option<u64>, issues_only: bool) -> Result<()> {
+    let entry_cf = db
+        .cf_handle("entry")
+        .ok_or_else(|| anyhow!("entry CF not found"))?;
+    let entry_meta_cf = db
+        .cf_handle("entry_meta")
+        .ok_or_else(|| anyhow!("entry_meta CF not found"))?;
+
+    let mut hash = match start {
+        Some(hash) => hash,
+        None => sysconf::read_hash(db, sysconf::TEMPORAL_TIP)?.ok_or_else(|| anyhow!("temporal_tip not found in sysconf"))?,
+    };
+    let to_height = to_height.unwrap_or(0);
+    println!("🚶 Walking the chain from {} down to height {}", hex::encode(&hash), to_height);
+    if !issues_only {
+        println!("{:>12} {:>12} {:<64} {:>8}", "height", "slot", "hash", "size");
+    }
+
+    let mut stats = WalkStats::default();
+    let mut entry_data = db
+        .get_cf(&entry_cf, &hash)?
+        .ok_or_else(|| anyhow!("Start entry {} not found", hex::encode(&hash)))?;
+
+    let stop_reason = loop {
+        let (height, slot, _) = crate::parse_entry_metadata(&entry_data)?;
+        stats.steps += 1;
+        if !issues_only {
+            println!("{:>12} {:>12} {:<64} {:>8}", height, slot, hex::encode(&hash), entry_data.len());
+        }
+
+        if !indexed_at(db, &entry_meta_cf, height, &hash)? {
+            stats.index_mismatches += 1;
+            println!("   ⚠️  by_height doesn't list {} at its header height {}", hex::encode(&hash), height);
+        }
+
+        if height <= to_height {
+            break format!("reached height {}", height);
+        }
+        let Some(prev_hash) = crate::entry_header_binary(&entry_data, "prev_hash") else {
+            break format!("entry at height {} has no prev_hash", height);
+        };
+        if prev_hash.iter().all(|byte| *byte == 0) {
+            break format!("reached genesis at height {}", height);
+        }
+
+        let Some(prev_data) = db.get_cf(&entry_cf, &prev_hash)? else {
+            stats.broken_links += 1;
+            println!("   ❌ Broken link at height {}: prev {} is missing", height, hex::encode(&prev_hash));
+            break format!("broken link below height {}", height);
+        };
+        let (prev_height, _, _) = crate::parse_entry_metadata(&prev_data)?;
+        match check_heights(height, prev_height) {
+            Some(LinkIssue::HeightGap { missing }) => {
+                stats.height_gaps += 1;
+                stats.missing_heights += missing;
+                println!("   ⚠️  Height gap: {} -> {} ({} heights missing)", height, prev_height, missing);
+            }
+            Some(LinkIssue::NonMonotonic { prev_height }) => {
+                stats.non_monotonic += 1;
+                println!("   ❌ Height not decreasing: {} -> {}", height, prev_height);
+                // A cycle would never end; stop at the first height that doesn't go down
+                break format!("height did not decrease below {}", height);
+            }
+            None => {}
+        }
+
+        hash = prev_hash;
+        entry_data = prev_data;
+    };
+
+    println!("✅ Walk finished ({}):", stop_reason);
+    println!("   - Entries visited: {}", stats.steps);
+    println!("   - Height gaps: {} ({} heights missing)", stats.height_gaps, stats.missing_heights);
+    println!("   - Broken links: {}", stats.broken_links);
+    println!("   - Non-decreasing heights: {}", stats.non_monotonic);
+    println!("   - by_height disagreements: {}", stats.index_mismatches);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_heights() {
+        assert_eq!(check_heights(10, 9), None);
+        assert_eq!(check_heights(10, 7), Some(LinkIssue::HeightGap { missing: 2 }));
+        assert_eq!(check_heights(10, 10), Some(LinkIssue::NonMonotonic { prev_height: 10 }));
+        assert_eq!(check_heights(10, 12), Some(LinkIssue::NonMonotonic { prev_height: 12 }));
+    }
+}